@@ -0,0 +1,46 @@
+// the dedicated server, hosts lan matches without rendering anything
+
+use amethyst::{config::Config, utils::application_root_dir};
+
+use minions::{
+    cli::{Options, USAGE},
+    config::MinionsConfig,
+    network::{self, MatchSetup},
+    server::Server,
+};
+
+// matches the server hosts at once unless told otherwise
+const MAX_MATCHES: usize = 4;
+
+fn main() -> amethyst::Result<()> {
+    amethyst::start_logger(Default::default());
+    let options = Options::from_args()?;
+    if options.help {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let app_root = application_root_dir()?;
+
+    let mut minions_config = MinionsConfig::load("config/config.ron")?;
+    minions_config.select_arena(
+        options.map.as_deref(),
+        options.seed,
+        options.players,
+        options.size,
+    )?;
+    minions_config.set_difficulty(options.difficulty);
+    let setup = MatchSetup {
+        seed: options.seed.unwrap_or_default(),
+        arena: minions_config.arena,
+        players: minions_config.players,
+        input_delay: network::INPUT_DELAY,
+    };
+
+    let mut server = Server::new(
+        options.host.unwrap_or(network::DEFAULT_PORT),
+        setup,
+        &app_root.join("assets").join("units"),
+        options.matches.unwrap_or(MAX_MATCHES),
+    )?;
+    server.run()
+}
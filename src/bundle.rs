@@ -1,5 +1,12 @@
-use crate::systems::{BorderSystem, CameraSystem, HoverSystemDesc};
+use crate::components::UnitPrefabData;
+use crate::systems::{
+    add_profiled, profiler, AlertSystem, BookmarkSystemDesc, BorderSystem, CameraSystem,
+    CursorSystem, DebugSystemDesc, EditorSystemDesc, FogSystem, HoverSystemDesc, HudSystem,
+    MessageSystem, PerformanceSystemDesc, PlaybackControlSystemDesc, PositionSystem,
+    ScreenshotSystemDesc, SelectionSystemDesc, TileLabelSystem,
+};
 use amethyst::{
+    assets::PrefabLoaderSystemDesc,
     controls::MouseFocusUpdateSystemDesc,
     core::bundle::SystemBundle,
     ecs::{DispatcherBuilder, World},
@@ -7,7 +14,20 @@ use amethyst::{
     prelude::SystemDesc,
 };
 use std::default::Default;
-pub struct MinionsBundle;
+// what the application is started for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Play,
+    Edit,
+    Replay,
+    // a picture of the arena for --overview
+    Overview,
+}
+
+pub struct MinionsBundle {
+    // the editor and replays replace some systems of the game
+    pub mode: Mode,
+}
 
 impl<'a, 'b> SystemBundle<'a, 'b> for MinionsBundle {
     fn build(
@@ -15,18 +35,138 @@ impl<'a, 'b> SystemBundle<'a, 'b> for MinionsBundle {
         world: &mut World,
         builder: &mut DispatcherBuilder<'a, 'b>,
     ) -> Result<(), Error> {
-        builder.add(BorderSystem::default(), "camera_border", &[]);
-        builder.add(
+        // every system is timed for the performance overlay and traces
+        let profiler = profiler(world);
+        add_profiled(
+            builder,
+            &profiler,
+            PrefabLoaderSystemDesc::<UnitPrefabData>::default().build(world),
+            "unit_prefab_loader",
+            &[],
+        );
+        add_profiled(
+            builder,
+            &profiler,
+            BorderSystem::default(),
+            "camera_border",
+            &[],
+        );
+        add_profiled(
+            builder,
+            &profiler,
+            BookmarkSystemDesc::default().build(world),
+            "bookmark_system",
+            &["input_system"],
+        );
+        add_profiled(
+            builder,
+            &profiler,
             CameraSystem,
             "camera_system",
-            &["input_system", "camera_border"],
+            &["input_system", "camera_border", "bookmark_system"],
         );
-        builder.add(
+        add_profiled(
+            builder,
+            &profiler,
             MouseFocusUpdateSystemDesc::default().build(world),
             "mouse_focus",
             &["camera_system"],
         );
-        builder.add(HoverSystemDesc::default().build(world), "hover_system", &[]);
+        add_profiled(
+            builder,
+            &profiler,
+            CursorSystem::default(),
+            "cursor_system",
+            &["input_system"],
+        );
+        add_profiled(
+            builder,
+            &profiler,
+            HoverSystemDesc::default().build(world),
+            "hover_system",
+            &["cursor_system"],
+        );
+        add_profiled(
+            builder,
+            &profiler,
+            DebugSystemDesc::default().build(world),
+            "debug_system",
+            &["hover_system"],
+        );
+        add_profiled(
+            builder,
+            &profiler,
+            TileLabelSystem::default(),
+            "tile_label_system",
+            &["hover_system"],
+        );
+        add_profiled(
+            builder,
+            &profiler,
+            PerformanceSystemDesc::default().build(world),
+            "performance_system",
+            &[],
+        );
+        add_profiled(
+            builder,
+            &profiler,
+            ScreenshotSystemDesc::default().build(world),
+            "screenshot_system",
+            &["camera_system"],
+        );
+        match self.mode {
+            Mode::Edit => {
+                add_profiled(
+                    builder,
+                    &profiler,
+                    EditorSystemDesc::default().build(world),
+                    "editor_system",
+                    &["input_system", "hover_system"],
+                );
+                return Ok(());
+            }
+            Mode::Play => add_profiled(
+                builder,
+                &profiler,
+                SelectionSystemDesc::default().build(world),
+                "selection_system",
+                &["input_system", "hover_system"],
+            ),
+            // the viewer of a replay can't give orders
+            Mode::Replay => add_profiled(
+                builder,
+                &profiler,
+                PlaybackControlSystemDesc::default().build(world),
+                "playback_control_system",
+                &["input_system"],
+            ),
+            // nothing but the arena is shown
+            Mode::Overview => return Ok(()),
+        }
+        // orders, production and movement run on the fixed tick of the simulation
+        add_profiled(builder, &profiler, PositionSystem, "position_system", &[]);
+        add_profiled(
+            builder,
+            &profiler,
+            FogSystem::default(),
+            "fog_system",
+            &["hover_system", "position_system"],
+        );
+        add_profiled(builder, &profiler, HudSystem::default(), "hud_system", &[]);
+        add_profiled(
+            builder,
+            &profiler,
+            MessageSystem::default(),
+            "message_system",
+            &[],
+        );
+        add_profiled(
+            builder,
+            &profiler,
+            AlertSystem::default(),
+            "alert_system",
+            &["position_system"],
+        );
         Ok(())
     }
 }
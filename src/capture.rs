@@ -0,0 +1,388 @@
+// screenshots of the running game and overviews of arenas for thumbnails and bug reports.
+// while a capture is requested the frame is drawn a second time into an image of the
+// size of the window, a node of the render graph copies it into a buffer the cpu reads
+
+use amethyst::{
+    ecs::{DispatcherBuilder, World},
+    error::Error,
+    renderer::{
+        bundle::{
+            ImageOptions, OutputColor, RenderOrder, RenderPlan, RenderPlugin, Target, TargetImage,
+            TargetPlanOutputs,
+        },
+        pass::{DrawDebugLinesDesc, DrawShadedDesc, DrawShadedTransparentDesc},
+        rendy::{
+            command::{
+                CommandBuffer, CommandPool, ExecutableState, Family, Fence, MultiShot,
+                PendingState, Queue, QueueType, SimultaneousUse, Submission, Submit, Transfer,
+            },
+            factory::Factory,
+            frame::Frames,
+            graph::{
+                gfx_acquire_barriers, gfx_release_barriers, BufferAccess, GraphContext,
+                ImageAccess, Node, NodeBuffer, NodeBuildError, NodeDesc, NodeImage,
+            },
+            hal::{
+                buffer,
+                command::{BufferImageCopy, ClearDepthStencil, ClearValue},
+                device::OutOfMemory,
+                format::{Aspects, Format},
+                image::{Access, Extent, Kind, Layout, Offset, SubresourceLayers, Usage},
+                pso::PipelineStage,
+            },
+            memory::Download,
+            resource::{Buffer, BufferInfo, Escape},
+        },
+        types::Backend,
+    },
+    ui::DrawUiDesc,
+    utils::application_root_dir,
+    window::ScreenDimensions,
+};
+use image::{DynamicImage, RgbaImage};
+use log::{error, info};
+use std::{
+    collections::VecDeque,
+    fs,
+    path::PathBuf,
+    thread::{self, JoinHandle},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// relative to the application root
+pub const SCREENSHOT_DIR: &str = "screenshots";
+
+const CAPTURE_TARGET: Target = Target::Custom("capture");
+// the same as the window
+const CLEAR_COLOR: [f32; 4] = [0.05, 0.05, 0.05, 1.0];
+
+// the png-file an overview of the arena is saved to, see --overview
+#[derive(Debug)]
+pub struct OverviewFile(pub PathBuf);
+
+// the pictures that are asked for and the threads that still encode earlier ones
+#[derive(Default)]
+pub struct Captures {
+    requested: VecDeque<PathBuf>,
+    writing: Vec<JoinHandle<()>>,
+}
+
+impl Captures {
+    // the next frame that is rendered is saved to the path
+    pub fn request(&mut self, path: PathBuf) {
+        self.requested.push_back(path);
+    }
+
+    pub fn is_requested(&self) -> bool {
+        !self.requested.is_empty()
+    }
+
+    // waits until every picture is written, before the application quits
+    pub fn finish(&mut self) {
+        for writer in self.writing.drain(..) {
+            if writer.join().is_err() {
+                error!("a thread saving a capture panicked");
+            }
+        }
+    }
+
+    // encoding the png takes longer than a frame
+    fn save(&mut self, image: RgbaImage) {
+        let path = match self.requested.pop_front() {
+            Some(path) => path,
+            None => return,
+        };
+        self.writing.push(thread::spawn(move || {
+            let image = DynamicImage::ImageRgba8(image).to_rgb();
+            match image.save(&path) {
+                Ok(()) => info!("saved a capture to {}", path.display()),
+                Err(e) => error!("failed to save the capture {}: {}", path.display(), e),
+            }
+        }));
+    }
+}
+
+// a new file in the screenshot folder named after the current time
+pub fn screenshot_path() -> amethyst::Result<PathBuf> {
+    let directory = application_root_dir()?.join(SCREENSHOT_DIR);
+    fs::create_dir_all(&directory)?;
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Ok(directory.join(format!(
+        "minions-{}-{:03}.png",
+        time.as_secs(),
+        time.subsec_millis()
+    )))
+}
+
+// adds the capture pass to the render graph while a capture is requested
+#[derive(Default, Debug)]
+pub struct RenderCapture {
+    // size of the capture image in the current graph
+    planned: Option<(u32, u32)>,
+}
+
+// the graph has to be rebuilt when a capture is requested, is done or the window resized
+fn wanted_size(world: &World) -> Option<(u32, u32)> {
+    if !world.fetch::<Captures>().is_requested() {
+        return None;
+    }
+    let screen_dimensions = world.fetch::<ScreenDimensions>();
+    Some((
+        screen_dimensions.width() as u32,
+        screen_dimensions.height() as u32,
+    ))
+}
+
+impl<B: Backend> RenderPlugin<B> for RenderCapture {
+    fn on_build<'a, 'b>(
+        &mut self,
+        world: &mut World,
+        _builder: &mut DispatcherBuilder<'a, 'b>,
+    ) -> Result<(), Error> {
+        world.insert(Captures::default());
+        Ok(())
+    }
+
+    fn should_rebuild(&mut self, world: &World) -> bool {
+        self.planned != wanted_size(world)
+    }
+
+    fn on_plan(
+        &mut self,
+        plan: &mut RenderPlan<B>,
+        _factory: &mut Factory<B>,
+        world: &World,
+    ) -> Result<(), Error> {
+        self.planned = wanted_size(world);
+        let (width, height) = match self.planned {
+            Some(size) => size,
+            None => return Ok(()),
+        };
+
+        // the same passes as the window, drawn into an image
+        let kind = Kind::D2(width, height, 1, 1);
+        plan.define_pass(
+            CAPTURE_TARGET,
+            TargetPlanOutputs {
+                colors: vec![OutputColor::Image(ImageOptions {
+                    kind,
+                    levels: 1,
+                    format: Format::Rgba8Srgb,
+                    clear: Some(ClearValue::Color(CLEAR_COLOR.into())),
+                })],
+                depth: Some(ImageOptions {
+                    kind,
+                    levels: 1,
+                    format: Format::D32Sfloat,
+                    clear: Some(ClearValue::DepthStencil(ClearDepthStencil(0.0, 0))),
+                }),
+            },
+        )?;
+        plan.extend_target(CAPTURE_TARGET, |ctx| {
+            ctx.add(RenderOrder::Opaque, DrawShadedDesc::new().builder())?;
+            ctx.add(
+                RenderOrder::Transparent,
+                DrawShadedTransparentDesc::new().builder(),
+            )?;
+            ctx.add(
+                RenderOrder::BeforeTransparent,
+                DrawDebugLinesDesc::new().builder(),
+            )?;
+            ctx.add(RenderOrder::Overlay, DrawUiDesc::new().builder())?;
+            Ok(())
+        });
+
+        // the copy waits for the capture pass, the window for the copy
+        plan.extend_target(Target::Main, move |ctx| {
+            let pass = ctx.get_node(CAPTURE_TARGET)?;
+            let image = ctx.get_image(TargetImage::Color(CAPTURE_TARGET, 0))?;
+            let node = ctx.graph().add_node(
+                CaptureNodeDesc { width, height }
+                    .builder()
+                    .with_image(image)
+                    .with_dependency(pass),
+            );
+            ctx.add_dep(node);
+            Ok(())
+        });
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct CaptureNodeDesc {
+    width: u32,
+    height: u32,
+}
+
+impl<B: Backend> NodeDesc<B, World> for CaptureNodeDesc {
+    type Node = CaptureNode<B>;
+
+    fn buffers(&self) -> Vec<BufferAccess> {
+        Vec::new()
+    }
+
+    fn images(&self) -> Vec<ImageAccess> {
+        vec![ImageAccess {
+            access: Access::TRANSFER_READ,
+            usage: Usage::TRANSFER_SRC,
+            layout: Layout::TransferSrcOptimal,
+            stages: PipelineStage::TRANSFER,
+        }]
+    }
+
+    fn build<'a>(
+        self,
+        ctx: &GraphContext<B>,
+        factory: &mut Factory<B>,
+        family: &mut Family<B>,
+        _queue: usize,
+        _aux: &World,
+        _buffers: Vec<NodeBuffer>,
+        images: Vec<NodeImage>,
+    ) -> Result<CaptureNode<B>, NodeBuildError> {
+        let image = &images[0];
+        let target = ctx
+            .get_image(image.id)
+            .expect("the image of the capture pass exists");
+        // rgba, 4 bytes a pixel without padding between the rows
+        let size = u64::from(self.width) * u64::from(self.height) * 4;
+        let buffer = factory
+            .create_buffer(
+                BufferInfo {
+                    size,
+                    usage: buffer::Usage::TRANSFER_DST,
+                },
+                Download,
+            )
+            .map_err(|e| {
+                error!("failed to create the buffer of a capture: {:?}", e);
+                NodeBuildError::OutOfMemory(OutOfMemory::Device)
+            })?;
+
+        // the copy is the same every time, it is recorded once
+        let mut pool = factory
+            .create_command_pool(family)
+            .map_err(NodeBuildError::OutOfMemory)?;
+        let initial = pool.allocate_buffers(1).remove(0);
+        let mut recording = initial.begin(MultiShot(SimultaneousUse), ());
+        let mut encoder = recording.encoder();
+        gfx_acquire_barriers(ctx, None, Some(image), &mut encoder);
+        unsafe {
+            encoder.copy_image_to_buffer(
+                target.raw(),
+                image.layout,
+                buffer.raw(),
+                Some(BufferImageCopy {
+                    buffer_offset: 0,
+                    buffer_width: self.width,
+                    buffer_height: self.height,
+                    image_layers: SubresourceLayers {
+                        aspects: Aspects::COLOR,
+                        level: 0,
+                        layers: 0..1,
+                    },
+                    image_offset: Offset::ZERO,
+                    image_extent: Extent {
+                        width: self.width,
+                        height: self.height,
+                        depth: 1,
+                    },
+                }),
+            );
+        }
+        gfx_release_barriers(ctx, None, Some(image), &mut encoder);
+        let (submit, command_buffer) = recording.finish().submit();
+
+        Ok(CaptureNode {
+            width: self.width,
+            height: self.height,
+            buffer,
+            pool,
+            submit,
+            command_buffer,
+            copying: None,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct CaptureNode<B: Backend> {
+    width: u32,
+    height: u32,
+    buffer: Escape<Buffer<B>>,
+    pool: CommandPool<B, QueueType>,
+    submit: Submit<B, SimultaneousUse>,
+    command_buffer:
+        CommandBuffer<B, QueueType, PendingState<ExecutableState<MultiShot<SimultaneousUse>>>>,
+    // index of the frame the buffer is filled in
+    copying: Option<u64>,
+}
+
+impl<B: Backend> CaptureNode<B> {
+    // the buffer as a picture, once the gpu is done with the copy
+    fn read(&mut self, factory: &Factory<B>) -> Result<RgbaImage, Error> {
+        let size = self.buffer.size();
+        let mut mapped = self
+            .buffer
+            .map(factory.device(), 0..size)
+            .map_err(|e| Error::from_string(format!("failed to map a capture: {:?}", e)))?;
+        let pixels = unsafe { mapped.read::<u8>(factory.device(), 0..size) }
+            .map_err(|e| Error::from_string(format!("failed to read a capture: {:?}", e)))?
+            .to_vec();
+        RgbaImage::from_raw(self.width, self.height, pixels)
+            .ok_or_else(|| Error::from_string("a capture is smaller than its image"))
+    }
+}
+
+impl<B: Backend> Node<B, World> for CaptureNode<B> {
+    type Capability = Transfer;
+
+    fn run<'a>(
+        &mut self,
+        _ctx: &GraphContext<B>,
+        factory: &Factory<B>,
+        queue: &mut Queue<B>,
+        aux: &World,
+        frames: &Frames<B>,
+        waits: &[(&'a B::Semaphore, PipelineStage)],
+        signals: &[&'a B::Semaphore],
+        fence: Option<&mut Fence<B>>,
+    ) {
+        if let Some(frame) = self.copying {
+            // every frame below the bound is complete
+            if frames.complete_upper_bound() > frame {
+                self.copying = None;
+                match self.read(factory) {
+                    Ok(image) => aux.fetch_mut::<Captures>().save(image),
+                    Err(e) => error!("{}", e),
+                }
+            }
+        }
+
+        // only one copy at a time, the buffer is shared
+        let copy = self.copying.is_none() && aux.fetch::<Captures>().is_requested();
+        if copy {
+            self.copying = Some(frames.next().index());
+        }
+        unsafe {
+            queue.submit(
+                Some(
+                    Submission::new()
+                        .wait(waits.iter().cloned())
+                        .submits(Some(&self.submit).filter(|_| copy))
+                        .signal(signals.iter()),
+                ),
+                fence,
+            );
+        }
+    }
+
+    unsafe fn dispose(mut self, factory: &mut Factory<B>, _aux: &World) {
+        self.pool
+            .free_buffers(Some(self.command_buffer.mark_complete()));
+        factory.destroy_command_pool(self.pool);
+    }
+}
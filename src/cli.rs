@@ -0,0 +1,164 @@
+// command-line options of the game and the dedicated server
+
+use amethyst::Error;
+use std::{env, path::PathBuf, str::FromStr};
+
+use crate::config::Difficulty;
+
+pub const USAGE: &str =
+    "usage: minions [--seed <number>] [--players <number>] [--size <width>x<depth>]
+               [--map <file>] [--edit <file>] [--record <file>] [--replay <file>]
+               [--host <port> | --join <address>] [--difficulty <easy|normal|hard>]
+               [--overview <file>]
+       minions-server [--host <port>] [--matches <number>] [--seed <number>] [--players <number>]
+                      [--size <width>x<depth>] [--map <file>] [--difficulty <easy|normal|hard>]
+
+  --seed      generate the arena from this seed instead of using config.ron
+  --players   number of players of the generated arena
+  --size      size of the generated arena in tiles, e.g. 24x16
+  --map       play on the arena of a map-file
+  --edit      open a map-file in the editor, a new one starts from the arena
+  --record    save the orders of the match to a replay-file when the game ends
+  --replay    watch a replay-file
+  --host      host a lan match, it starts once a player joined for every team
+  --join      join the lan match of a host, e.g. 127.0.0.1:7777
+  --matches   number of matches the dedicated server hosts at once
+  --difficulty  how hard the waves of a tower-defense map are
+  --overview  save a picture of the whole arena to a png-file and quit";
+
+#[derive(Debug, Default)]
+pub struct Options {
+    pub seed: Option<u64>,
+    pub players: Option<u8>,
+    pub size: Option<(i16, i16)>,
+    pub map: Option<PathBuf>,
+    pub edit: Option<PathBuf>,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub host: Option<u16>,
+    pub join: Option<String>,
+    pub matches: Option<usize>,
+    pub difficulty: Option<Difficulty>,
+    pub overview: Option<PathBuf>,
+    // the caller prints USAGE and quits
+    pub help: bool,
+}
+
+impl Options {
+    pub fn from_args() -> Result<Self, Error> {
+        Self::parse(env::args().skip(1))
+    }
+
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, Error> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| usage_error(&format!("{} needs a value", arg)))
+            };
+            match arg.as_str() {
+                "--seed" => options.seed = Some(parse_value(&value()?)?),
+                "--players" => {
+                    let players = parse_value(&value()?)?;
+                    if players == 0 {
+                        return Err(usage_error("an arena needs at least one player"));
+                    }
+                    options.players = Some(players);
+                }
+                "--size" => {
+                    let size = value()?;
+                    let mut parts = size.splitn(2, 'x');
+                    match (parts.next(), parts.next()) {
+                        (Some(width), Some(depth)) => {
+                            options.size = Some((parse_value(width)?, parse_value(depth)?))
+                        }
+                        _ => return Err(usage_error(&format!("invalid size {}", size))),
+                    }
+                }
+                "--map" => options.map = Some(PathBuf::from(value()?)),
+                "--edit" => options.edit = Some(PathBuf::from(value()?)),
+                "--record" => options.record = Some(PathBuf::from(value()?)),
+                "--replay" => options.replay = Some(PathBuf::from(value()?)),
+                "--host" => options.host = Some(parse_value(&value()?)?),
+                "--join" => options.join = Some(value()?),
+                "--matches" => options.matches = Some(parse_value(&value()?)?),
+                "--difficulty" => options.difficulty = Some(parse_value(&value()?)?),
+                "--overview" => options.overview = Some(PathBuf::from(value()?)),
+                "--help" | "-h" => options.help = true,
+                _ => return Err(usage_error(&format!("unknown argument {}", arg))),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn parse_value<T: FromStr>(value: &str) -> Result<T, Error> {
+    value
+        .parse()
+        .map_err(|_| usage_error(&format!("invalid value {}", value)))
+}
+
+fn usage_error(message: &str) -> Error {
+    Error::from_string(format!("{}\n\n{}", message, USAGE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, Error> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn no_arguments() {
+        let options = parse(&[]).unwrap();
+        assert_eq!(options.seed, None);
+        assert_eq!(options.map, None);
+    }
+
+    #[test]
+    fn values() {
+        let options = parse(&[
+            "--seed",
+            "42",
+            "--players",
+            "3",
+            "--size",
+            "24x16",
+            "--map",
+            "maps/valley.ron",
+            "--join",
+            "127.0.0.1:7777",
+            "--difficulty",
+            "hard",
+        ])
+        .unwrap();
+        assert_eq!(options.seed, Some(42));
+        assert_eq!(options.players, Some(3));
+        assert_eq!(options.size, Some((24, 16)));
+        assert_eq!(options.map, Some(PathBuf::from("maps/valley.ron")));
+        assert_eq!(options.join, Some("127.0.0.1:7777".to_string()));
+        assert_eq!(options.difficulty, Some(Difficulty::Hard));
+    }
+
+    #[test]
+    fn help() {
+        assert!(parse(&["--help"]).unwrap().help);
+        assert!(parse(&["--seed", "1", "-h"]).unwrap().help);
+        assert!(!parse(&["--seed", "1"]).unwrap().help);
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        assert!(parse(&["--seed"]).is_err());
+        assert!(parse(&["--seed", "many"]).is_err());
+        assert!(parse(&["--players", "300"]).is_err());
+        assert!(parse(&["--players", "0"]).is_err());
+        assert!(parse(&["--size", "24"]).is_err());
+        assert!(parse(&["--size", "24xwide"]).is_err());
+        assert!(parse(&["--difficulty", "impossible"]).is_err());
+        assert!(parse(&["--fast"]).is_err());
+    }
+}
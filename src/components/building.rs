@@ -0,0 +1,113 @@
+use amethyst::ecs::{Component, DenseVecStorage};
+use std::collections::VecDeque;
+
+use crate::grid::Topology;
+
+pub const MAX_QUEUE_LENGTH: usize = 5;
+
+// a building occupies the tiles x..x+width and z..z+depth
+#[derive(Clone, Copy, Debug)]
+pub struct Building {
+    pub x: i16,
+    pub z: i16,
+    pub width: i16,
+    pub depth: i16,
+}
+
+impl Component for Building {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Building {
+    pub fn new(x: i16, z: i16, width: i16, depth: i16) -> Self {
+        Building { x, z, width, depth }
+    }
+
+    pub fn contains(&self, x: i16, z: i16) -> bool {
+        self.x <= x && x < self.x + self.width && self.z <= z && z < self.z + self.depth
+    }
+
+    // tiles with chebyshev-distance `distance` to the footprint
+    pub fn ring(&self, distance: i16) -> Vec<(i16, i16)> {
+        let (left, right) = (self.x - distance, self.x + self.width - 1 + distance);
+        let (bottom, top) = (self.z - distance, self.z + self.depth - 1 + distance);
+        let mut tiles = Vec::new();
+        for x in left..=right {
+            tiles.push((x, bottom));
+            tiles.push((x, top));
+        }
+        for z in bottom + 1..top {
+            tiles.push((left, z));
+            tiles.push((right, z));
+        }
+        tiles
+    }
+
+    // mean of the centers of the footprint, in tiles (see Topology::center)
+    pub fn center(&self, topology: Topology) -> (f32, f32) {
+        let tiles = (self.width * self.depth).max(1) as f32;
+        let (sum_x, sum_z) = (self.x..self.x + self.width)
+            .flat_map(|x| (self.z..self.z + self.depth).map(move |z| (x, z)))
+            .map(|tile| topology.center(tile))
+            .fold((0.0, 0.0), |(sum_x, sum_z), (x, z)| (sum_x + x, sum_z + z));
+        (sum_x / tiles, sum_z / tiles)
+    }
+}
+
+// a building of the defenders of a tower-defense map which shoots at the attackers
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Tower {
+    // ticks until it can shoot again
+    pub cooldown: u32,
+}
+
+impl Component for Tower {
+    type Storage = DenseVecStorage<Self>;
+}
+
+pub struct ProductionQueue {
+    // ids of the units shown on the command card of this building
+    pub options: Vec<String>,
+    pub queue: VecDeque<String>,
+    // ticks spent on the first unit in the queue
+    pub progress: u32,
+    pub rally_point: Option<(i16, i16)>,
+}
+
+impl Component for ProductionQueue {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl ProductionQueue {
+    pub fn new(options: Vec<String>) -> Self {
+        ProductionQueue {
+            options,
+            queue: VecDeque::with_capacity(MAX_QUEUE_LENGTH),
+            progress: 0,
+            rally_point: None,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.queue.len() >= MAX_QUEUE_LENGTH
+    }
+
+    pub fn push(&mut self, unit_type: String) {
+        self.queue.push_back(unit_type);
+    }
+
+    // removes the last queued unit, the caller has to refund it
+    pub fn cancel_last(&mut self) -> Option<String> {
+        let cancelled = self.queue.pop_back();
+        if self.queue.is_empty() {
+            self.progress = 0;
+        }
+        cancelled
+    }
+
+    // removes the unit in production once it is done
+    pub fn finish(&mut self) -> Option<String> {
+        self.progress = 0;
+        self.queue.pop_front()
+    }
+}
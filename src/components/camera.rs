@@ -2,6 +2,7 @@ use amethyst::{
     assets::PrefabData,
     derive::PrefabData,
     ecs::{Component, Entity, NullStorage, VecStorage, WriteStorage},
+    renderer::Camera,
     Error,
 };
 use serde::{Deserialize, Serialize};
@@ -26,3 +27,45 @@ pub struct CameraBorders {
 impl Component for CameraBorders {
     type Storage = VecStorage<Self>;
 }
+
+// vertical field of view of the perspective camera, in radians
+pub const FIELD_OF_VIEW: f32 = 1.027_197_6;
+
+pub fn perspective_camera(aspect: f32) -> Camera {
+    Camera::perspective(aspect, FIELD_OF_VIEW, 0.1)
+}
+
+// half of the height an orthographic camera shows, in units. zooming changes it
+// instead of moving the camera
+#[derive(Debug)]
+pub struct OrthographicExtent(pub f32);
+
+impl OrthographicExtent {
+    pub const MIN: f32 = 2.0;
+    pub const MAX: f32 = 15.0;
+    // the camera stays at this height, high enough for the near plane to stay above
+    // the terrain at the largest extent
+    pub const HEIGHT: f32 = 40.0;
+
+    pub fn camera(&self, aspect: f32) -> Camera {
+        let (width, height) = (self.0 * aspect, self.0);
+        Camera::orthographic(
+            -width,
+            width,
+            -height,
+            height,
+            0.1,
+            2.0 * Self::HEIGHT + 100.0,
+        )
+    }
+}
+
+impl Default for OrthographicExtent {
+    fn default() -> Self {
+        OrthographicExtent(6.0)
+    }
+}
+
+impl Component for OrthographicExtent {
+    type Storage = VecStorage<Self>;
+}
@@ -1,5 +1,14 @@
+mod building;
 mod camera;
+mod unit;
 mod world;
 
-pub use self::camera::{CameraBorders, CameraControlTag};
-pub use self::world::PieceInfo;
+pub use self::building::{Building, ProductionQueue, Tower};
+pub use self::camera::{perspective_camera, CameraBorders, CameraControlTag, OrthographicExtent};
+pub use self::unit::{
+    Attack, AttackCooldown, Attacker, GridPosition, Health, Minion, MoveOrder, Sight, Team,
+    UnitPrefabData, UnitStats,
+};
+pub use self::world::{
+    GoldMine, GridLines, PieceInfo, SimulationId, Terrain, TileMaterials, Visibility,
+};
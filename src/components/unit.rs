@@ -0,0 +1,142 @@
+use amethyst::{
+    assets::{PrefabData, ProgressCounter},
+    derive::PrefabData,
+    ecs::{Component, DenseVecStorage, Entity, VecStorage, WriteStorage},
+    renderer::{
+        formats::{mesh::MeshPrefab, mtl::MaterialPrefab},
+        rendy::mesh::{Normal, Position, Tangent, TexCoord},
+    },
+    Error,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{fixed::Fixed, grid::Topology};
+
+// archetype of a unit, loaded from a prefab in assets/units/
+// units without a material are painted in the color of their team
+#[derive(Debug, Deserialize, Serialize, PrefabData)]
+#[serde(deny_unknown_fields)]
+pub struct UnitPrefabData {
+    pub stats: UnitStats,
+    pub mesh: Option<MeshPrefab<(Vec<Position>, Vec<Normal>, Vec<Tangent>, Vec<TexCoord>)>>,
+    pub material: Option<MaterialPrefab>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PrefabData)]
+#[prefab(Component)]
+#[serde(deny_unknown_fields)]
+pub struct UnitStats {
+    pub name: String,
+    pub health: u32,
+    // tiles per second
+    pub speed: f32,
+    // in tiles
+    pub sight: f32,
+    pub cost: u32,
+    // seconds until the unit leaves the building
+    pub build_time: f32,
+    pub attack: Attack,
+    #[serde(default)]
+    pub abilities: Vec<String>,
+}
+
+impl Component for UnitStats {
+    type Storage = DenseVecStorage<Self>;
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Attack {
+    pub damage: u32,
+    // in tiles
+    pub range: f32,
+    // seconds between two attacks
+    pub cooldown: f32,
+}
+
+pub struct Minion {
+    // id of the unit in the UnitRegistry
+    pub unit_type: String,
+}
+
+impl Component for Minion {
+    type Storage = DenseVecStorage<Self>;
+}
+
+// radius in tiles in which the team of the entity can see
+#[derive(Clone, Copy, Debug)]
+pub struct Sight(pub f32);
+
+impl Component for Sight {
+    type Storage = VecStorage<Self>;
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Team(pub u8);
+
+impl Component for Team {
+    type Storage = VecStorage<Self>;
+}
+
+// position of a unit in the simulation, measured in tiles from the lower left corner
+// of the arena like `Topology::center`, the Transform only follows it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GridPosition {
+    pub x: Fixed,
+    pub z: Fixed,
+}
+
+impl Component for GridPosition {
+    type Storage = VecStorage<Self>;
+}
+
+impl GridPosition {
+    pub fn center(topology: Topology, tile: (i16, i16)) -> Self {
+        let (x, z) = topology.center(tile);
+        GridPosition {
+            x: Fixed::from_f32(x),
+            z: Fixed::from_f32(z),
+        }
+    }
+
+    pub fn tile(self, topology: Topology) -> (i16, i16) {
+        topology.tile_at((self.x.to_f32(), self.z.to_f32()))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Health(pub u32);
+
+impl Component for Health {
+    type Storage = VecStorage<Self>;
+}
+
+// ticks until the unit can attack again, a unit gets it with its first attack
+#[derive(Clone, Copy, Debug)]
+pub struct AttackCooldown(pub u32);
+
+impl Component for AttackCooldown {
+    type Storage = DenseVecStorage<Self>;
+}
+
+// a unit of a wave, it walks to the goal of the tower-defense map
+#[derive(Clone, Copy, Debug)]
+pub struct Attacker {
+    // gold for the team which kills it
+    pub bounty: u32,
+}
+
+impl Component for Attacker {
+    type Storage = DenseVecStorage<Self>;
+}
+
+// tile the unit is walking to, removed on arrival
+#[derive(Clone, Copy, Debug)]
+pub struct MoveOrder {
+    pub x: i16,
+    pub z: i16,
+}
+
+impl Component for MoveOrder {
+    type Storage = DenseVecStorage<Self>;
+}
@@ -1,14 +1,86 @@
 use amethyst::{
     assets::Handle,
-    ecs::{Component, DenseVecStorage},
+    ecs::{Component, DenseVecStorage, NullStorage},
     renderer::Material,
 };
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Terrain {
+    Grass,
+    Rock,
+}
+
+impl Default for Terrain {
+    fn default() -> Self {
+        Terrain::Grass
+    }
+}
+
+impl Terrain {
+    pub const ALL: [Terrain; 2] = [Terrain::Grass, Terrain::Rock];
+
+    pub fn blocks_sight(self) -> bool {
+        match self {
+            Terrain::Grass => false,
+            Terrain::Rock => true,
+        }
+    }
+
+    pub fn is_passable(self) -> bool {
+        match self {
+            Terrain::Grass => true,
+            Terrain::Rock => false,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct GoldMine;
+
+impl Component for GoldMine {
+    type Storage = NullStorage<Self>;
+}
+
+// the entity drawing the lines between the tiles
+#[derive(Default)]
+pub struct GridLines;
+
+impl Component for GridLines {
+    type Storage = NullStorage<Self>;
+}
+
+// identifies buildings and units across runs of the same match, unlike the entity
+// it only depends on the simulation so replays and other clients can refer to it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SimulationId(pub u32);
+
+impl Component for SimulationId {
+    type Storage = DenseVecStorage<Self>;
+}
+
+// how much the local player knows about a tile
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Visibility {
+    Unexplored,
+    // seen before but not in sight of any unit
+    Explored,
+    Visible,
+}
+
+#[derive(Clone)]
+pub struct TileMaterials {
+    pub basic: Handle<Material>,
+    pub hover: Handle<Material>,
+    pub explored: Handle<Material>,
+    pub unexplored: Handle<Material>,
+}
 
 pub struct PieceInfo {
     pub x: i16,
     pub z: i16,
-	pub basic_material: Handle<Material>,
-	pub hover_material: Handle<Material>,
+    pub materials: TileMaterials,
+    pub visibility: Visibility,
 }
 
 impl Component for PieceInfo {
@@ -16,7 +88,21 @@ impl Component for PieceInfo {
 }
 
 impl PieceInfo {
-    pub fn new(x: i16, z: i16, basic_material: Handle<Material>, hover_material: Handle<Material>) -> Self {
-        PieceInfo { x, z, basic_material, hover_material }
+    pub fn new(x: i16, z: i16, materials: TileMaterials) -> Self {
+        PieceInfo {
+            x,
+            z,
+            materials,
+            visibility: Visibility::Visible,
+        }
+    }
+
+    // material of the tile when it is not hovered
+    pub fn material(&self) -> Handle<Material> {
+        match self.visibility {
+            Visibility::Visible => self.materials.basic.clone(),
+            Visibility::Explored => self.materials.explored.clone(),
+            Visibility::Unexplored => self.materials.unexplored.clone(),
+        }
     }
 }
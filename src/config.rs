@@ -1,22 +1,340 @@
 // all config-related stuff
 
+use amethyst::config::Config;
+use log::info;
 use serde::{Deserialize, Serialize};
+use std::{path::Path, str::FromStr};
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+use crate::{components::Terrain, grid::Topology, mapgen};
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct ArenaConfig {
     pub depth: i16,
     pub width: i16,
     pub tile_size: f32,
+    // shape of the tiles, square if not given
+    #[serde(default)]
+    pub topology: Topology,
+    #[serde(default)]
+    pub buildings: Vec<BuildingConfig>,
+    // every tile which is not listed is grass
+    #[serde(default)]
+    pub terrain: Vec<TerrainConfig>,
+    // grayscale image relative to the application root, white is max_height
+    #[serde(default)]
+    pub heightmap: Option<HeightmapConfig>,
+    // every tile which is not listed is at the height of the heightmap or at 0
+    #[serde(default)]
+    pub heights: Vec<HeightConfig>,
+    #[serde(default)]
+    pub gold_mines: Vec<GoldMineConfig>,
+    // rhai-scripts of the scenario relative to the application root, see resources::Scripts
+    #[serde(default)]
+    pub scripts: Vec<String>,
+    // the match never ends without a condition
+    #[serde(default)]
+    pub victory: Vec<VictoryCondition>,
+    // makes the arena a tower-defense map
+    #[serde(default)]
+    pub defense: Option<DefenseConfig>,
+}
+
+// how a match is won, the first condition a team meets decides the match
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum VictoryCondition {
+    // teams without buildings are defeated, the last team left wins.
+    // the units of the other teams destroy buildings in their range
+    DestroyBuildings,
+    // the only team with units on every control point for `seconds` wins
+    HoldControlPoints {
+        points: Vec<(i16, i16)>,
+        seconds: f32,
+    },
+    // every team which is not defeated after `seconds` wins
+    Survive {
+        seconds: f32,
+    },
+    // the first team with `amount` gold wins
+    Gold {
+        amount: u32,
+    },
+    // the defenders win once every wave is beaten and lose when they run out of lives
+    SurviveWaves,
+}
+
+// the attackers walk from the spawn points to the goal, the defenders build towers to stop them
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DefenseConfig {
+    pub spawns: Vec<(i16, i16)>,
+    pub goal: (i16, i16),
+    // attackers which may reach the goal before the defenders lose
+    pub lives: u32,
+    // team of the attackers, every other team defends
+    pub attackers: u8,
+    pub tower: TowerConfig,
+    pub waves: Vec<WaveConfig>,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TowerConfig {
+    pub cost: u32,
+    pub damage: u32,
+    // in tiles
+    pub range: f32,
+    // seconds between two shots
+    pub cooldown: f32,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WaveConfig {
+    // seconds after the previous wave, after the start of the match for the first one
+    pub delay: f32,
+    pub unit_type: String,
+    pub count: u32,
+    // seconds between two attackers
+    pub interval: f32,
+    // gold for the defenders for every attacker they kill
+    #[serde(default)]
+    pub bounty: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Difficulty::Normal
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "easy" => Ok(Difficulty::Easy),
+            "normal" => Ok(Difficulty::Normal),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(format!("unknown difficulty {}", name)),
+        }
+    }
+}
+
+impl Difficulty {
+    // (attackers of a wave, health of the attackers, added health per wave), in percent
+    fn scaling(self) -> (u32, u32, u32) {
+        match self {
+            Difficulty::Easy => (75, 80, 5),
+            Difficulty::Normal => (100, 100, 10),
+            Difficulty::Hard => (150, 120, 20),
+        }
+    }
+
+    pub fn wave_count(self, count: u32) -> u32 {
+        percent(u64::from(count), u64::from(self.scaling().0))
+    }
+
+    // later waves are tougher, `wave` counts from 0
+    pub fn health(self, health: u32, wave: usize) -> u32 {
+        let (_, base, per_wave) = self.scaling();
+        let scaling = u64::from(per_wave)
+            .saturating_mul(wave as u64)
+            .saturating_add(u64::from(base));
+        percent(u64::from(health), scaling)
+    }
+}
+
+// the waves of a long survival map grow until they stop at the largest number
+fn percent(value: u64, percent: u64) -> u32 {
+    let scaled = value.saturating_mul(percent) / 100;
+    scaled.max(1).min(u64::from(u32::MAX)) as u32
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct GoldMineConfig {
+    pub x: i16,
+    pub z: i16,
+    pub amount: u32,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct HeightmapConfig {
+    pub path: String,
+    pub max_height: f32,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct HeightConfig {
+    pub x: i16,
+    pub z: i16,
+    pub height: f32,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct TerrainConfig {
+    pub x: i16,
+    pub z: i16,
+    pub terrain: Terrain,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BuildingConfig {
+    pub x: i16,
+    pub z: i16,
+    pub width: i16,
+    pub depth: i16,
+    pub team: u8,
+    // ids of the producible units, every registered unit if empty
+    #[serde(default)]
+    pub produces: Vec<String>,
+    // minions::BUILDING_HEALTH if not given
+    #[serde(default)]
+    pub health: Option<u32>,
 }
 
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub struct CameraConfig {
     pub camera_tilt: f32,
     pub movement_factor: f32,
+    #[serde(default)]
+    pub projection: CameraProjection,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum CameraProjection {
+    Perspective,
+    // parallel lines stay parallel, isometric with a camera_tilt of about -0.62
+    Orthographic,
+}
+
+impl Default for CameraProjection {
+    fn default() -> Self {
+        CameraProjection::Perspective
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PlayerConfig {
+    pub teams: u8,
+    pub starting_gold: u32,
+}
+
+// parameters of the arena-generator, used instead of the arena when a seed is given
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct GeneratorConfig {
+    pub width: i16,
+    pub depth: i16,
+    pub players: u8,
+    // fraction of rock tiles
+    pub rock: f32,
+    pub max_height: f32,
+    // gold mines per tile
+    pub gold_density: f32,
+    pub gold_amount: u32,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        GeneratorConfig {
+            width: 24,
+            depth: 16,
+            players: 2,
+            rock: 0.2,
+            max_height: 2.0,
+            gold_density: 0.01,
+            gold_amount: 1500,
+        }
+    }
 }
 
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub struct MinionsConfig {
     pub arena: ArenaConfig,
     pub camera: CameraConfig,
+    #[serde(default)]
+    pub players: PlayerConfig,
+    #[serde(default)]
+    pub generator: GeneratorConfig,
+}
+
+impl MinionsConfig {
+    // overrides the difficulty of a tower-defense arena
+    pub fn set_difficulty(&mut self, difficulty: Option<Difficulty>) {
+        if let (Some(difficulty), Some(defense)) = (difficulty, &mut self.arena.defense) {
+            defense.difficulty = difficulty;
+        }
+    }
+
+    // a map-file or a seed replace the arena of the config
+    pub fn select_arena(
+        &mut self,
+        map: Option<&Path>,
+        seed: Option<u64>,
+        players: Option<u8>,
+        size: Option<(i16, i16)>,
+    ) -> amethyst::Result<()> {
+        if let Some(path) = map {
+            self.arena = ArenaConfig::load(path)?;
+            let spawns = self
+                .arena
+                .buildings
+                .iter()
+                .map(|building| building.team + 1)
+                .max()
+                .unwrap_or(0);
+            self.players.teams = self.players.teams.max(spawns);
+            info!("loaded the map {:?}", path);
+        } else if let Some(seed) = seed {
+            if let Some(players) = players {
+                self.generator.players = players;
+            }
+            if let Some((width, depth)) = size {
+                self.generator.width = width;
+                self.generator.depth = depth;
+            }
+            self.arena = mapgen::generate(
+                seed,
+                &self.generator,
+                self.arena.tile_size,
+                self.arena.topology,
+            );
+            // the generator makes an arena for one player at least
+            self.players.teams = self.generator.players.max(1);
+            info!(
+                "generated a {}x{} arena for {} players from seed {}",
+                self.arena.width, self.arena.depth, self.players.teams, seed
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn difficulty_scales_the_waves() {
+        assert_eq!(Difficulty::Normal.wave_count(10), 10);
+        assert_eq!(Difficulty::Hard.wave_count(10), 15);
+        // a wave has one attacker at least
+        assert_eq!(Difficulty::Easy.wave_count(1), 1);
+        assert_eq!(Difficulty::Normal.health(100, 0), 100);
+        assert_eq!(Difficulty::Hard.health(100, 2), 160);
+        assert_eq!(Difficulty::Easy.health(1, 0), 1);
+    }
+
+    #[test]
+    fn endless_waves_saturate() {
+        assert_eq!(Difficulty::Hard.wave_count(u32::MAX), u32::MAX);
+        assert_eq!(Difficulty::Normal.health(1000, 1_000_000_000), u32::MAX);
+        assert_eq!(Difficulty::Hard.health(u32::MAX, usize::MAX), u32::MAX);
+    }
 }
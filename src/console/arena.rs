@@ -0,0 +1,27 @@
+use amethyst::{prelude::*, Error};
+
+use super::ConsoleCommand;
+use crate::resources::{FogOfWar, LocalPlayer};
+
+pub fn commands() -> Vec<Box<dyn ConsoleCommand>> {
+    vec![Box::new(Reveal)]
+}
+
+// the local player sees the whole arena for the rest of the match
+struct Reveal;
+
+impl ConsoleCommand for Reveal {
+    fn name(&self) -> &'static str {
+        "reveal"
+    }
+
+    fn cheat(&self) -> bool {
+        true
+    }
+
+    fn run(&self, world: &mut World, _arguments: &[&str]) -> Result<String, Error> {
+        let team = world.read_resource::<LocalPlayer>().team;
+        world.write_resource::<FogOfWar>().reveal_all(team);
+        Ok("the fog is gone".to_string())
+    }
+}
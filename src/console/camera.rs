@@ -0,0 +1,83 @@
+use amethyst::{core::Transform, ecs::Join, prelude::*, Error};
+
+use super::{argument, ConsoleCommand};
+use crate::{
+    components::CameraControlTag,
+    config::{ArenaConfig, CameraConfig},
+    minions::WorldBorders,
+    picking::terrain_height,
+    resources::{CameraBookmarks, CameraFollow, TerrainMap},
+    systems::looking_at,
+};
+
+pub fn commands() -> Vec<Box<dyn ConsoleCommand>> {
+    vec![Box::new(SetMovementFactor), Box::new(Teleport)]
+}
+
+struct SetMovementFactor;
+
+impl ConsoleCommand for SetMovementFactor {
+    fn name(&self) -> &'static str {
+        "set camera.movement_factor"
+    }
+
+    fn arguments(&self) -> &'static str {
+        "<speed>"
+    }
+
+    fn run(&self, world: &mut World, arguments: &[&str]) -> Result<String, Error> {
+        let factor: f32 = argument(arguments, 0, "speed")?;
+        world.write_resource::<CameraConfig>().movement_factor = factor;
+        Ok(format!("camera.movement_factor is {}", factor))
+    }
+}
+
+// the camera looks at the center of a tile at once
+struct Teleport;
+
+impl ConsoleCommand for Teleport {
+    fn name(&self) -> &'static str {
+        "tp camera"
+    }
+
+    fn arguments(&self) -> &'static str {
+        "<x> <z>"
+    }
+
+    fn run(&self, world: &mut World, arguments: &[&str]) -> Result<String, Error> {
+        let (x, z): (i16, i16) = (argument(arguments, 0, "x")?, argument(arguments, 1, "z")?);
+        let (center_x, center_z, height) = {
+            let world_borders = world.read_resource::<WorldBorders>();
+            let tile_size = world.read_resource::<ArenaConfig>().tile_size;
+            if !world_borders.contains_tile(x, z) {
+                return Err(Error::from_string(format!(
+                    "{} {} is outside of the arena",
+                    x, z
+                )));
+            }
+            let (center_x, center_z) = world_borders.tile_center(x, z, tile_size);
+            let height = terrain_height(
+                &world.read_resource::<TerrainMap>(),
+                &world_borders,
+                tile_size,
+                center_x,
+                center_z,
+            );
+            (center_x, center_z, height)
+        };
+
+        let tags = world.read_storage::<CameraControlTag>();
+        let mut transforms = world.write_storage::<Transform>();
+        let transform = match (&tags, &mut transforms).join().next() {
+            Some((_, transform)) => transform,
+            None => return Err(Error::from_string("there is no camera")),
+        };
+        let translation = looking_at(transform, center_x, height, center_z)
+            .ok_or_else(|| Error::from_string("the camera doesn't look down"))?;
+        *transform.translation_mut() = translation;
+        // a glide or following the selection would move it away again
+        world.write_resource::<CameraBookmarks>().target = None;
+        world.write_resource::<CameraFollow>().active = false;
+        Ok(String::new())
+    }
+}
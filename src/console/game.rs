@@ -0,0 +1,62 @@
+use amethyst::{prelude::*, Error};
+
+use super::{argument, ConsoleCommand};
+use crate::resources::{LocalPlayer, SimulationSpeed, Treasury};
+
+pub fn commands() -> Vec<Box<dyn ConsoleCommand>> {
+    vec![Box::new(Give), Box::new(Speed)]
+}
+
+// gold for the local player
+struct Give;
+
+impl ConsoleCommand for Give {
+    fn name(&self) -> &'static str {
+        "give"
+    }
+
+    fn arguments(&self) -> &'static str {
+        "<gold>"
+    }
+
+    fn cheat(&self) -> bool {
+        true
+    }
+
+    fn run(&self, world: &mut World, arguments: &[&str]) -> Result<String, Error> {
+        let gold: u32 = argument(arguments, 0, "gold")?;
+        let team = world.read_resource::<LocalPlayer>().team;
+        let mut treasury = world.write_resource::<Treasury>();
+        treasury.earn(team, gold);
+        Ok(format!("team {} has {} gold", team, treasury.gold(team)))
+    }
+}
+
+// ticks of the simulation per fixed update
+struct Speed;
+
+impl ConsoleCommand for Speed {
+    fn name(&self) -> &'static str {
+        "speed"
+    }
+
+    fn arguments(&self) -> &'static str {
+        "<factor>"
+    }
+
+    fn cheat(&self) -> bool {
+        true
+    }
+
+    fn run(&self, world: &mut World, arguments: &[&str]) -> Result<String, Error> {
+        let factor: f32 = argument(arguments, 0, "factor")?;
+        if !(0.0..=SimulationSpeed::MAX).contains(&factor) {
+            return Err(Error::from_string(format!(
+                "the factor has to be between 0 and {}",
+                SimulationSpeed::MAX
+            )));
+        }
+        world.insert(SimulationSpeed(factor));
+        Ok(format!("the match runs at {}x", factor))
+    }
+}
@@ -0,0 +1,176 @@
+// The developer console: commands typed in the game or listed in the startup file.
+// every module contributes its own commands, see the submodules
+
+mod arena;
+mod camera;
+mod game;
+mod state;
+mod units;
+
+pub use self::state::{DeveloperConsole, CONSOLE_KEY};
+
+use amethyst::{prelude::*, utils::application_root_dir, Error};
+use log::{error, info};
+use std::{fs, str::FromStr};
+
+use crate::resources::{Lockstep, ReplayPlayer, ReplayRecorder};
+
+// relative to the application root, a command per line and # starts a comment
+pub const STARTUP_PATH: &str = "config/console.txt";
+
+// lines the console keeps
+const MAX_OUTPUT: usize = 100;
+
+pub trait ConsoleCommand: Send + Sync {
+    // the words which start the command, e.g. "tp camera"
+    fn name(&self) -> &'static str;
+
+    // the words after the name, shown by the help
+    fn arguments(&self) -> &'static str {
+        ""
+    }
+
+    // cheats change the simulation, which the other players of a lan match
+    // and a replay of the match wouldn't see
+    fn cheat(&self) -> bool {
+        false
+    }
+
+    // the answer is shown in the console
+    fn run(&self, world: &mut World, arguments: &[&str]) -> Result<String, Error>;
+}
+
+// the commands and what was entered, a resource so it survives closing the console
+pub struct Console {
+    commands: Vec<Box<dyn ConsoleCommand>>,
+    // entered lines and answers, oldest first
+    pub output: Vec<String>,
+    // entered lines, oldest first
+    pub history: Vec<String>,
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Console {
+            commands: camera::commands()
+                .into_iter()
+                .chain(arena::commands())
+                .chain(units::commands())
+                .chain(game::commands())
+                .collect(),
+            output: Vec::new(),
+            history: Vec::new(),
+        }
+    }
+}
+
+impl Console {
+    pub fn execute(&mut self, world: &mut World, line: &str) {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return;
+        }
+        self.history.push(line.to_string());
+        self.print(format!("> {}", line));
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words == ["help"] {
+            let mut usages: Vec<_> = self
+                .commands
+                .iter()
+                .map(|command| usage(command.as_ref()))
+                .collect();
+            usages.sort();
+            for usage in usages {
+                self.print(usage);
+            }
+            return;
+        }
+        // the longest name wins, e.g. "kill selected" over a "kill"
+        let command = self
+            .commands
+            .iter()
+            .filter(|command| {
+                let name: Vec<&str> = command.name().split(' ').collect();
+                words.starts_with(&name)
+            })
+            .max_by_key(|command| command.name().len());
+        let answer = match command {
+            None => Err(Error::from_string(format!(
+                "unknown command {}, help lists them",
+                words[0]
+            ))),
+            Some(command) if command.cheat() && !cheats_allowed(world) => Err(Error::from_string(
+                "cheats are off in lan matches and replays",
+            )),
+            Some(command) => {
+                let skipped = command.name().split(' ').count();
+                command.run(world, &words[skipped..]).map_err(|e| {
+                    Error::from_string(format!("{}, usage: {}", e, usage(command.as_ref())))
+                })
+            }
+        };
+        match answer {
+            Ok(answer) if answer.is_empty() => {}
+            Ok(answer) => self.print(answer),
+            Err(e) => self.print(e.to_string()),
+        }
+    }
+
+    fn print(&mut self, line: String) {
+        info!("console: {}", line);
+        self.output.push(line);
+        if self.output.len() > MAX_OUTPUT {
+            self.output.remove(0);
+        }
+    }
+}
+
+// runs a line with the console of the world
+pub fn execute(world: &mut World, line: &str) {
+    let mut console = world.remove::<Console>().unwrap_or_default();
+    console.execute(world, line);
+    world.insert(console);
+}
+
+// the commands of the startup file, a missing file has none
+pub fn run_startup(world: &mut World) {
+    let path = match application_root_dir() {
+        Ok(root) => root.join(STARTUP_PATH),
+        Err(e) => {
+            error!("failed to find the console startup file: {}", e);
+            return;
+        }
+    };
+    let script = match fs::read_to_string(&path) {
+        Ok(script) => script,
+        Err(_) => return,
+    };
+    info!("running the console commands of {:?}", path);
+    for line in script.lines() {
+        execute(world, line);
+    }
+}
+
+// e.g. tp camera <x> <z>
+fn usage(command: &dyn ConsoleCommand) -> String {
+    format!("{} {}", command.name(), command.arguments())
+        .trim_end()
+        .to_string()
+}
+
+fn cheats_allowed(world: &World) -> bool {
+    world.try_fetch::<Lockstep>().is_none()
+        && world.try_fetch::<ReplayPlayer>().is_none()
+        && world.try_fetch::<ReplayRecorder>().is_none()
+}
+
+// the argument at the index, the name describes it in the error
+pub fn argument<T: FromStr>(arguments: &[&str], index: usize, name: &str) -> Result<T, Error> {
+    let value = arguments
+        .get(index)
+        .ok_or_else(|| Error::from_string(format!("{} is missing", name)))?;
+    value
+        .parse()
+        .map_err(|_| Error::from_string(format!("invalid {} {}", name, value)))
+}
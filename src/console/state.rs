@@ -0,0 +1,182 @@
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    ecs::Entity,
+    input::{is_close_requested, Bindings, InputHandler, VirtualKeyCode},
+    prelude::*,
+    ui::{get_default_font, Anchor, FontAsset, LineMode, UiText, UiTransform},
+    winit::{ElementState, Event, KeyboardInput, WindowEvent},
+};
+use log::error;
+use std::mem;
+
+use super::{execute, Console};
+use crate::input::MovementBindingTypes;
+
+// opens and closes the console, like the options menu it can't be rebound
+pub const CONSOLE_KEY: VirtualKeyCode = VirtualKeyCode::Grave;
+
+const FONT_SIZE: f32 = 16.0;
+// in pixels
+const ROW_HEIGHT: f32 = 20.0;
+const WIDTH: f32 = 900.0;
+const MARGIN: f32 = 20.0;
+// lines of output above the input line
+const ROWS: usize = 16;
+
+const TEXT_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+const INPUT_COLOR: [f32; 4] = [0.9, 0.8, 0.1, 1.0];
+
+#[derive(Default)]
+pub struct DeveloperConsole {
+    // the bindings are put aside while the console is open so typing doesn't move the camera
+    bindings: Bindings<MovementBindingTypes>,
+    rows: Vec<Entity>,
+    input_row: Option<Entity>,
+    input: String,
+    // how far up the history the arrow keys went, None for a new line
+    recalled: Option<usize>,
+}
+
+impl SimpleState for DeveloperConsole {
+    // the match waits while the console is open, like with the options menu
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+        self.bindings = mem::take(
+            &mut world
+                .write_resource::<InputHandler<MovementBindingTypes>>()
+                .bindings,
+        );
+        let font = get_default_font(
+            &world.read_resource::<Loader>(),
+            &world.read_resource::<AssetStorage<FontAsset>>(),
+        );
+        // the input line is at the bottom, the output above it
+        let mut create_text = |id: String, row: usize, color: [f32; 4]| {
+            let transform = UiTransform::new(
+                id,
+                Anchor::BottomLeft,
+                Anchor::BottomLeft,
+                MARGIN,
+                MARGIN + row as f32 * ROW_HEIGHT,
+                1.0,
+                WIDTH,
+                ROW_HEIGHT,
+            );
+            let text = UiText::new(
+                font.clone(),
+                String::new(),
+                color,
+                FONT_SIZE,
+                LineMode::Single,
+                Anchor::MiddleLeft,
+            );
+            world.create_entity().with(transform).with(text).build()
+        };
+        self.input_row = Some(create_text("console_input".to_string(), 0, INPUT_COLOR));
+        self.rows = (1..=ROWS)
+            .map(|row| create_text(format!("console_{}", row), row, TEXT_COLOR))
+            .collect();
+        if world.try_fetch::<Console>().is_none() {
+            world.insert(Console::default());
+        }
+        self.refresh(world);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        data.world
+            .write_resource::<InputHandler<MovementBindingTypes>>()
+            .bindings = mem::take(&mut self.bindings);
+        let entities: Vec<_> = self.rows.drain(..).chain(self.input_row.take()).collect();
+        if let Err(e) = data.world.delete_entities(&entities) {
+            error!("failed to remove the console: {}", e);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        let world = data.world;
+        let event = match event {
+            StateEvent::Window(event) => event,
+            _ => return Trans::None,
+        };
+        if is_close_requested(&event) {
+            return Trans::Quit;
+        }
+        match event {
+            // the key which opened the console arrives as a character as well
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter(character),
+                ..
+            } if !character.is_control() && character != '`' => {
+                self.input.push(character);
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(key),
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => match key {
+                VirtualKeyCode::Escape | CONSOLE_KEY => return Trans::Pop,
+                VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+                    let line = mem::take(&mut self.input);
+                    self.recalled = None;
+                    execute(world, &line);
+                }
+                VirtualKeyCode::Back => {
+                    self.input.pop();
+                }
+                VirtualKeyCode::Up | VirtualKeyCode::Down => self.recall(world, key),
+                _ => return Trans::None,
+            },
+            _ => return Trans::None,
+        }
+        self.refresh(world);
+        Trans::None
+    }
+}
+
+impl DeveloperConsole {
+    // puts an earlier line into the input
+    fn recall(&mut self, world: &World, key: VirtualKeyCode) {
+        let console = world.read_resource::<Console>();
+        let history = &console.history;
+        if history.is_empty() {
+            return;
+        }
+        self.recalled = match (key, self.recalled) {
+            (VirtualKeyCode::Up, None) => Some(history.len() - 1),
+            (VirtualKeyCode::Up, Some(index)) => Some(index.saturating_sub(1)),
+            (_, Some(index)) if index + 1 < history.len() => Some(index + 1),
+            _ => None,
+        };
+        self.input = self
+            .recalled
+            .map(|index| history[index].clone())
+            .unwrap_or_default();
+    }
+
+    // writes the input and the last lines of the output into the rows
+    fn refresh(&self, world: &World) {
+        let console = world.read_resource::<Console>();
+        let mut texts = world.write_storage::<UiText>();
+        if let Some(text) = self.input_row.and_then(|entity| texts.get_mut(entity)) {
+            text.text = format!("> {}_", self.input);
+        }
+        let mut output = console.output.iter().rev();
+        for entity in &self.rows {
+            if let Some(text) = texts.get_mut(*entity) {
+                text.text = output.next().cloned().unwrap_or_default();
+            }
+        }
+    }
+}
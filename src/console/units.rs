@@ -0,0 +1,105 @@
+use amethyst::{ecs::LazyUpdate, prelude::*, Error};
+
+use super::{argument, ConsoleCommand};
+use crate::{
+    components::GridPosition,
+    config::ArenaConfig,
+    minions::WorldBorders,
+    resources::{MinionAssets, Selection, Simulation, TerrainMap, UnitRegistry},
+    systems::unit_transform,
+};
+
+pub fn commands() -> Vec<Box<dyn ConsoleCommand>> {
+    vec![Box::new(Spawn), Box::new(KillSelected)]
+}
+
+struct Spawn;
+
+impl ConsoleCommand for Spawn {
+    fn name(&self) -> &'static str {
+        "spawn"
+    }
+
+    fn arguments(&self) -> &'static str {
+        "<unit> <x> <z> <team>"
+    }
+
+    fn cheat(&self) -> bool {
+        true
+    }
+
+    fn run(&self, world: &mut World, arguments: &[&str]) -> Result<String, Error> {
+        let unit_type: String = argument(arguments, 0, "unit")?;
+        let (x, z): (i16, i16) = (argument(arguments, 1, "x")?, argument(arguments, 2, "z")?);
+        let team: u8 = argument(arguments, 3, "team")?;
+        {
+            let world_borders = world.read_resource::<WorldBorders>();
+            if !world_borders.contains_tile(x, z) {
+                return Err(Error::from_string(format!(
+                    "{} {} is outside of the arena",
+                    x, z
+                )));
+            }
+            let unit_registry = world.read_resource::<UnitRegistry>();
+            let (lazy, entities) = (world.read_resource::<LazyUpdate>(), world.entities());
+            let builder = unit_registry.spawn(
+                &lazy,
+                &entities,
+                &unit_type,
+                team,
+                GridPosition::center(world_borders.topology, (x, z)),
+                world
+                    .try_fetch::<MinionAssets>()
+                    .map(|minion_assets| minion_assets.team_material(team)),
+            );
+            let builder = builder.ok_or_else(|| {
+                Error::from_string(format!(
+                    "unknown unit {}, the units are {}",
+                    unit_type,
+                    unit_registry.ids().join(", ")
+                ))
+            })?;
+            let transform = unit_transform(
+                &world_borders,
+                &world.read_resource::<TerrainMap>(),
+                world.read_resource::<ArenaConfig>().tile_size,
+                (x, z),
+            );
+            builder
+                .with(transform)
+                .with(world.write_resource::<Simulation>().new_id())
+                .build();
+        }
+        // the unit exists before the next tick like the ones of the ProductionSystem
+        world.maintain();
+        Ok(format!("spawned a {} of team {}", unit_type, team))
+    }
+}
+
+struct KillSelected;
+
+impl ConsoleCommand for KillSelected {
+    fn name(&self) -> &'static str {
+        "kill selected"
+    }
+
+    fn cheat(&self) -> bool {
+        true
+    }
+
+    fn run(&self, world: &mut World, _arguments: &[&str]) -> Result<String, Error> {
+        let selected: Vec<_> = {
+            let entities = world.entities();
+            let selection = world.read_resource::<Selection>();
+            selection
+                .entities
+                .iter()
+                .copied()
+                .filter(|&entity| entities.is_alive(entity))
+                .collect()
+        };
+        world.delete_entities(&selected)?;
+        world.write_resource::<Selection>().clear();
+        Ok(format!("killed {} entities", selected.len()))
+    }
+}
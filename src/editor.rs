@@ -0,0 +1,20 @@
+// Edit the arena and save it as a map-file
+
+use amethyst::prelude::*;
+use log::info;
+
+use crate::{minions::initialize_arena, resources::MapEditor};
+
+#[derive(Default)]
+pub struct Editor {}
+
+impl SimpleState for Editor {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        initialize_arena(data.world);
+        info!(
+            "editing {:?}: 1-6 select the tool, +/- resize the brush, \
+             ctrl+z/y undo and redo, ctrl+s saves",
+            data.world.read_resource::<MapEditor>().path
+        );
+    }
+}
@@ -0,0 +1,212 @@
+// fixed-point numbers with 16 fractional bits, the simulation uses them
+// instead of floats so every machine computes exactly the same state
+
+use std::{
+    cmp::Ordering,
+    ops::{Add, AddAssign, Div, Mul, Sub},
+};
+
+const FRACTION_BITS: u32 = 16;
+const ONE: i64 = 1 << FRACTION_BITS;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed(i32);
+
+impl Fixed {
+    // integers beyond ±32767 saturate
+    pub fn from_int(value: i32) -> Self {
+        Fixed::saturate((value as i64) << FRACTION_BITS)
+    }
+
+    // rounds to the nearest representable number
+    pub fn from_f32(value: f32) -> Self {
+        Fixed::saturate((value * ONE as f32).round() as i64)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / ONE as f32
+    }
+
+    // length of the vector (x, z)
+    pub fn hypot(x: Fixed, z: Fixed) -> Fixed {
+        let (x, z) = (x.0 as i64, z.0 as i64);
+        // each square is at most 2^62, so the sum fits
+        Fixed::saturate(isqrt((x * x) as u64 + (z * z) as u64) as i64)
+    }
+
+    // results beyond the range stick to its ends instead of wrapping around,
+    // so an overflow behaves the same in debug and release builds
+    fn saturate(value: i64) -> Self {
+        Fixed(value.max(i32::MIN as i64).min(i32::MAX as i64) as i32)
+    }
+}
+
+// largest integer whose square is at most n
+fn isqrt(n: u64) -> u64 {
+    if n < 2 {
+        return n;
+    }
+    let mut root = (n as f64).sqrt() as u64;
+    // the float is only a first guess, the result must not depend on its rounding
+    while root.checked_mul(root).map_or(true, |square| square > n) {
+        root -= 1;
+    }
+    while (root + 1)
+        .checked_mul(root + 1)
+        .map_or(false, |square| square <= n)
+    {
+        root += 1;
+    }
+    root
+}
+
+impl Add for Fixed {
+    type Output = Fixed;
+
+    fn add(self, other: Fixed) -> Fixed {
+        Fixed(self.0.saturating_add(other.0))
+    }
+}
+
+impl AddAssign for Fixed {
+    fn add_assign(&mut self, other: Fixed) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+
+    fn sub(self, other: Fixed) -> Fixed {
+        Fixed(self.0.saturating_sub(other.0))
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Fixed;
+
+    fn mul(self, other: Fixed) -> Fixed {
+        Fixed::saturate((self.0 as i64 * other.0 as i64) >> FRACTION_BITS)
+    }
+}
+
+impl Div for Fixed {
+    type Output = Fixed;
+
+    // a zero divisor gives the end of the range the dividend points to, so a
+    // mistake in the simulation can't crash every client of a match at once
+    fn div(self, other: Fixed) -> Fixed {
+        if other.0 == 0 {
+            return match self.0.cmp(&0) {
+                Ordering::Less => Fixed(i32::MIN),
+                Ordering::Equal => Fixed(0),
+                Ordering::Greater => Fixed(i32::MAX),
+            };
+        }
+        Fixed::saturate(((self.0 as i64) << FRACTION_BITS) / other.0 as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multiply_and_divide_negatives() {
+        let (three, two) = (Fixed::from_int(3), Fixed::from_int(2));
+        assert_eq!(Fixed::from_int(-3) * two, Fixed::from_int(-6));
+        assert_eq!(
+            Fixed::from_int(-3) * Fixed::from_int(-2),
+            Fixed::from_int(6)
+        );
+        assert_eq!(
+            Fixed::from_f32(1.5) * Fixed::from_f32(-0.5),
+            Fixed::from_f32(-0.75)
+        );
+        assert_eq!(Fixed::from_int(-7) / two, Fixed::from_f32(-3.5));
+        assert_eq!(
+            Fixed::from_int(7) / Fixed::from_int(-2),
+            Fixed::from_f32(-3.5)
+        );
+        assert_eq!(three / three, Fixed::from_int(1));
+    }
+
+    #[test]
+    fn rounding_of_the_last_bit() {
+        let half = Fixed(1 << (FRACTION_BITS - 1));
+        // the product is floored, the quotient truncated towards zero
+        assert_eq!(Fixed(1) * half, Fixed(0));
+        assert_eq!(Fixed(-1) * half, Fixed(-1));
+        assert_eq!(Fixed(-1) / Fixed::from_int(2), Fixed(0));
+        assert_eq!(Fixed(1) / Fixed::from_int(2), Fixed(0));
+    }
+
+    #[test]
+    fn overflow_saturates() {
+        let (max, min) = (Fixed(i32::MAX), Fixed(i32::MIN));
+        let big = Fixed::from_int(30_000);
+        assert_eq!(big * big, max);
+        assert_eq!(big * Fixed::from_int(-30_000), min);
+        assert_eq!(big / Fixed(1), max);
+        assert_eq!(big / Fixed(-1), min);
+        assert_eq!(max + Fixed(1), max);
+        assert_eq!(min - Fixed(1), min);
+        let mut sum = max;
+        sum += max;
+        assert_eq!(sum, max);
+        // the largest values themselves still work
+        assert_eq!(max * Fixed::from_int(1), max);
+        assert_eq!(min / Fixed::from_int(1), min);
+        assert_eq!(max - max, Fixed(0));
+    }
+
+    #[test]
+    fn integers_beyond_the_range_saturate() {
+        assert_eq!(Fixed::from_int(32_767), Fixed(32_767 << FRACTION_BITS));
+        assert_eq!(Fixed::from_int(32_768), Fixed(i32::MAX));
+        assert_eq!(Fixed::from_int(i32::MAX), Fixed(i32::MAX));
+        assert_eq!(Fixed::from_int(-32_768), Fixed(i32::MIN));
+        assert_eq!(Fixed::from_int(-32_769), Fixed(i32::MIN));
+        assert_eq!(Fixed::from_int(i32::MIN), Fixed(i32::MIN));
+        assert_eq!(Fixed::from_f32(1e9), Fixed(i32::MAX));
+        assert_eq!(Fixed::from_f32(-1e9), Fixed(i32::MIN));
+    }
+
+    #[test]
+    fn division_by_zero_saturates() {
+        let zero = Fixed(0);
+        assert_eq!(Fixed::from_int(3) / zero, Fixed(i32::MAX));
+        assert_eq!(Fixed(-1) / zero, Fixed(i32::MIN));
+        assert_eq!(zero / zero, zero);
+    }
+
+    #[test]
+    fn isqrt_of_perfect_squares() {
+        for &root in &[1u64, 2, 3, 10, 65_535, 65_536, 3_037_000_499, 4_294_967_295] {
+            assert_eq!(isqrt(root * root), root);
+            assert_eq!(isqrt(root * root - 1), root - 1);
+            assert_eq!(isqrt(root * root + 2 * root), root);
+        }
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(1 << 63), 3_037_000_499);
+        assert_eq!(isqrt(u64::MAX), 4_294_967_295);
+    }
+
+    #[test]
+    fn hypot() {
+        let (three, four) = (Fixed::from_int(3), Fixed::from_int(4));
+        assert_eq!(Fixed::hypot(three, four), Fixed::from_int(5));
+        assert_eq!(
+            Fixed::hypot(Fixed(0) - three, Fixed(0) - four),
+            Fixed::from_int(5)
+        );
+        assert_eq!(Fixed::hypot(Fixed(0), Fixed(0)), Fixed(0));
+        assert_eq!(Fixed::hypot(Fixed(i32::MAX), Fixed(0)), Fixed(i32::MAX));
+        assert_eq!(Fixed::hypot(Fixed(0), Fixed(i32::MIN + 1)), Fixed(i32::MAX));
+        // longer than the largest number
+        assert_eq!(
+            Fixed::hypot(Fixed(i32::MIN), Fixed(i32::MIN)),
+            Fixed(i32::MAX)
+        );
+    }
+}
@@ -0,0 +1,167 @@
+// Show how every team did once the match is decided, the arena stays on screen
+
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    ecs::Entity,
+    input::{is_close_requested, is_key_down, VirtualKeyCode},
+    prelude::*,
+    ui::{get_default_font, Anchor, FontAsset, FontHandle, LineMode, UiImage, UiText, UiTransform},
+};
+use log::info;
+
+use crate::{
+    minions::TEAM_COLORS,
+    resources::{LocalPlayer, Victory},
+};
+
+const FONT_SIZE: f32 = 24.0;
+// in pixels
+const LINE_HEIGHT: f32 = 32.0;
+const LINE_WIDTH: f32 = 800.0;
+const PADDING: f32 = 20.0;
+
+const TEXT_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+const ERROR_COLOR: [f32; 4] = [0.9, 0.3, 0.2, 1.0];
+const VICTORY_COLOR: [f32; 4] = [0.9, 0.8, 0.1, 1.0];
+const BACKDROP_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.7];
+
+#[derive(Default)]
+pub struct GameOver {
+    // why the match ended before it was decided
+    reason: Option<String>,
+}
+
+impl GameOver {
+    // the match couldn't go on, e.g. a lan match lost the host
+    pub fn interrupted(reason: String) -> Self {
+        GameOver {
+            reason: Some(reason),
+        }
+    }
+}
+
+impl SimpleState for GameOver {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+        let font = get_default_font(
+            &world.read_resource::<Loader>(),
+            &world.read_resource::<AssetStorage<FontAsset>>(),
+        );
+        let mut lines = Vec::new();
+        if let Some(reason) = &self.reason {
+            info!("the match ended: {}", reason);
+            lines.push((format!("the match ended: {}", reason), ERROR_COLOR));
+        }
+        lines.extend(result_lines(world));
+        lines.push(("press escape to quit".to_string(), TEXT_COLOR));
+        info!("press escape to quit");
+
+        // the lines are centered on the screen, a backdrop keeps them readable over the arena
+        let height = lines.len() as f32 * LINE_HEIGHT;
+        let top = height / 2.0;
+        world
+            .create_entity()
+            .with(UiTransform::new(
+                "game_over_backdrop".to_string(),
+                Anchor::Middle,
+                Anchor::Middle,
+                0.0,
+                0.0,
+                1.0,
+                LINE_WIDTH + 2.0 * PADDING,
+                height + 2.0 * PADDING,
+            ))
+            .with(UiImage::SolidColor(BACKDROP_COLOR))
+            .build();
+        for (index, (text, color)) in lines.into_iter().enumerate() {
+            create_line(
+                world,
+                &font,
+                index,
+                top - index as f32 * LINE_HEIGHT,
+                text,
+                color,
+            );
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        _data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_close_requested(event) || is_key_down(event, VirtualKeyCode::Escape) {
+                return Trans::Quit;
+            }
+        }
+        Trans::None
+    }
+}
+
+// the outcome for the local player and a line for every team, colored like the team
+fn result_lines(world: &World) -> Vec<(String, [f32; 4])> {
+    let victory = world.read_resource::<Victory>();
+    let results = match victory.results() {
+        Some(results) => results,
+        None => return Vec::new(),
+    };
+    let mut lines = Vec::new();
+    // only a player who takes part wins or loses
+    if let Some(local_player) = world.try_fetch::<LocalPlayer>() {
+        let won = results
+            .iter()
+            .any(|result| result.team == local_player.team && result.won);
+        let (headline, color) = if won {
+            ("victory!", VICTORY_COLOR)
+        } else {
+            ("defeat", ERROR_COLOR)
+        };
+        info!("{}", headline);
+        lines.push((headline.to_string(), color));
+    }
+    for result in results {
+        let line = format!(
+            "team {}: {}, {} gold, {} units, {} buildings",
+            result.team,
+            if result.won { "won" } else { "lost" },
+            result.gold,
+            result.units,
+            result.buildings
+        );
+        info!("{}", line);
+        let (r, g, b) = TEAM_COLORS[result.team as usize % TEAM_COLORS.len()];
+        lines.push((line, [r, g, b, 1.0]));
+    }
+    lines
+}
+
+// y is the top of the line relative to the middle of the screen
+fn create_line(
+    world: &mut World,
+    font: &FontHandle,
+    index: usize,
+    y: f32,
+    text: String,
+    color: [f32; 4],
+) -> Entity {
+    let transform = UiTransform::new(
+        format!("game_over_{}", index),
+        Anchor::Middle,
+        Anchor::TopMiddle,
+        0.0,
+        y,
+        2.0,
+        LINE_WIDTH,
+        LINE_HEIGHT,
+    );
+    let text = UiText::new(
+        font.clone(),
+        text,
+        color,
+        FONT_SIZE,
+        LineMode::Single,
+        Anchor::Middle,
+    );
+    world.create_entity().with(transform).with(text).build()
+}
@@ -0,0 +1,369 @@
+// algorithms on the tile-grid, tiles are addressed by their column x and row z;
+// hex-grids shift every odd row (pointy-top) or odd column (flat-top) by half a tile
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+const SQRT_3: f32 = 1.732_050_8;
+
+// directions to the six neighbours of a hex in axial coordinates
+const HEX_DIRECTIONS: [(i32, i32); 6] = [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Topology {
+    Square,
+    PointyHex,
+    FlatHex,
+}
+
+impl Default for Topology {
+    fn default() -> Self {
+        Topology::Square
+    }
+}
+
+impl Topology {
+    // center of a tile relative to the lower left corner of the arena,
+    // measured in tiles so the centers of neighbours are one apart
+    pub fn center(self, (x, z): (i16, i16)) -> (f32, f32) {
+        match self {
+            Topology::Square => (x as f32 + 0.5, z as f32 + 0.5),
+            Topology::PointyHex => (
+                x as f32 + 0.5 + 0.5 * odd(z),
+                z as f32 * SQRT_3 / 2.0 + 1.0 / SQRT_3,
+            ),
+            Topology::FlatHex => (
+                x as f32 * SQRT_3 / 2.0 + 1.0 / SQRT_3,
+                z as f32 + 0.5 + 0.5 * odd(x),
+            ),
+        }
+    }
+
+    // the tile containing a position, the inverse of `center`
+    pub fn tile_at(self, (x, z): (f32, f32)) -> (i16, i16) {
+        match self {
+            Topology::Square => (x.floor() as i16, z.floor() as i16),
+            Topology::PointyHex => {
+                let (x, z) = (x - 0.5, z - 1.0 / SQRT_3);
+                self.from_axial(round_axial(x - z / SQRT_3, 2.0 * z / SQRT_3))
+            }
+            Topology::FlatHex => {
+                let (x, z) = (x - 1.0 / SQRT_3, z - 0.5);
+                self.from_axial(round_axial(2.0 * x / SQRT_3, z - x / SQRT_3))
+            }
+        }
+    }
+
+    // size of the bounding box of an arena with width x depth tiles, in tiles
+    pub fn extent(self, width: i16, depth: i16) -> (f32, f32) {
+        let (width, depth) = (width.max(0) as f32, depth.max(0) as f32);
+        let shift = |count: f32| if count > 1.0 { 0.5 } else { 0.0 };
+        match self {
+            Topology::Square => (width, depth),
+            Topology::PointyHex => (
+                width + shift(depth),
+                (depth - 1.0).max(0.0) * SQRT_3 / 2.0 + 2.0 / SQRT_3,
+            ),
+            Topology::FlatHex => (
+                (width - 1.0).max(0.0) * SQRT_3 / 2.0 + 2.0 / SQRT_3,
+                depth + shift(width),
+            ),
+        }
+    }
+
+    // corners of a tile relative to its center, in tiles and counter-clockwise
+    pub fn corners(self) -> Vec<(f32, f32)> {
+        match self {
+            Topology::Square => vec![(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)],
+            Topology::PointyHex | Topology::FlatHex => {
+                let start = if self == Topology::PointyHex {
+                    30.0
+                } else {
+                    0.0
+                };
+                (0..6)
+                    .map(|i| {
+                        let angle = (start + 60.0 * i as f32).to_radians();
+                        (angle.cos() / SQRT_3, angle.sin() / SQRT_3)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    fn to_axial(self, (x, z): (i16, i16)) -> (i32, i32) {
+        let (x, z) = (x as i32, z as i32);
+        match self {
+            Topology::Square => (x, z),
+            Topology::PointyHex => (x - (z - (z & 1)) / 2, z),
+            Topology::FlatHex => (x, z - (x - (x & 1)) / 2),
+        }
+    }
+
+    fn from_axial(self, (q, r): (i32, i32)) -> (i16, i16) {
+        match self {
+            Topology::Square => (q as i16, r as i16),
+            Topology::PointyHex => ((q + (r - (r & 1)) / 2) as i16, r as i16),
+            Topology::FlatHex => (q as i16, (r + (q - (q & 1)) / 2) as i16),
+        }
+    }
+}
+
+fn odd(n: i16) -> f32 {
+    (n & 1) as f32
+}
+
+// the hex closest to fractional axial coordinates
+fn round_axial(q: f32, r: f32) -> (i32, i32) {
+    let s = -q - r;
+    let (mut rounded_q, mut rounded_r, rounded_s) = (q.round(), r.round(), s.round());
+    let (dq, dr, ds) = (
+        (rounded_q - q).abs(),
+        (rounded_r - r).abs(),
+        (rounded_s - s).abs(),
+    );
+    if dq > dr && dq > ds {
+        rounded_q = -rounded_r - rounded_s;
+    } else if dr > ds {
+        rounded_r = -rounded_q - rounded_s;
+    }
+    (rounded_q as i32, rounded_r as i32)
+}
+
+// tiles sharing an edge with the tile
+pub fn neighbours(topology: Topology, (x, z): (i16, i16)) -> Vec<(i16, i16)> {
+    match topology {
+        Topology::Square => vec![(x + 1, z), (x - 1, z), (x, z + 1), (x, z - 1)],
+        Topology::PointyHex | Topology::FlatHex => {
+            let (q, r) = topology.to_axial((x, z));
+            HEX_DIRECTIONS
+                .iter()
+                .map(|&(dq, dr)| topology.from_axial((q + dq, r + dr)))
+                .collect()
+        }
+    }
+}
+
+// number of steps between two tiles when moving from neighbour to neighbour
+pub fn distance(topology: Topology, from: (i16, i16), to: (i16, i16)) -> i32 {
+    let ((q1, r1), (q2, r2)) = (topology.to_axial(from), topology.to_axial(to));
+    let (dq, dr) = (q2 - q1, r2 - r1);
+    match topology {
+        Topology::Square => dq.abs() + dr.abs(),
+        Topology::PointyHex | Topology::FlatHex => (dq.abs() + dr.abs() + (dq + dr).abs()) / 2,
+    }
+}
+
+// tiles crossed by the line between the centers of two tiles, including both ends
+pub fn line(topology: Topology, from: (i16, i16), to: (i16, i16)) -> Vec<(i16, i16)> {
+    match topology {
+        Topology::Square => square_line(from, to),
+        Topology::PointyHex | Topology::FlatHex => {
+            let steps = distance(topology, from, to);
+            let ((x0, z0), (x1, z1)) = (topology.center(from), topology.center(to));
+            // nudged so lines along the edges of hexes always pick the same side
+            (0..=steps)
+                .map(|step| {
+                    let t = if steps == 0 {
+                        0.0
+                    } else {
+                        step as f32 / steps as f32
+                    };
+                    topology.tile_at((x0 + (x1 - x0) * t + 1e-4, z0 + (z1 - z0) * t + 2e-4))
+                })
+                .collect()
+        }
+    }
+}
+
+// bresenham
+fn square_line(from: (i16, i16), to: (i16, i16)) -> Vec<(i16, i16)> {
+    let (mut x, mut z) = (from.0 as i32, from.1 as i32);
+    let (x1, z1) = (to.0 as i32, to.1 as i32);
+    let (dx, dz) = ((x1 - x).abs(), -(z1 - z).abs());
+    let (step_x, step_z) = ((x1 - x).signum(), (z1 - z).signum());
+    let mut error = dx + dz;
+    let mut tiles = Vec::with_capacity((dx - dz) as usize + 1);
+
+    loop {
+        tiles.push((x as i16, z as i16));
+        if x == x1 && z == z1 {
+            return tiles;
+        }
+        let error2 = 2 * error;
+        if error2 >= dz {
+            error += dz;
+            x += step_x;
+        }
+        if error2 <= dx {
+            error += dx;
+            z += step_z;
+        }
+    }
+}
+
+// true if no tile between the two tiles blocks the view, the tiles themselves may block
+pub fn line_of_sight(
+    topology: Topology,
+    from: (i16, i16),
+    to: (i16, i16),
+    blocks_sight: impl Fn(i16, i16) -> bool,
+) -> bool {
+    let tiles = line(topology, from, to);
+    tiles.len() <= 2
+        || tiles[1..tiles.len() - 1]
+            .iter()
+            .all(|&(x, z)| !blocks_sight(x, z))
+}
+
+// tiles whose center is at most `radius` tiles away from the center of `center`
+pub fn tiles_in_radius(
+    topology: Topology,
+    center: (i16, i16),
+    radius: f32,
+) -> impl Iterator<Item = (i16, i16)> {
+    // rows and columns of hexes are closer than one tile
+    let reach = (radius * 2.0 / SQRT_3).ceil() as i16 + 1;
+    let (center_x, center_z) = topology.center(center);
+    let radius_squared = radius * radius + 1e-3;
+    (-reach..=reach)
+        .flat_map(move |dx| (-reach..=reach).map(move |dz| (center.0 + dx, center.1 + dz)))
+        .filter(move |&tile| {
+            let (x, z) = topology.center(tile);
+            (x - center_x).powi(2) + (z - center_z).powi(2) <= radius_squared
+        })
+}
+
+// the next tile on a shortest way to `goal` from every tile which can reach it
+pub fn flow_field(
+    topology: Topology,
+    goal: (i16, i16),
+    passable: impl Fn(i16, i16) -> bool,
+) -> HashMap<(i16, i16), (i16, i16)> {
+    let mut next = HashMap::new();
+    if !passable(goal.0, goal.1) {
+        return next;
+    }
+    next.insert(goal, goal);
+    let mut open = VecDeque::new();
+    open.push_back(goal);
+    // breadth-first from the goal, the order of the neighbours breaks ties
+    while let Some(tile) = open.pop_front() {
+        for neighbour in neighbours(topology, tile) {
+            if !next.contains_key(&neighbour) && passable(neighbour.0, neighbour.1) {
+                next.insert(neighbour, tile);
+                open.push_back(neighbour);
+            }
+        }
+    }
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOPOLOGIES: [Topology; 3] = [Topology::Square, Topology::PointyHex, Topology::FlatHex];
+
+    fn tiles() -> impl Iterator<Item = (i16, i16)> {
+        (-6..7).flat_map(|x| (-6..7).map(move |z| (x, z)))
+    }
+
+    #[test]
+    fn axial_round_trip() {
+        for &topology in TOPOLOGIES.iter() {
+            for tile in tiles() {
+                assert_eq!(topology.from_axial(topology.to_axial(tile)), tile);
+            }
+        }
+    }
+
+    #[test]
+    fn odd_rows_and_columns_to_axial() {
+        assert_eq!(Topology::PointyHex.to_axial((0, 1)), (0, 1));
+        assert_eq!(Topology::PointyHex.to_axial((3, 2)), (2, 2));
+        assert_eq!(Topology::PointyHex.to_axial((3, 3)), (2, 3));
+        assert_eq!(Topology::PointyHex.to_axial((0, -1)), (1, -1));
+        assert_eq!(Topology::FlatHex.to_axial((3, 2)), (3, 1));
+        assert_eq!(Topology::FlatHex.to_axial((2, 3)), (2, 2));
+        assert_eq!(Topology::FlatHex.to_axial((-1, 0)), (-1, 1));
+    }
+
+    #[test]
+    fn tile_at_center() {
+        for &topology in TOPOLOGIES.iter() {
+            for tile in tiles() {
+                assert_eq!(topology.tile_at(topology.center(tile)), tile);
+            }
+        }
+    }
+
+    #[test]
+    fn round_axial_to_closest_hex() {
+        assert_eq!(round_axial(2.0, -3.0), (2, -3));
+        assert_eq!(round_axial(0.1, -0.05), (0, 0));
+        assert_eq!(round_axial(0.9, 0.05), (1, 0));
+        // rounding both would leave the plane q + r + s = 0
+        assert_eq!(round_axial(0.6, 0.6), (1, 0));
+        assert_eq!(round_axial(-0.6, -0.6), (-1, 0));
+    }
+
+    #[test]
+    fn distance_between_tiles() {
+        assert_eq!(distance(Topology::Square, (0, 0), (3, 4)), 7);
+        assert_eq!(distance(Topology::Square, (3, 4), (0, 0)), 7);
+        assert_eq!(distance(Topology::PointyHex, (0, 0), (3, 0)), 3);
+        assert_eq!(distance(Topology::PointyHex, (0, 0), (0, 2)), 2);
+        assert_eq!(distance(Topology::FlatHex, (0, 0), (2, 0)), 2);
+        for &topology in TOPOLOGIES.iter() {
+            assert_eq!(distance(topology, (2, 5), (2, 5)), 0);
+            for neighbour in neighbours(topology, (2, 5)) {
+                assert_eq!(distance(topology, (2, 5), neighbour), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn neighbours_are_mutual() {
+        for &topology in TOPOLOGIES.iter() {
+            for tile in tiles() {
+                let around = neighbours(topology, tile);
+                let count = if topology == Topology::Square { 4 } else { 6 };
+                assert_eq!(around.len(), count);
+                for neighbour in around {
+                    assert!(neighbours(topology, neighbour).contains(&tile));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn flow_field_leads_to_the_goal() {
+        // a wall at x = 2 with a gap at z = 4
+        let passable = |x: i16, z: i16| 0 <= x && x < 5 && 0 <= z && z < 5 && (x != 2 || z == 4);
+        for &topology in TOPOLOGIES.iter() {
+            let goal = (4, 0);
+            let field = flow_field(topology, goal, passable);
+            assert_eq!(field[&goal], goal);
+            // every passable tile can reach the goal through the gap
+            assert_eq!(field.len(), 21);
+            for (&tile, _) in field.iter() {
+                let mut current = tile;
+                let mut steps = 0;
+                while current != goal {
+                    let next = field[&current];
+                    assert_eq!(distance(topology, current, next), 1);
+                    assert!(passable(next.0, next.1));
+                    current = next;
+                    steps += 1;
+                    assert!(steps <= 25);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn flow_field_of_a_blocked_goal() {
+        assert!(flow_field(Topology::Square, (0, 0), |_, _| false).is_empty());
+    }
+}
@@ -1,17 +1,74 @@
-use std::fmt::{self, Display};
+use std::{
+    fmt::{self, Display},
+    path::Path,
+};
 
-use amethyst::input::BindingTypes;
+use amethyst::{
+    config::Config,
+    input::{Axis, BindingTypes, Bindings, Button, InputHandler, VirtualKeyCode},
+    Error,
+};
+use log::warn;
 use serde::{Deserialize, Serialize};
 
+use crate::resources::DebugLayer;
+
+// relative to the application root, the bindings of the user override the shipped ones
+pub const INPUT_PATH: &str = "config/input.ron";
+pub const USER_INPUT_PATH: &str = "config/user_input.ron";
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AxisBinding {
     Right,
     Forward,
     Zoom,
+    // zooms while a trigger of a gamepad is held, in units per second
+    ZoomHeld,
+    // the virtual cursor of a gamepad, in screen directions
+    CursorRight,
+    CursorDown,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
-pub enum ActionBinding {}
+pub enum ActionBinding {
+    Select,
+    Command,
+    Cancel,
+    // slot on the command card of the selected building
+    CommandCard(u8),
+    // on the hovered tile of a tower-defense map
+    BuildTower,
+    // binds the selection to a number key, recalls it and centres the camera on a double-tap
+    AssignControlGroup(u8),
+    ControlGroup(u8),
+    // camera positions on the F-keys, the camera glides to them
+    StoreBookmark(u8),
+    Bookmark(u8),
+    JumpToAlert,
+    // the camera keeps the selection in the centre until the player pans
+    FollowSelection,
+    // saves what the camera sees to the screenshot folder
+    Screenshot,
+    // shift and the F-keys, see resources::DebugLayers
+    ToggleDebugLayer(DebugLayer),
+    // fps, frame times and the time of every system, see resources::Profiler
+    PerformanceOverlay,
+    // starts a trace of the systems and saves it to the trace folder on the next press
+    RecordTrace,
+    // map editor
+    Tool(u8),
+    BrushLarger,
+    BrushSmaller,
+    Undo,
+    Redo,
+    Save,
+    // replays
+    Pause,
+    Slower,
+    Faster,
+    SeekBack,
+    SeekForward,
+}
 
 impl Display for AxisBinding {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -31,3 +88,174 @@ impl BindingTypes for MovementBindingTypes {
     type Axis = AxisBinding;
     type Action = ActionBinding;
 }
+
+// keyboard layouts the player can start from in the options, they only differ in the
+// keys which move the camera
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layout {
+    Qwerty,
+    Azerty,
+    Neo,
+}
+
+impl Layout {
+    pub const ALL: [Layout; 3] = [Layout::Qwerty, Layout::Azerty, Layout::Neo];
+
+    // the shipped bindings with the camera on the keys of the layout
+    pub fn bindings(
+        self,
+        shipped: &Bindings<MovementBindingTypes>,
+    ) -> Result<Bindings<MovementBindingTypes>, Error> {
+        use VirtualKeyCode::*;
+        let ((right, left), (forward, back)) = match self {
+            Layout::Qwerty => ((D, A), (W, S)),
+            Layout::Azerty => ((D, Q), (Z, S)),
+            Layout::Neo => ((A, U), (V, I)),
+        };
+        let mut bindings = shipped.clone();
+        let axes: Vec<_> = [
+            (AxisBinding::Right, right, left),
+            (AxisBinding::Forward, forward, back),
+        ]
+        .iter()
+        .map(|(axis, pos, neg)| {
+            let (pos, neg) = (Button::Key(*pos), Button::Key(*neg));
+            let value = bindings
+                .axis(axis)
+                .and_then(|value| with_axis_keys(value, pos.clone(), neg.clone()))
+                .unwrap_or(Axis::Emulated { pos, neg });
+            (axis.clone(), value)
+        })
+        .collect();
+        for (axis, _) in &axes {
+            bindings.remove_axis(axis);
+        }
+        for (axis, value) in axes {
+            bindings
+                .insert_axis(axis, value)
+                .map_err(|e| Error::from_string(e.to_string()))?;
+        }
+        Ok(bindings)
+    }
+}
+
+impl Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Layout::Qwerty => write!(f, "QWERTY"),
+            Layout::Azerty => write!(f, "AZERTY"),
+            Layout::Neo => write!(f, "Neo"),
+        }
+    }
+}
+
+pub fn shipped_bindings(app_root: &Path) -> Result<Bindings<MovementBindingTypes>, Error> {
+    Ok(Bindings::load(app_root.join(INPUT_PATH))?)
+}
+
+// the shipped bindings, overridden by the ones the player changed. bindings which are
+// new since the user file was written keep their shipped keys
+pub fn load_bindings(app_root: &Path) -> Result<Bindings<MovementBindingTypes>, Error> {
+    let mut bindings = shipped_bindings(app_root)?;
+    let user_path = app_root.join(USER_INPUT_PATH);
+    if !user_path.exists() {
+        return Ok(bindings);
+    }
+    let user = match Bindings::<MovementBindingTypes>::load(&user_path) {
+        Ok(user) => user,
+        Err(e) => {
+            warn!("ignoring the invalid bindings in {:?}: {}", user_path, e);
+            return Ok(bindings);
+        }
+    };
+    let mut merged = Bindings::new();
+    for axis in user.axes().chain(bindings.axes()) {
+        if merged.axis(axis).is_some() {
+            continue;
+        }
+        let value = user.axis(axis).or_else(|| bindings.axis(axis));
+        if let Some(value) = value {
+            if let Err(e) = merged.insert_axis(axis.clone(), value.clone()) {
+                warn!("ignoring the binding of {}: {}", axis, e);
+            }
+        }
+    }
+    for action in user.actions().chain(bindings.actions()) {
+        if merged.action_bindings(action).next().is_some() {
+            continue;
+        }
+        let combos: Vec<Vec<Button>> = if user.action_bindings(action).next().is_some() {
+            user.action_bindings(action).map(<[_]>::to_vec).collect()
+        } else {
+            bindings
+                .action_bindings(action)
+                .map(<[_]>::to_vec)
+                .collect()
+        };
+        for combo in combos {
+            if let Err(e) = merged.insert_action_binding(action.clone(), combo) {
+                warn!("ignoring a binding of {}: {}", action, e);
+            }
+        }
+    }
+    if merged.check_invariants().is_ok() {
+        bindings = merged;
+    } else {
+        warn!(
+            "the bindings in {:?} conflict, using the shipped ones",
+            user_path
+        );
+    }
+    Ok(bindings)
+}
+
+pub fn save_bindings(
+    app_root: &Path,
+    bindings: &Bindings<MovementBindingTypes>,
+) -> Result<(), Error> {
+    Ok(bindings.write(app_root.join(USER_INPUT_PATH))?)
+}
+
+// the keys of the debug layers share the F-keys with other actions, which skip them
+pub fn debug_layer_down(input: &InputHandler<MovementBindingTypes>) -> bool {
+    DebugLayer::ALL.iter().any(|layer| {
+        input
+            .action_is_down(&ActionBinding::ToggleDebugLayer(*layer))
+            .unwrap_or(false)
+    })
+}
+
+// e.g. LControl+Key1 or Mouse(Left)
+pub fn combo_name(combo: &[Button]) -> String {
+    combo
+        .iter()
+        .map(|button| match button {
+            Button::Key(key) => format!("{:?}", key),
+            button => format!("{:?}", button),
+        })
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+// the keys of an axis, it may combine them with a stick
+pub fn axis_keys(axis: &Axis) -> Option<(&Button, &Button)> {
+    match axis {
+        Axis::Emulated { pos, neg } => Some((pos, neg)),
+        Axis::Multiple(axes) => axes.iter().find_map(axis_keys),
+        _ => None,
+    }
+}
+
+// the axis on other keys, its sticks stay
+pub fn with_axis_keys(axis: &Axis, pos: Button, neg: Button) -> Option<Axis> {
+    match axis {
+        Axis::Emulated { .. } => Some(Axis::Emulated { pos, neg }),
+        Axis::Multiple(axes) => {
+            let index = axes.iter().position(|axis| axis_keys(axis).is_some())?;
+            let mut axes = axes.clone();
+            axes[index] = with_axis_keys(&axes[index], pos, neg)?;
+            Some(Axis::Multiple(axes))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,27 @@
+// everything but the entry points, shared by the game and the dedicated server
+
+pub mod bundle;
+pub mod capture;
+pub mod cli;
+pub mod components;
+pub mod config;
+pub mod console;
+pub mod editor;
+pub mod fixed;
+pub mod game_over;
+pub mod grid;
+pub mod input;
+pub mod loading;
+pub mod mapgen;
+pub mod minions;
+pub mod network;
+pub mod options_menu;
+pub mod orders;
+pub mod overview;
+pub mod picking;
+pub mod playback;
+pub mod resources;
+pub mod rng;
+pub mod server;
+pub mod simulation;
+pub mod systems;
@@ -0,0 +1,128 @@
+// Load the unit prefabs before the game starts
+
+use amethyst::{
+    assets::{AssetStorage, Completion, Handle, Prefab, PrefabLoader, ProgressCounter, RonFormat},
+    prelude::*,
+    utils::application_root_dir,
+};
+use log::{error, info, warn};
+use std::fs;
+
+use crate::{
+    bundle::Mode,
+    components::UnitPrefabData,
+    editor::Editor,
+    minions::Minions,
+    overview::Overview,
+    playback::Playback,
+    resources::{UnitRegistry, UnitType},
+};
+
+// relative to the assets-directory
+const UNITS_DIR: &str = "units";
+
+pub struct Loading {
+    progress: ProgressCounter,
+    units: Vec<(String, Handle<Prefab<UnitPrefabData>>)>,
+    // the state which follows
+    mode: Mode,
+}
+
+impl Loading {
+    pub fn new(mode: Mode) -> Self {
+        Loading {
+            progress: ProgressCounter::default(),
+            units: Vec::new(),
+            mode,
+        }
+    }
+}
+
+impl SimpleState for Loading {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let units_dir = match application_root_dir() {
+            Ok(root) => root.join("assets").join(UNITS_DIR),
+            Err(e) => {
+                error!("failed to find the application root: {}", e);
+                return;
+            }
+        };
+        let entries = match fs::read_dir(&units_dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                error!("failed to read {:?}: {}", units_dir, e);
+                return;
+            }
+        };
+
+        // every .ron file is a unit, its file name is the id
+        for path in entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+        {
+            if path
+                .extension()
+                .map_or(true, |extension| extension != "ron")
+            {
+                continue;
+            }
+            let (id, file_name) = match (path.file_stem(), path.file_name()) {
+                (Some(id), Some(file_name)) => (
+                    id.to_string_lossy().into_owned(),
+                    file_name.to_string_lossy().into_owned(),
+                ),
+                _ => continue,
+            };
+            let progress = &mut self.progress;
+            let handle = data.world.exec(|loader: PrefabLoader<'_, UnitPrefabData>| {
+                loader.load(format!("{}/{}", UNITS_DIR, file_name), RonFormat, progress)
+            });
+            self.units.push((id, handle));
+        }
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        match self.progress.complete() {
+            Completion::Loading => Trans::None,
+            Completion::Failed => {
+                for error in self.progress.errors() {
+                    error!("failed to load {}: {}", error.asset_name, error.error);
+                }
+                Trans::Quit
+            }
+            Completion::Complete => {
+                let mut registry = UnitRegistry::default();
+                {
+                    let prefabs = data
+                        .world
+                        .read_resource::<AssetStorage<Prefab<UnitPrefabData>>>();
+                    for (id, handle) in self.units.drain(..) {
+                        let unit = prefabs
+                            .get(&handle)
+                            .and_then(|prefab| prefab.entities().next())
+                            .and_then(|entity| entity.data());
+                        match unit {
+                            Some(unit) => {
+                                let unit_type = UnitType {
+                                    prefab: Some(handle),
+                                    stats: unit.stats.clone(),
+                                    team_colored: unit.material.is_none(),
+                                };
+                                registry.insert(id, unit_type);
+                            }
+                            None => warn!("the prefab of unit {} has no data", id),
+                        }
+                    }
+                }
+                info!("loaded units: {:?}", registry.ids());
+                data.world.insert(registry);
+                match self.mode {
+                    Mode::Play => Trans::Switch(Box::new(Minions::default())),
+                    Mode::Edit => Trans::Switch(Box::new(Editor::default())),
+                    Mode::Replay => Trans::Switch(Box::new(Playback::default())),
+                    Mode::Overview => Trans::Switch(Box::new(Overview::default())),
+                }
+            }
+        }
+    }
+}
@@ -6,38 +6,125 @@ use amethyst::{
     renderer::{
         types::DefaultBackend, RenderDebugLines, RenderShaded3D, RenderToWindow, RenderingBundle,
     },
+    ui::{RenderUi, UiBundle},
     utils::application_root_dir,
     Application, GameDataBuilder,
 };
 use std::time::Duration;
 
-mod bundle;
-mod components;
-mod config;
-mod input;
-mod minions;
-mod systems;
-use crate::{
-    bundle::MinionsBundle, config::MinionsConfig, input::MovementBindingTypes, minions::Minions,
+#[cfg(feature = "gamepad")]
+use amethyst::input::SdlEventsSystemDesc;
+
+use minions::{
+    bundle::{MinionsBundle, Mode},
+    capture::{OverviewFile, RenderCapture},
+    cli::{Options, USAGE},
+    config::MinionsConfig,
+    input::{load_bindings, MovementBindingTypes},
+    loading::Loading,
+    network::{self, MatchSetup},
+    orders::LocalOrders,
+    resources::{
+        LocalPlayer, Lockstep, MapEditor, Replay, ReplayPlayer, ReplayRecorder, Simulation,
+        Treasury,
+    },
+    rng::Rng,
+    simulation::TICKS_PER_SECOND,
 };
 
 fn main() -> amethyst::Result<()> {
     amethyst::start_logger(Default::default());
+    let options = Options::from_args()?;
+    if options.help {
+        println!("{}", USAGE);
+        return Ok(());
+    }
 
     // prepare data for GameDataBuilder
     let app_root = application_root_dir()?;
     let display_config_path = app_root.join("config/display.ron");
-    let input_path = app_root.join("config").join("input.ron");
     let input_bundle =
-        InputBundle::<MovementBindingTypes>::new().with_bindings_from_file(&input_path)?;
+        InputBundle::<MovementBindingTypes>::new().with_bindings(load_bindings(&app_root)?);
     let assets_dir = app_root.join("assets/");
-    let minions_config = MinionsConfig::load("config/config.ron")?;
+    let mut minions_config = MinionsConfig::load("config/config.ron")?;
+    let mode = if options.overview.is_some() {
+        Mode::Overview
+    } else if options.edit.is_some() {
+        Mode::Edit
+    } else if options.replay.is_some() {
+        Mode::Replay
+    } else {
+        Mode::Play
+    };
+    let mut seed = options.seed.unwrap_or_default();
+    let replay = match &options.replay {
+        Some(path) => Some(Replay::load(path)?),
+        None => None,
+    };
+
+    // a replay, a map-file or a seed replace the arena of the config,
+    // a new map of the editor starts from the arena as well
+    if let Some(replay) = &replay {
+        minions_config.arena = replay.arena.clone();
+        minions_config.players = replay.players.clone();
+        seed = replay.seed;
+    } else {
+        let map_file = options
+            .map
+            .clone()
+            .or_else(|| options.edit.clone().filter(|path| path.exists()));
+        minions_config.select_arena(
+            map_file.as_deref(),
+            options.seed,
+            options.players,
+            options.size,
+        )?;
+        minions_config.set_difficulty(options.difficulty);
+    }
+
+    // the players of a lan match play the arena and seed of the host
+    let mut team = 0;
+    let mut lockstep = None;
+    if let Some(port) = options.host {
+        let setup = MatchSetup {
+            seed,
+            arena: minions_config.arena.clone(),
+            players: minions_config.players.clone(),
+            input_delay: network::INPUT_DELAY,
+        };
+        let connections = network::host(port, &setup)?;
+        lockstep = Some(Lockstep::new(
+            Some(team),
+            setup.players.teams,
+            setup.input_delay,
+            true,
+            connections,
+        ));
+    } else if let Some(address) = &options.join {
+        let (joined, setup, connection) = network::join(address)?;
+        team = joined;
+        seed = setup.seed;
+        lockstep = Some(Lockstep::new(
+            Some(team),
+            setup.players.teams,
+            setup.input_delay,
+            false,
+            vec![connection],
+        ));
+        minions_config.arena = setup.arena;
+        minions_config.players = setup.players;
+    }
+    let treasury = Treasury::new(
+        minions_config.players.teams,
+        minions_config.players.starting_gold,
+    );
 
     // create game_data with GameDataBuilder
     let game_data = GameDataBuilder::default()
         .with_bundle(input_bundle)?
         .with_bundle(TransformBundle::new())?
-        .with_bundle(MinionsBundle)?
+        .with_bundle(UiBundle::<MovementBindingTypes>::new())?
+        .with_bundle(MinionsBundle { mode })?
         .with_bundle(
             RenderingBundle::<DefaultBackend>::new()
                 .with_plugin(RenderDebugLines::default())
@@ -45,16 +132,55 @@ fn main() -> amethyst::Result<()> {
                     RenderToWindow::from_config_path(display_config_path)?
                         .with_clear([0.05, 0.05, 0.05, 1.0]),
                 )
-                .with_plugin(RenderShaded3D::default()),
+                .with_plugin(RenderShaded3D::default())
+                .with_plugin(RenderUi::default())
+                .with_plugin(RenderCapture::default()),
         )?;
-    let mut game = Application::build(assets_dir, Minions::default())?
+    // without the feature the gamepad bindings of input.ron never fire
+    #[cfg(feature = "gamepad")]
+    let game_data =
+        game_data.with_thread_local_desc(SdlEventsSystemDesc::<MovementBindingTypes>::default());
+    let recorder = options.record.map(|path| ReplayRecorder {
+        path,
+        replay: Replay {
+            seed,
+            arena: minions_config.arena.clone(),
+            players: minions_config.players.clone(),
+            orders: Vec::new(),
+        },
+    });
+    let mut builder = Application::build(assets_dir, Loading::new(mode))?
         .with_frame_limit(
             FrameRateLimitStrategy::SleepAndYield(Duration::from_millis(2)),
             144,
         )
+        .with_fixed_step_length(Duration::from_nanos(
+            1_000_000_000 / TICKS_PER_SECOND as u64,
+        ))
         .with_resource(minions_config.arena)
         .with_resource(minions_config.camera)
-        .build(game_data)?;
+        .with_resource(minions_config.players)
+        .with_resource(treasury)
+        .with_resource(Rng::new(seed))
+        .with_resource(Simulation::default())
+        .with_resource(LocalOrders::default())
+        .with_resource(LocalPlayer { team });
+    if let Some(path) = options.edit {
+        builder = builder.with_resource(MapEditor::new(path));
+    }
+    if let Some(path) = options.overview {
+        builder = builder.with_resource(OverviewFile(path));
+    }
+    if let Some(recorder) = recorder {
+        builder = builder.with_resource(recorder);
+    }
+    if let Some(replay) = replay {
+        builder = builder.with_resource(ReplayPlayer::new(replay));
+    }
+    if let Some(lockstep) = lockstep {
+        builder = builder.with_resource(lockstep);
+    }
+    let mut game = builder.build(game_data)?;
     game.run();
     Ok(())
 }
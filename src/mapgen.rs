@@ -0,0 +1,412 @@
+// Procedural arenas, the same seed and parameters always generate the same arena
+
+use std::{
+    collections::{HashSet, VecDeque},
+    f32::consts::PI,
+};
+
+use crate::{
+    components::Terrain,
+    config::{
+        ArenaConfig, BuildingConfig, GeneratorConfig, GoldMineConfig, HeightConfig, TerrainConfig,
+        VictoryCondition,
+    },
+    grid::{self, Topology},
+    rng::Rng,
+};
+
+// resolution of the noise-lattice over the whole arena
+const NOISE_CELLS: usize = 6;
+// radius in tiles of the flat grass around every spawn point
+const SPAWN_CLEARING: f32 = 3.0;
+// distance of the spawn points from the center, relative to the size of the arena
+const SPAWN_RADIUS: f32 = 0.7;
+
+pub fn generate(
+    seed: u64,
+    params: &GeneratorConfig,
+    tile_size: f32,
+    topology: Topology,
+) -> ArenaConfig {
+    let mut rng = Rng::new(seed);
+    let (width, depth) = (params.width.max(8), params.depth.max(8));
+    let players = params.players.max(1);
+    let mut layout = Layout::new(width, depth, topology);
+
+    // every player gets the same terrain, rotated around the center
+    let rock_noise = ValueNoise::new(&mut rng);
+    let height_noise = ValueNoise::new(&mut rng);
+    for x in 0..width {
+        for z in 0..depth {
+            let (nx, nz) = canonical(layout.normalize(x, z), players);
+            if rock_noise.sample(nx, nz) < params.rock {
+                layout.set_terrain(x, z, Terrain::Rock);
+            }
+            layout.set_height(x, z, height_noise.sample(nx, nz) * params.max_height);
+        }
+    }
+
+    let spawns: Vec<(i16, i16)> = (0..players)
+        .map(|player| {
+            let angle = PI + 2.0 * PI * player as f32 / players as f32;
+            let tile = layout.denormalize(SPAWN_RADIUS * angle.cos(), SPAWN_RADIUS * angle.sin());
+            (tile.0.max(1).min(width - 3), tile.1.max(1).min(depth - 3))
+        })
+        .collect();
+    let spawn_height = layout.height(spawns[0].0, spawns[0].1);
+    for &spawn in spawns.iter() {
+        for (x, z) in grid::tiles_in_radius(topology, spawn, SPAWN_CLEARING) {
+            layout.set_terrain(x, z, Terrain::Grass);
+            layout.set_height(x, z, spawn_height);
+        }
+    }
+
+    // the gold mines of the first player are rotated to the others
+    let area = width as f32 * depth as f32;
+    let mines_per_player = ((params.gold_density * area / players as f32).round() as usize).max(1);
+    let mut gold_mines = Vec::new();
+    for _ in 0..mines_per_player {
+        let sector = 2.0 * PI / players as f32;
+        let angle = PI + (rng.next_f32() - 0.5) * sector * 0.6;
+        let radius = 0.35 + rng.next_f32() * 0.5;
+        for player in 0..players {
+            let angle = angle + sector * player as f32;
+            let (x, z) = layout.denormalize(radius * angle.cos(), radius * angle.sin());
+            let on_spawn = spawns
+                .iter()
+                .any(|&(sx, sz)| sx <= x && x <= sx + 1 && sz <= z && z <= sz + 1);
+            if layout.contains(x, z) && !on_spawn {
+                layout.set_terrain(x, z, Terrain::Grass);
+                gold_mines.push(GoldMineConfig {
+                    x,
+                    z,
+                    amount: params.gold_amount,
+                });
+            }
+        }
+    }
+
+    // every spawn point and gold mine is connected to the center if one can't be reached,
+    // the grass nobody can reach becomes rock so every passable tile can be reached
+    let center = (width / 2, depth / 2);
+    let reachable = layout.reachable_from(spawns[0]);
+    let cut_off: Vec<(i16, i16)> = gold_mines
+        .iter()
+        .map(|mine| (mine.x, mine.z))
+        .filter(|mine| !reachable.contains(mine))
+        .collect();
+    if !cut_off.is_empty() || spawns.iter().any(|spawn| !reachable.contains(spawn)) {
+        for &tile in spawns.iter().chain(cut_off.iter()) {
+            layout.carve(tile, center);
+        }
+    }
+    let reachable = layout.reachable_from(spawns[0]);
+    for (x, z, _) in layout.tiles().collect::<Vec<_>>() {
+        if layout.is_passable(x, z) && !reachable.contains(&(x, z)) {
+            layout.set_terrain(x, z, Terrain::Rock);
+        }
+    }
+
+    ArenaConfig {
+        width,
+        depth,
+        tile_size,
+        topology,
+        buildings: spawns
+            .iter()
+            .enumerate()
+            .map(|(team, &(x, z))| BuildingConfig {
+                x,
+                z,
+                width: 2,
+                depth: 2,
+                team: team as u8,
+                produces: Vec::new(),
+                health: None,
+            })
+            .collect(),
+        terrain: layout.terrain_config(),
+        heightmap: None,
+        heights: layout.height_config(),
+        gold_mines,
+        scripts: Vec::new(),
+        victory: vec![VictoryCondition::DestroyBuildings],
+        defense: None,
+    }
+}
+
+// maps a position into the sector of the first player, so the terrain
+// is rotationally symmetric for any number of players
+fn canonical((x, z): (f32, f32), players: u8) -> (f32, f32) {
+    let radius = (x * x + z * z).sqrt();
+    let sector = 2.0 * PI / players as f32;
+    let angle = z.atan2(x).rem_euclid(sector);
+    (radius * angle.cos(), radius * angle.sin())
+}
+
+// random values on a lattice over [-1, 1]², smoothly interpolated in between
+struct ValueNoise {
+    values: Vec<f32>,
+}
+
+impl ValueNoise {
+    fn new(rng: &mut Rng) -> Self {
+        ValueNoise {
+            values: (0..(NOISE_CELLS + 1) * (NOISE_CELLS + 1))
+                .map(|_| rng.next_f32())
+                .collect(),
+        }
+    }
+
+    fn sample(&self, x: f32, z: f32) -> f32 {
+        let to_lattice = |v: f32| ((v + 1.0) / 2.0).max(0.0).min(1.0) * NOISE_CELLS as f32;
+        let (lx, lz) = (to_lattice(x), to_lattice(z));
+        let (cell_x, cell_z) = (
+            (lx.floor() as usize).min(NOISE_CELLS - 1),
+            (lz.floor() as usize).min(NOISE_CELLS - 1),
+        );
+        let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+        let (tx, tz) = (smooth(lx - cell_x as f32), smooth(lz - cell_z as f32));
+
+        let value = |x: usize, z: usize| self.values[z * (NOISE_CELLS + 1) + x];
+        let bottom = value(cell_x, cell_z) * (1.0 - tx) + value(cell_x + 1, cell_z) * tx;
+        let top = value(cell_x, cell_z + 1) * (1.0 - tx) + value(cell_x + 1, cell_z + 1) * tx;
+        bottom * (1.0 - tz) + top * tz
+    }
+}
+
+struct Layout {
+    width: i16,
+    depth: i16,
+    topology: Topology,
+    terrain: Vec<Terrain>,
+    heights: Vec<f32>,
+}
+
+impl Layout {
+    fn new(width: i16, depth: i16, topology: Topology) -> Self {
+        let tiles = width as usize * depth as usize;
+        Layout {
+            width,
+            depth,
+            topology,
+            terrain: vec![Terrain::Grass; tiles],
+            heights: vec![0.0; tiles],
+        }
+    }
+
+    fn contains(&self, x: i16, z: i16) -> bool {
+        0 <= x && x < self.width && 0 <= z && z < self.depth
+    }
+
+    fn index(&self, x: i16, z: i16) -> Option<usize> {
+        if self.contains(x, z) {
+            Some(z as usize * self.width as usize + x as usize)
+        } else {
+            None
+        }
+    }
+
+    // position relative to the center, the borders of the arena are at -1 and 1
+    fn normalize(&self, x: i16, z: i16) -> (f32, f32) {
+        let (half_width, half_depth) = self.half_extent();
+        let (center_x, center_z) = self.topology.center((x, z));
+        (
+            (center_x - half_width) / half_width,
+            (center_z - half_depth) / half_depth,
+        )
+    }
+
+    fn denormalize(&self, x: f32, z: f32) -> (i16, i16) {
+        let (half_width, half_depth) = self.half_extent();
+        self.topology
+            .tile_at((x * half_width + half_width, z * half_depth + half_depth))
+    }
+
+    fn half_extent(&self) -> (f32, f32) {
+        let (width, depth) = self.topology.extent(self.width, self.depth);
+        (width / 2.0, depth / 2.0)
+    }
+
+    fn set_terrain(&mut self, x: i16, z: i16, terrain: Terrain) {
+        if let Some(index) = self.index(x, z) {
+            self.terrain[index] = terrain;
+        }
+    }
+
+    fn height(&self, x: i16, z: i16) -> f32 {
+        self.index(x, z).map_or(0.0, |index| self.heights[index])
+    }
+
+    fn set_height(&mut self, x: i16, z: i16, height: f32) {
+        if let Some(index) = self.index(x, z) {
+            self.heights[index] = height;
+        }
+    }
+
+    fn is_passable(&self, x: i16, z: i16) -> bool {
+        self.index(x, z)
+            .map_or(false, |index| self.terrain[index].is_passable())
+    }
+
+    // turns a way from neighbour to neighbour into grass, every step gets closer
+    fn carve(&mut self, from: (i16, i16), to: (i16, i16)) {
+        let topology = self.topology;
+        let distance = |tile| grid::distance(topology, tile, to);
+        let mut tile = from;
+        self.set_terrain(tile.0, tile.1, Terrain::Grass);
+        while let Some(next) = grid::neighbours(topology, tile)
+            .into_iter()
+            .filter(|&(x, z)| self.contains(x, z))
+            .min_by_key(|&next| distance(next))
+            .filter(|&next| distance(next) < distance(tile))
+        {
+            tile = next;
+            self.set_terrain(tile.0, tile.1, Terrain::Grass);
+        }
+    }
+
+    fn reachable_from(&self, start: (i16, i16)) -> HashSet<(i16, i16)> {
+        let mut reached = HashSet::new();
+        let mut queue = VecDeque::new();
+        reached.insert(start);
+        queue.push_back(start);
+        while let Some(tile) = queue.pop_front() {
+            for &(x, z) in grid::neighbours(self.topology, tile).iter() {
+                if self.is_passable(x, z) && reached.insert((x, z)) {
+                    queue.push_back((x, z));
+                }
+            }
+        }
+        reached
+    }
+
+    fn terrain_config(&self) -> Vec<TerrainConfig> {
+        self.tiles()
+            .filter(|&(_, _, index)| self.terrain[index] != Terrain::Grass)
+            .map(|(x, z, index)| TerrainConfig {
+                x,
+                z,
+                terrain: self.terrain[index],
+            })
+            .collect()
+    }
+
+    fn height_config(&self) -> Vec<HeightConfig> {
+        self.tiles()
+            .filter(|&(_, _, index)| self.heights[index] != 0.0)
+            .map(|(x, z, index)| HeightConfig {
+                x,
+                z,
+                height: self.heights[index],
+            })
+            .collect()
+    }
+
+    fn tiles(&self) -> impl Iterator<Item = (i16, i16, usize)> + '_ {
+        (0..self.depth).flat_map(move |z| {
+            (0..self.width).map(move |x| (x, z, z as usize * self.width as usize + x as usize))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOPOLOGIES: [Topology; 3] = [Topology::Square, Topology::PointyHex, Topology::FlatHex];
+
+    #[test]
+    fn every_passable_tile_is_reachable() {
+        for &topology in TOPOLOGIES.iter() {
+            for players in 1..=4 {
+                for seed in 0..16 {
+                    let params = GeneratorConfig {
+                        players,
+                        rock: 0.45,
+                        ..GeneratorConfig::default()
+                    };
+                    let arena = generate(seed, &params, 1.0, topology);
+                    let rocks: HashSet<(i16, i16)> = arena
+                        .terrain
+                        .iter()
+                        .filter(|config| !config.terrain.is_passable())
+                        .map(|config| (config.x, config.z))
+                        .collect();
+                    let passable = |(x, z): (i16, i16)| {
+                        0 <= x
+                            && x < arena.width
+                            && 0 <= z
+                            && z < arena.depth
+                            && !rocks.contains(&(x, z))
+                    };
+
+                    let start = (arena.buildings[0].x, arena.buildings[0].z);
+                    let mut reached = HashSet::new();
+                    let mut open = VecDeque::new();
+                    reached.insert(start);
+                    open.push_back(start);
+                    while let Some(tile) = open.pop_front() {
+                        for neighbour in grid::neighbours(topology, tile) {
+                            if passable(neighbour) && reached.insert(neighbour) {
+                                open.push_back(neighbour);
+                            }
+                        }
+                    }
+
+                    let passable_tiles = (0..arena.width)
+                        .flat_map(|x| (0..arena.depth).map(move |z| (x, z)))
+                        .filter(|&tile| passable(tile))
+                        .count();
+                    assert_eq!(
+                        reached.len(),
+                        passable_tiles,
+                        "seed {} with {} players on {:?}",
+                        seed,
+                        players,
+                        topology
+                    );
+                    for building in &arena.buildings {
+                        assert!(reached.contains(&(building.x, building.z)));
+                    }
+                    for mine in &arena.gold_mines {
+                        assert!(reached.contains(&(mine.x, mine.z)));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_same_arena() {
+        let params = GeneratorConfig::default();
+        let first = generate(7, &params, 1.0, Topology::Square);
+        let second = generate(7, &params, 1.0, Topology::Square);
+        assert_eq!(format!("{:?}", first), format!("{:?}", second));
+    }
+
+    #[test]
+    fn large_arena() {
+        let params = GeneratorConfig {
+            width: 200,
+            depth: 200,
+            ..GeneratorConfig::default()
+        };
+        let arena = generate(3, &params, 1.0, Topology::Square);
+        assert_eq!((arena.width, arena.depth), (200, 200));
+        assert_eq!(arena.buildings.len(), 2);
+        // about 0.01 mines per tile, some fall on a spawn point
+        assert!(arena.gold_mines.len() > 300);
+    }
+
+    #[test]
+    fn no_players_make_one() {
+        let params = GeneratorConfig {
+            players: 0,
+            ..GeneratorConfig::default()
+        };
+        let arena = generate(5, &params, 1.0, Topology::Square);
+        assert_eq!(arena.buildings.len(), 1);
+        assert_eq!(arena.buildings[0].team, 0);
+    }
+}
@@ -2,11 +2,13 @@
 
 use amethyst::{
     assets::{AssetLoaderSystemData, Handle},
+    config::Config,
     core::{
-        ecs::{Builder, WorldExt},
+        ecs::{Builder, Dispatcher, EntityBuilder, WorldExt},
         math::{Point3, Vector3},
         Transform,
     },
+    input::is_key_down,
     prelude::*,
     renderer::palette::Srgb,
     renderer::Camera,
@@ -15,37 +17,205 @@ use amethyst::{
         light::{DirectionalLight, Light},
         loaders::load_from_linear_rgba,
         palette::{LinSrgba, Srgba},
-        rendy::mesh::{Normal, Position, Tangent, TexCoord},
+        rendy::mesh::{MeshBuilder, Normal, Position, Tangent, TexCoord},
         shape::Shape,
+        types::MeshData,
         Material, MaterialDefaults, Mesh, Texture,
     },
+    utils::application_root_dir,
+    window::ScreenDimensions,
     SimpleState,
 };
-use std::f32::consts::PI;
+use log::{error, info};
+use std::{collections::HashMap, f32::consts::PI};
 
 use crate::{
-    components::{CameraBorders, CameraControlTag, PieceInfo},
-    config::{ArenaConfig, CameraConfig},
+    components::{
+        perspective_camera, Building, CameraBorders, CameraControlTag, GoldMine, GridLines, Health,
+        OrthographicExtent, PieceInfo, ProductionQueue, Sight, Team, Terrain, TileMaterials,
+    },
+    config::{
+        ArenaConfig, BuildingConfig, CameraConfig, CameraProjection, GoldMineConfig,
+        HeightmapConfig, PlayerConfig,
+    },
+    console::{self, DeveloperConsole, CONSOLE_KEY},
+    game_over::GameOver,
+    grid::Topology,
+    options_menu::{OptionsMenu, MENU_KEY},
+    resources::{
+        Defense, FogOfWar, Lockstep, MinionAssets, ReplayRecorder, Scripts, Simulation,
+        SimulationSpeed, TerrainAssets, TerrainMap, UnitRegistry, Victory,
+    },
+    simulation,
+    systems::look_at,
 };
+
+// in tiles
+const BUILDING_SIGHT: f32 = 4.0;
+// of a building whose config doesn't give it
+pub const BUILDING_HEALTH: u32 = 1000;
+
+const GOLD_COLOR: (f32, f32, f32) = (0.8, 0.6, 0.05);
+
+// albedo of the units and buildings of each team
+pub const TEAM_COLORS: [(f32, f32, f32); 8] = [
+    (0.1, 0.3, 0.9),
+    (0.9, 0.1, 0.1),
+    (0.1, 0.8, 0.2),
+    (0.9, 0.8, 0.1),
+    (0.6, 0.1, 0.8),
+    (0.1, 0.8, 0.8),
+    (0.9, 0.5, 0.1),
+    (0.9, 0.9, 0.9),
+];
+
 #[derive(Default)]
-pub struct Minions {}
+pub struct Minions {
+    // the gameplay systems, dispatched once per fixed tick
+    simulation: Option<Dispatcher<'static, 'static>>,
+    // fractions of ticks left over when the console slows the match down
+    owed_ticks: f32,
+}
 
 impl SimpleState for Minions {
     fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
-        initialize_debug_lines(data.world);
-        initialize_ground(data.world);
-        initialize_camera(data.world);
-        initialize_light(data.world);
+        initialize_arena(data.world);
+        initialize_scenario(data.world);
+        self.simulation = Some(simulation::build_dispatcher(data.world));
+        console::run_startup(data.world);
+    }
+
+    fn fixed_update(&mut self, data: StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        let dispatcher = match &mut self.simulation {
+            Some(dispatcher) => dispatcher,
+            None => return Trans::None,
+        };
+        self.owed_ticks += data
+            .world
+            .try_fetch::<SimulationSpeed>()
+            .map_or(1.0, |speed| speed.0);
+        while self.owed_ticks >= 1.0 {
+            self.owed_ticks -= 1.0;
+            // in a lan match the simulation waits for the commands of the other players
+            if let Some(mut lockstep) = data.world.try_fetch_mut::<Lockstep>() {
+                let tick = data.world.read_resource::<Simulation>().tick();
+                lockstep.poll(tick);
+                if let Some(error) = lockstep.error() {
+                    return Trans::Switch(Box::new(GameOver::interrupted(error.to_string())));
+                }
+                if !lockstep.ready(tick) {
+                    self.owed_ticks = 0.0;
+                    return Trans::None;
+                }
+            }
+            simulation::step(dispatcher, data.world);
+            if let Some(mut lockstep) = data.world.try_fetch_mut::<Lockstep>() {
+                lockstep.verify(&data.world.read_resource::<Simulation>());
+                if let Some(error) = lockstep.error() {
+                    return Trans::Switch(Box::new(GameOver::interrupted(error.to_string())));
+                }
+            }
+            if data.world.read_resource::<Victory>().is_over() {
+                return Trans::Switch(Box::new(GameOver::default()));
+            }
+        }
+        Trans::None
+    }
+
+    fn handle_event(
+        &mut self,
+        _data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_key_down(event, MENU_KEY) {
+                return Trans::Push(Box::new(OptionsMenu::default()));
+            }
+            if is_key_down(event, CONSOLE_KEY) {
+                return Trans::Push(Box::new(DeveloperConsole::default()));
+            }
+        }
+        Trans::None
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(recorder) = data.world.try_fetch::<ReplayRecorder>() {
+            match recorder.replay.write(&recorder.path) {
+                Ok(()) => info!("saved the replay to {:?}", recorder.path),
+                Err(e) => error!("failed to save the replay to {:?}: {}", recorder.path, e),
+            }
+        }
     }
 }
 
+// everything shown by the game and the editor
+pub fn initialize_arena(world: &mut World) {
+    initialize_debug_lines(world);
+    initialize_ground(world);
+    initialize_minion_assets(world);
+    initialize_gold_mines(world);
+    initialize_buildings(world);
+    initialize_camera(world);
+    initialize_light(world);
+}
+
+// the scripts, waves and victory conditions of the scenario start over with the simulation
+pub fn initialize_scenario(world: &mut World) {
+    let (scripts, defense) = {
+        let arena_config = world.read_resource::<ArenaConfig>();
+        (
+            Scripts::new(&arena_config.scripts),
+            arena_config.defense.as_ref().map(Defense::new),
+        )
+    };
+    world.insert(scripts);
+    match defense {
+        Some(defense) => world.insert(defense),
+        None => {
+            world.remove::<Defense>();
+        }
+    }
+    world.insert(Victory::default());
+}
+
 #[derive(Debug)]
 pub struct WorldBorders {
     pub right: f32,
     pub left: f32,
     pub bottom: f32,
     pub top: f32,
+    pub topology: Topology,
+    // in tiles
+    pub width: i16,
+    pub depth: i16,
 }
+impl WorldBorders {
+    // indices of the tile at a world-position, None if it is outside of the arena
+    pub fn tile_at(&self, x: f32, z: f32, tile_size: f32) -> Option<(i16, i16)> {
+        let (x, z) = self
+            .topology
+            .tile_at(((x - self.left) / tile_size, (z - self.bottom) / tile_size));
+        if self.contains_tile(x, z) {
+            Some((x, z))
+        } else {
+            None
+        }
+    }
+
+    // world-position of the center of a tile
+    pub fn tile_center(&self, x: i16, z: i16, tile_size: f32) -> (f32, f32) {
+        let (center_x, center_z) = self.topology.center((x, z));
+        (
+            self.left + center_x * tile_size,
+            self.bottom + center_z * tile_size,
+        )
+    }
+
+    pub fn contains_tile(&self, x: i16, z: i16) -> bool {
+        0 <= x && x < self.width && 0 <= z && z < self.depth
+    }
+}
+
 impl Default for WorldBorders {
     fn default() -> Self {
         WorldBorders {
@@ -53,118 +223,422 @@ impl Default for WorldBorders {
             left: -1.0,
             bottom: -1.0,
             top: 1.0,
+            topology: Topology::Square,
+            width: 2,
+            depth: 2,
         }
     }
 }
 
+// the DebugSystem draws the layers the player turned on
 fn initialize_debug_lines(world: &mut World) {
     world.insert(DebugLines::new());
     world.insert(DebugLinesParams { line_width: 1.0 });
-    let mut debug_lines_component = DebugLinesComponent::with_capacity(3);
-
-    debug_lines_component.add_direction(
-        Point3::new(0.0, 0.0001, 0.0),
-        Vector3::new(1.0, 0.0, 0.0),
-        Srgba::new(1.0, 0.0, 0.23, 1.0),
-    );
-    debug_lines_component.add_direction(
-        Point3::new(0.0, 0.0, 0.0),
-        Vector3::new(0.0, 1.0, 0.0),
-        Srgba::new(0.5, 0.85, 0.1, 1.0),
-    );
-    debug_lines_component.add_direction(
-        Point3::new(0.0, 0.0001, 0.0),
-        Vector3::new(0.0, 0.0, 1.0),
-        Srgba::new(0.2, 0.75, 0.93, 1.0),
-    );
-
     world.register::<DebugLinesComponent>();
-    world.create_entity().with(debug_lines_component).build();
 }
 
 fn initialize_ground(world: &mut World) {
     let mat_defaults = world.read_resource::<MaterialDefaults>().0.clone();
-    let mesh = create_plane(world);
-	let basic_albedo = create_albedo(world, 0.005);
-	let hover_albedo = create_albedo(world, 0.01);
     let roughness = 1.0f32;
     let metallic = 1.0f32;
 
     // load config
-    let (width, depth, tile_size) = {
+    let (terrain_map, world_borders, tile_size) = {
         let arena_config = world.read_resource::<ArenaConfig>();
         (
-            arena_config.width,
-            arena_config.depth,
+            terrain_map(&arena_config),
+            world_borders(&arena_config),
             arena_config.tile_size,
         )
     };
-	
-	let basic_mtl = create_material(world, roughness, metallic, basic_albedo.clone(), mat_defaults.clone());
-	let hover_mtl = create_material(world, roughness, metallic, hover_albedo.clone(), mat_defaults.clone());
+    let (width, depth) = (world_borders.width, world_borders.depth);
+    let teams = world.read_resource::<PlayerConfig>().teams;
 
-    // initialize planes
-    // last term because coords are in center of plane
-    let x0 = -(tile_size * width as f32 / 2.) + 0.5 * tile_size;
-    let z0 = -(tile_size * depth as f32 / 2.) + 0.5 * tile_size;
+    // all tiles of one terrain share their materials
+    let mut tile_materials = HashMap::new();
+    for &terrain in Terrain::ALL.iter() {
+        let materials = create_tile_materials(
+            world,
+            terrain_color(terrain),
+            roughness,
+            metallic,
+            mat_defaults.clone(),
+        );
+        tile_materials.insert(terrain, materials);
+    }
 
     for x in 0..width {
         for y in 0..depth {
+            let mesh = create_tile_mesh(world, &terrain_map, x, y, tile_size);
+            let (center_x, center_z) = world_borders.tile_center(x, y, tile_size);
             let mut pos = Transform::default();
-            pos.append_rotation_x_axis(-1.5707);
-            pos.set_translation_xyz(
-                x0 + (tile_size * x as f32),
-                0.0,
-                z0 + (tile_size * y as f32),
-			);
-			let piece_info = PieceInfo::new(x, y, basic_mtl.clone(), hover_mtl.clone());
+            pos.set_translation_xyz(center_x, 0.0, center_z);
+            let terrain = terrain_map.get(x, y).unwrap_or_default();
+            let piece_info = PieceInfo::new(x, y, tile_materials[&terrain].clone());
             world
                 .create_entity()
                 .with(pos.clone())
-                .with(mesh.clone())
-                .with(basic_mtl.clone())
+                .with(mesh)
+                .with(piece_info.material())
                 .with(piece_info)
                 .build();
         }
     }
 
-    // create grid-lines
-    let mut debug_lines_component = DebugLinesComponent::with_capacity((width * depth) as usize);
-    let main_color = Srgba::new(0.0, 0.0, 0.0, 0.8);
-    for x in 0..=width {
-        let position = Point3::new(
-            x0 - tile_size / 2.0 + (x as f32 * tile_size),
-            0.0,
-            z0 - tile_size / 2.00,
+    // filled by the DebugSystem while the grid layer is shown
+    world
+        .create_entity()
+        .with(DebugLinesComponent::new())
+        .with(GridLines)
+        .build();
+    world.insert(TerrainAssets {
+        materials: tile_materials,
+    });
+    world.insert(terrain_map);
+    world.insert(FogOfWar::new(width, depth, teams));
+    world.insert(world_borders);
+}
+
+pub fn terrain_map(arena_config: &ArenaConfig) -> TerrainMap {
+    let mut terrain_map = TerrainMap::new(
+        arena_config.width,
+        arena_config.depth,
+        arena_config.topology,
+    );
+    for tile in &arena_config.terrain {
+        terrain_map.set(tile.x, tile.z, tile.terrain);
+    }
+    // single heights are applied on top of the heightmap
+    if let Some(heightmap) = &arena_config.heightmap {
+        if let Err(e) = load_heightmap(heightmap, &mut terrain_map) {
+            error!("failed to load the heightmap {}: {}", heightmap.path, e);
+        }
+    }
+    for tile in &arena_config.heights {
+        terrain_map.set_height(tile.x, tile.z, tile.height);
+    }
+    terrain_map
+}
+
+// the arena is centered around the origin
+pub fn world_borders(arena_config: &ArenaConfig) -> WorldBorders {
+    let (width, depth, tile_size) = (
+        arena_config.width,
+        arena_config.depth,
+        arena_config.tile_size,
+    );
+    let (extent_x, extent_z) = arena_config.topology.extent(width, depth);
+    WorldBorders {
+        left: -extent_x * tile_size / 2.0,
+        right: extent_x * tile_size / 2.0,
+        bottom: -extent_z * tile_size / 2.0,
+        top: extent_z * tile_size / 2.0,
+        topology: arena_config.topology,
+        width,
+        depth,
+    }
+}
+
+fn initialize_minion_assets(world: &mut World) {
+    let mat_defaults = world.read_resource::<MaterialDefaults>().0.clone();
+    let mesh = create_mesh(world, Shape::Cube);
+    let team_materials = TEAM_COLORS
+        .iter()
+        .map(|&(r, g, b)| {
+            let albedo = create_colored_albedo(world, LinSrgba::new(r, g, b, 1.0));
+            create_material(world, 1.0, 0.0, albedo, mat_defaults.clone())
+        })
+        .collect();
+    let (r, g, b) = GOLD_COLOR;
+    let albedo = create_colored_albedo(world, LinSrgba::new(r, g, b, 1.0));
+    let gold_material = create_material(world, 0.3, 1.0, albedo, mat_defaults);
+    world.insert(MinionAssets {
+        mesh,
+        team_materials,
+        gold_material,
+    });
+}
+
+fn initialize_gold_mines(world: &mut World) {
+    let (gold_mines, tile_size) = {
+        let arena_config = world.read_resource::<ArenaConfig>();
+        (arena_config.gold_mines.clone(), arena_config.tile_size)
+    };
+    let (mesh, material) = {
+        let assets = world.read_resource::<MinionAssets>();
+        (assets.mesh.clone(), assets.gold_material.clone())
+    };
+
+    for config in gold_mines {
+        let pos = gold_mine_transform(
+            &config,
+            &world.read_resource::<WorldBorders>(),
+            &world.read_resource::<TerrainMap>(),
+            tile_size,
         );
-        let direction = Vector3::new(0.0, 0.0, tile_size * depth as f32);
-        debug_lines_component.add_direction(position, direction, main_color);
-    }
-    for z in 0..=depth {
-        let position = Point3::new(
-            x0 - tile_size / 2.0,
-            0.0,
-            z0 - tile_size / 2.00 + (z as f32 * tile_size),
+        world
+            .create_entity()
+            .with(pos)
+            .with(mesh.clone())
+            .with(material.clone())
+            .with(GoldMine)
+            .build();
+    }
+}
+
+// also used to reset the buildings when a replay is rewound
+pub fn initialize_buildings(world: &mut World) {
+    let (buildings, tile_size) = {
+        let arena_config = world.read_resource::<ArenaConfig>();
+        (arena_config.buildings.clone(), arena_config.tile_size)
+    };
+    let unit_ids = world.read_resource::<UnitRegistry>().ids();
+
+    for config in buildings {
+        let (mesh, material) = {
+            let assets = world.read_resource::<MinionAssets>();
+            (assets.mesh.clone(), assets.team_material(config.team))
+        };
+        let pos = building_transform(
+            &Building::new(config.x, config.z, config.width, config.depth),
+            &world.read_resource::<WorldBorders>(),
+            &world.read_resource::<TerrainMap>(),
+            tile_size,
         );
-        let direction = Vector3::new(tile_size * width as f32, 0.0, 0.0 as f32);
-        debug_lines_component.add_direction(position, direction, main_color);
-    }
-    world.create_entity().with(debug_lines_component).build();
-
-    let x0 = x0 - 0.5 * tile_size;
-    let z0 = z0 - 0.5 * tile_size;
-    world.insert(WorldBorders {
-        left: x0,
-        right: x0 + tile_size * width as f32,
-        top: z0 + tile_size * depth as f32,
-        bottom: z0,
-    })
+
+        create_building(world, config, &unit_ids)
+            .with(pos)
+            .with(mesh)
+            .with(material)
+            .build();
+    }
+}
+
+// the components of a building the simulation needs, the game adds how it looks
+pub fn create_building<'a>(
+    world: &'a mut World,
+    config: BuildingConfig,
+    unit_ids: &[String],
+) -> EntityBuilder<'a> {
+    let id = world.write_resource::<Simulation>().new_id();
+    world
+        .create_entity()
+        .with(Building::new(
+            config.x,
+            config.z,
+            config.width,
+            config.depth,
+        ))
+        .with(ProductionQueue::new(if config.produces.is_empty() {
+            unit_ids.to_vec()
+        } else {
+            config.produces
+        }))
+        .with(Team(config.team))
+        .with(Sight(BUILDING_SIGHT))
+        .with(Health(config.health.unwrap_or(BUILDING_HEALTH)))
+        .with(id)
 }
 
-fn create_plane(world: &mut World) -> Handle<Mesh> {
+pub fn gold_mine_transform(
+    config: &GoldMineConfig,
+    world_borders: &WorldBorders,
+    terrain_map: &TerrainMap,
+    tile_size: f32,
+) -> Transform {
+    let (center_x, center_z) = world_borders.tile_center(config.x, config.z, tile_size);
+    let height = terrain_map.height(config.x, config.z);
+
+    let mut pos = Transform::default();
+    pos.set_translation_xyz(center_x, height + tile_size * 0.15, center_z);
+    pos.set_scale(Vector3::new(
+        tile_size * 0.3,
+        tile_size * 0.15,
+        tile_size * 0.3,
+    ));
+    pos
+}
+
+pub fn building_transform(
+    building: &Building,
+    world_borders: &WorldBorders,
+    terrain_map: &TerrainMap,
+    tile_size: f32,
+) -> Transform {
+    let (x, z) = building.center(world_borders.topology);
+    let (center_x, center_z) = (
+        world_borders.left + x * tile_size,
+        world_borders.bottom + z * tile_size,
+    );
+    // buildings stand on the highest tile of their footprint
+    let base = (building.x..building.x + building.width)
+        .flat_map(|x| (building.z..building.z + building.depth).map(move |z| (x, z)))
+        .map(|(x, z)| terrain_map.height(x, z))
+        .fold(0.0, f32::max);
+
+    // the cube-mesh is two units wide
+    let mut pos = Transform::default();
+    pos.set_translation_xyz(center_x, base + tile_size / 2.0, center_z);
+    pos.set_scale(Vector3::new(
+        building.width as f32 * tile_size * 0.45,
+        tile_size / 2.0,
+        building.depth as f32 * tile_size * 0.45,
+    ));
+    pos
+}
+
+// grid-lines following the edges of the tiles over the terrain,
+// an edge between two tiles is drawn by the tile which comes first
+pub fn grid_lines(
+    terrain_map: &TerrainMap,
+    world_borders: &WorldBorders,
+    tile_size: f32,
+) -> DebugLinesComponent {
+    let topology = terrain_map.topology();
+    let (width, depth) = (terrain_map.width(), terrain_map.depth());
+    let corners = topology.corners();
+    let mut debug_lines_component =
+        DebugLinesComponent::with_capacity(width as usize * depth as usize * corners.len());
+    let main_color = Srgba::new(0.0, 0.0, 0.0, 0.8);
+    for x in 0..width {
+        for z in 0..depth {
+            let (tile_x, tile_z) = topology.center((x, z));
+            let (center_x, center_z) = world_borders.tile_center(x, z, tile_size);
+            let corner = |i: usize| {
+                let (dx, dz) = corners[i];
+                Point3::new(
+                    center_x + dx * tile_size,
+                    terrain_map.corner_height((x, z), i) + 0.001,
+                    center_z + dz * tile_size,
+                )
+            };
+            for i in 0..corners.len() {
+                let next = (i + 1) % corners.len();
+                // the middle of the edge is halfway to the center of the tile beyond it
+                let beyond = topology.tile_at((
+                    tile_x + corners[i].0 + corners[next].0,
+                    tile_z + corners[i].1 + corners[next].1,
+                ));
+                if !terrain_map.contains(beyond.0, beyond.1) || beyond > (x, z) {
+                    debug_lines_component.add_line(corner(i), corner(next), main_color);
+                }
+            }
+        }
+    }
+    debug_lines_component
+}
+
+fn terrain_color(terrain: Terrain) -> (f32, f32, f32) {
+    match terrain {
+        Terrain::Grass => (0.005, 0.005, 0.005),
+        Terrain::Rock => (0.02, 0.012, 0.006),
+    }
+}
+
+fn create_tile_materials(
+    world: &mut World,
+    (r, g, b): (f32, f32, f32),
+    roughness: f32,
+    metallic: f32,
+    mat_defaults: Material,
+) -> TileMaterials {
+    let material = |world: &mut World, brightness: f32| {
+        let albedo = create_colored_albedo(
+            world,
+            LinSrgba::new(r * brightness, g * brightness, b * brightness, 1.0),
+        );
+        create_material(world, roughness, metallic, albedo, mat_defaults.clone())
+    };
+    TileMaterials {
+        basic: material(world, 1.0),
+        hover: material(world, 2.0),
+        explored: material(world, 0.3),
+        unexplored: material(world, 0.0),
+    }
+}
+
+fn load_heightmap(config: &HeightmapConfig, terrain_map: &mut TerrainMap) -> amethyst::Result<()> {
+    let image = image::open(application_root_dir()?.join(&config.path))?.to_luma();
+    let (image_width, image_depth) = image.dimensions();
+    let (width, depth) = (terrain_map.width(), terrain_map.depth());
+
+    // the first row of the image is the back of the arena
+    for x in 0..width {
+        for z in 0..depth {
+            let pixel_x = (x as u32 * image_width / width as u32).min(image_width - 1);
+            let pixel_z = (z as u32 * image_depth / depth as u32).min(image_depth - 1);
+            let brightness = image.get_pixel(pixel_x, image_depth - 1 - pixel_z).0[0];
+            terrain_map.set_height(x, z, brightness as f32 / 255.0 * config.max_height);
+        }
+    }
+    Ok(())
+}
+
+fn create_tile_mesh(
+    world: &mut World,
+    terrain_map: &TerrainMap,
+    x: i16,
+    z: i16,
+    tile_size: f32,
+) -> Handle<Mesh> {
+    let mesh = tile_mesh(terrain_map, x, z, tile_size);
+    world.exec(|loader: AssetLoaderSystemData<'_, Mesh>| loader.load_from_data(mesh, ()))
+}
+
+// a fan of triangles from the center of the tile to its corners
+pub fn tile_mesh(terrain_map: &TerrainMap, x: i16, z: i16, tile_size: f32) -> MeshData {
+    let center = Vector3::new(0.0, terrain_map.height(x, z), 0.0);
+    let corners: Vec<Vector3<f32>> = terrain_map
+        .topology()
+        .corners()
+        .iter()
+        .enumerate()
+        .map(|(i, &(dx, dz))| {
+            Vector3::new(
+                dx * tile_size,
+                terrain_map.corner_height((x, z), i),
+                dz * tile_size,
+            )
+        })
+        .collect();
+
+    let vertices = 3 * corners.len();
+    let mut positions = Vec::with_capacity(vertices);
+    let mut normals = Vec::with_capacity(vertices);
+    let mut tangents = Vec::with_capacity(vertices);
+    let mut tex_coords = Vec::with_capacity(vertices);
+    for (i, &corner) in corners.iter().enumerate() {
+        let (mut first, mut second) = (corner, corners[(i + 1) % corners.len()]);
+        let mut normal = (first - center).cross(&(second - center));
+        // counter-clockwise seen from above
+        if normal.y < 0.0 {
+            std::mem::swap(&mut first, &mut second);
+            normal = -normal;
+        }
+        let normal = normal.normalize();
+        for vertex in [center, first, second].iter() {
+            positions.push(Position([vertex.x, vertex.y, vertex.z]));
+            normals.push(Normal([normal.x, normal.y, normal.z]));
+            tangents.push(Tangent([1.0, 0.0, 0.0, 1.0]));
+            tex_coords.push(TexCoord([
+                vertex.x / tile_size + 0.5,
+                vertex.z / tile_size + 0.5,
+            ]));
+        }
+    }
+
+    MeshBuilder::new()
+        .with_vertices(positions)
+        .with_vertices(normals)
+        .with_vertices(tangents)
+        .with_vertices(tex_coords)
+        .into()
+}
+
+fn create_mesh(world: &mut World, shape: Shape) -> Handle<Mesh> {
     world.exec(|loader: AssetLoaderSystemData<'_, Mesh>| {
         loader.load_from_data(
-            Shape::Plane(None)
+            shape
                 .generate::<(Vec<Position>, Vec<Normal>, Vec<Tangent>, Vec<TexCoord>)>(None)
                 .into(),
             (),
@@ -172,31 +646,44 @@ fn create_plane(world: &mut World) -> Handle<Mesh> {
     })
 }
 
-fn create_albedo(world: &mut World, brightness: f32) -> Handle<Texture> {
+fn create_colored_albedo(world: &mut World, color: LinSrgba) -> Handle<Texture> {
     world.exec(|loader: AssetLoaderSystemData<'_, Texture>| {
-        loader.load_from_data(
-            load_from_linear_rgba(LinSrgba::new(brightness, brightness, brightness, 1.0)).into(),
-            (),
-        )
+        loader.load_from_data(load_from_linear_rgba(color).into(), ())
     })
 }
 
 fn initialize_camera(world: &mut World) {
     // load config
-    let camera_tilt = {
+    let (camera_tilt, projection) = {
         let camera_config = world.read_resource::<CameraConfig>();
-        camera_config.camera_tilt
+        (camera_config.camera_tilt, camera_config.projection)
     };
     assert!(-PI / 2.0 < camera_tilt && camera_tilt < 0.0);
 
     let mut transform = Transform::default();
     transform.set_translation_xyz(0.0, 7.0, 10.0);
     transform.prepend_rotation_x_axis(camera_tilt);
-    let camera = Camera::perspective(1.3, 1.0271975512, 0.1);
 
-    world
-        .create_entity()
-        .with(camera)
+    let aspect = world
+        .try_fetch::<ScreenDimensions>()
+        .map_or(1.3, |screen| screen.aspect_ratio());
+    let builder = match projection {
+        CameraProjection::Perspective => world.create_entity().with(perspective_camera(aspect)),
+        CameraProjection::Orthographic => {
+            // high above the point the perspective camera would look at
+            let direction = transform.rotation() * -Vector3::z();
+            let ground =
+                transform.translation() + direction * (transform.translation().y / -direction.y);
+            transform.translation_mut().y = OrthographicExtent::HEIGHT;
+            look_at(&mut transform, ground.x, ground.y, ground.z);
+            let extent = OrthographicExtent::default();
+            world
+                .create_entity()
+                .with(extent.camera(aspect))
+                .with(extent)
+        }
+    };
+    builder
         .with(transform)
         .with(CameraBorders::default()) // they are useless before the first zoom
         .with(CameraControlTag)
@@ -238,4 +725,4 @@ fn create_material(world: &mut World, roughness: f32, metallic: f32, albedo: Han
             )
         },
     )
-}
\ No newline at end of file
+}
@@ -0,0 +1,332 @@
+// lan matches: the host waits in a lobby until every team has a player, afterwards
+// it relays the commands of each player to the others and all of them simulate in lockstep
+
+use amethyst::Error;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    mem,
+    net::{SocketAddr, TcpListener, TcpStream},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    config::{ArenaConfig, PlayerConfig},
+    orders::OrderKind,
+};
+
+// the dedicated server listens on it unless told otherwise
+pub const DEFAULT_PORT: u16 = 7777;
+
+// ticks between issuing an order and applying it, hides the latency of the network
+pub const INPUT_DELAY: u64 = 6;
+
+// the host gives up when the lobby isn't full by then
+const LOBBY_TIMEOUT: Duration = Duration::from_secs(600);
+// between looking for new and lost players in the lobby
+const LOBBY_POLL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Message {
+    // sent by the host to a player who joined
+    Welcome {
+        team: u8,
+        setup: MatchSetup,
+    },
+    // sent by the host once every team has a player
+    Start,
+    // sent by a host which has no room for another player before it disconnects
+    Refused {
+        reason: String,
+    },
+    // the orders of a team for one tick and the checksum of a tick it finished
+    Commands {
+        team: u8,
+        tick: u64,
+        orders: Vec<OrderKind<u32>>,
+        checksum: Option<(u64, u64)>,
+    },
+    // sent by the host when a player disconnected, it sends no commands after last_tick
+    Left {
+        team: u8,
+        last_tick: u64,
+    },
+}
+
+// everything the players have to agree on before the match starts
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MatchSetup {
+    pub seed: u64,
+    pub arena: ArenaConfig,
+    pub players: PlayerConfig,
+    pub input_delay: u64,
+}
+
+// a tcp-connection sending one message per line
+#[derive(Debug)]
+pub struct Connection {
+    // the team of the player at the other end, the host is team 0
+    pub team: u8,
+    stream: TcpStream,
+    incoming: Vec<u8>,
+    outgoing: Vec<u8>,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream, team: u8) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        Ok(Connection {
+            team,
+            stream,
+            incoming: Vec::new(),
+            outgoing: Vec::new(),
+        })
+    }
+
+    pub fn set_nonblocking(&self) -> io::Result<()> {
+        self.stream.set_nonblocking(true)
+    }
+
+    pub fn send(&mut self, message: &Message) -> io::Result<()> {
+        let line = ron::ser::to_string(message)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidData, e.to_string()))?;
+        self.outgoing.extend_from_slice(line.as_bytes());
+        self.outgoing.push(b'\n');
+        self.flush()
+    }
+
+    // the messages which arrived since the last call, an error means the connection is lost
+    pub fn receive(&mut self) -> io::Result<Vec<Message>> {
+        self.flush()?;
+        loop {
+            match self.fill() {
+                Ok(_) => {}
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+        let mut messages = Vec::new();
+        while let Some(message) = self.next_message()? {
+            messages.push(message);
+        }
+        Ok(messages)
+    }
+
+    // an error once the player left the lobby, nothing is sent before the match starts
+    fn receive_in_lobby(&mut self) -> io::Result<()> {
+        for message in self.receive()? {
+            warn!("team {} sent {:?} in the lobby", self.team, message);
+        }
+        Ok(())
+    }
+
+    // blocks until the next message arrives, only used in the lobby
+    fn wait(&mut self) -> io::Result<Message> {
+        loop {
+            if let Some(message) = self.next_message()? {
+                return Ok(message);
+            }
+            self.fill()?;
+        }
+    }
+
+    fn fill(&mut self) -> io::Result<()> {
+        let mut buffer = [0; 4096];
+        match self.stream.read(&mut buffer)? {
+            0 => Err(ErrorKind::UnexpectedEof.into()),
+            read => {
+                self.incoming.extend_from_slice(&buffer[..read]);
+                Ok(())
+            }
+        }
+    }
+
+    fn next_message(&mut self) -> io::Result<Option<Message>> {
+        let end = match self.incoming.iter().position(|&byte| byte == b'\n') {
+            Some(end) => end,
+            None => return Ok(None),
+        };
+        let line: Vec<u8> = self.incoming.drain(..=end).collect();
+        ron::de::from_bytes(&line[..end])
+            .map(Some)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidData, e.to_string()))
+    }
+
+    // writes as much as the socket takes without blocking
+    fn flush(&mut self) -> io::Result<()> {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(written) => {
+                    self.outgoing.drain(..written);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+// the players waiting for a match, a player who leaves frees the team for the next one
+pub struct Lobby {
+    // the teams from this one on are filled by the players who join
+    first_team: u8,
+    teams: u8,
+    players: Vec<Connection>,
+}
+
+impl Lobby {
+    pub fn new(first_team: u8, teams: u8) -> Self {
+        Lobby {
+            first_team,
+            teams,
+            players: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.players.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.players.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.players.len() >= usize::from(self.teams.saturating_sub(self.first_team))
+    }
+
+    // welcomes a player to the lowest free team
+    pub fn join(&mut self, stream: TcpStream, address: SocketAddr, setup: &MatchSetup) {
+        self.remove_lost();
+        let team = match (self.first_team..self.teams)
+            .find(|&team| self.players.iter().all(|player| player.team != team))
+        {
+            Some(team) => team,
+            None => {
+                refuse(stream, address, "the lobby is full");
+                return;
+            }
+        };
+        let welcomed = Connection::new(stream, team).and_then(|mut connection| {
+            // the lobby notices a player who leaves before the match starts
+            connection.set_nonblocking()?;
+            connection.send(&Message::Welcome {
+                team,
+                setup: setup.clone(),
+            })?;
+            Ok(connection)
+        });
+        match welcomed {
+            Ok(connection) => {
+                info!("player {} joined the lobby from {}", team, address);
+                self.players.push(connection);
+            }
+            Err(e) => warn!("failed to welcome {}: {}", address, e),
+        }
+    }
+
+    // forgets the players whose connection closed
+    pub fn remove_lost(&mut self) {
+        for mut player in mem::take(&mut self.players) {
+            match player.receive_in_lobby() {
+                Ok(()) => self.players.push(player),
+                Err(e) => info!("player {} left the lobby: {}", player.team, e),
+            }
+        }
+    }
+
+    // tells everybody the match starts, the players who left are noticed by the lockstep
+    pub fn start(&mut self) -> Vec<Connection> {
+        let mut players = mem::take(&mut self.players);
+        for player in &mut players {
+            if let Err(e) = player.send(&Message::Start) {
+                warn!("failed to start the match for team {}: {}", player.team, e);
+            }
+        }
+        players
+    }
+}
+
+// tells a player there is no room before disconnecting
+pub fn refuse(stream: TcpStream, address: SocketAddr, reason: &str) {
+    warn!("refused {}, {}", address, reason);
+    let refused = Connection::new(stream, 0).and_then(|mut connection| {
+        connection.send(&Message::Refused {
+            reason: reason.to_string(),
+        })
+    });
+    if let Err(e) = refused {
+        warn!("failed to refuse {}: {}", address, e);
+    }
+}
+
+// waits until a player joined for every team but the first, which is the host's
+pub fn host(port: u16, setup: &MatchSetup) -> Result<Vec<Connection>, Error> {
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    listener.set_nonblocking(true)?;
+    info!(
+        "hosting on port {}, waiting for {} players",
+        port,
+        setup.players.teams.saturating_sub(1)
+    );
+    let mut lobby = Lobby::new(1, setup.players.teams);
+    let start = Instant::now();
+    loop {
+        match listener.accept() {
+            Ok((stream, address)) => {
+                lobby.join(stream, address, setup);
+                continue;
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e.into()),
+        }
+        lobby.remove_lost();
+        if lobby.is_full() {
+            return Ok(lobby.start());
+        }
+        if start.elapsed() >= LOBBY_TIMEOUT {
+            return Err(Error::from_string(format!(
+                "only {} of {} players joined in {} minutes",
+                lobby.len(),
+                setup.players.teams.saturating_sub(1),
+                LOBBY_TIMEOUT.as_secs() / 60
+            )));
+        }
+        thread::sleep(LOBBY_POLL);
+    }
+}
+
+// joins the lobby of a host and waits for the match to start
+pub fn join(address: &str) -> Result<(u8, MatchSetup, Connection), Error> {
+    let mut connection = Connection::new(TcpStream::connect(address)?, 0)?;
+    let (team, setup) = match connection.wait()? {
+        Message::Welcome { team, setup } => (team, setup),
+        Message::Refused { reason } => {
+            return Err(Error::from_string(format!(
+                "the host refused to let us join: {}",
+                reason
+            )))
+        }
+        message => {
+            return Err(Error::from_string(format!(
+                "unexpected message from the host: {:?}",
+                message
+            )))
+        }
+    };
+    info!(
+        "joined {} as team {}, waiting for the other players",
+        address, team
+    );
+    loop {
+        if let Message::Start = connection.wait()? {
+            break;
+        }
+    }
+    connection.set_nonblocking()?;
+    Ok((team, setup, connection))
+}
@@ -0,0 +1,421 @@
+// Rebind the keys of the game, opened with F10 during a match. changes apply at once,
+// saving writes them to the user input file which overrides the shipped one
+
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    ecs::Entity,
+    input::{is_close_requested, Axis, Bindings, Button, InputHandler, VirtualKeyCode},
+    prelude::*,
+    ui::{
+        get_default_font, Anchor, FontAsset, FontHandle, Interactable, LineMode, UiEvent,
+        UiEventType, UiText, UiTransform,
+    },
+    utils::application_root_dir,
+    winit::{ElementState, Event, KeyboardInput, ModifiersState, WindowEvent},
+};
+use log::{error, info};
+
+use crate::input::{
+    axis_keys, combo_name, save_bindings, shipped_bindings, with_axis_keys, ActionBinding,
+    AxisBinding, Layout, MovementBindingTypes,
+};
+
+// opens and closes the menu, it can't be rebound so nobody locks themselves out
+pub const MENU_KEY: VirtualKeyCode = VirtualKeyCode::F10;
+
+const FONT_SIZE: f32 = 16.0;
+// in pixels
+const ROW_HEIGHT: f32 = 20.0;
+const COLUMN_WIDTH: f32 = 380.0;
+const MARGIN: f32 = 20.0;
+const ROWS_PER_COLUMN: usize = 28;
+
+const TEXT_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+const BUTTON_COLOR: [f32; 4] = [0.9, 0.8, 0.1, 1.0];
+
+#[derive(Clone, Debug)]
+enum Row {
+    Axis(AxisBinding),
+    Action(ActionBinding),
+    Preset(Layout),
+    Save,
+    Back,
+}
+
+// the binding which waits for a key or mouse button
+#[derive(Clone, Debug)]
+enum Capture {
+    Action(ActionBinding),
+    // the positive button comes first, e.g. right before left
+    AxisPos(AxisBinding),
+    AxisNeg(AxisBinding, Button),
+}
+
+#[derive(Default)]
+pub struct OptionsMenu {
+    bindings: Bindings<MovementBindingTypes>,
+    rows: Vec<(Entity, Row)>,
+    status: Option<Entity>,
+    capture: Option<Capture>,
+}
+
+impl SimpleState for OptionsMenu {
+    // the match below waits while the menu is open, in a lan match the others wait as well
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+        self.bindings = world
+            .read_resource::<InputHandler<MovementBindingTypes>>()
+            .bindings
+            .clone();
+        let font = get_default_font(
+            &world.read_resource::<Loader>(),
+            &world.read_resource::<AssetStorage<FontAsset>>(),
+        );
+
+        let mut axes: Vec<_> = self.bindings.axes().cloned().collect();
+        axes.sort_by_key(|axis| axis.to_string());
+        let mut actions: Vec<_> = self.bindings.actions().cloned().collect();
+        actions.sort_by_key(|action| action.to_string());
+        let rows = axes
+            .into_iter()
+            .map(Row::Axis)
+            .chain(actions.into_iter().map(Row::Action))
+            .chain(Layout::ALL.iter().copied().map(Row::Preset))
+            .chain(vec![Row::Save, Row::Back]);
+
+        // the status line comes first, the rows fill the columns below it
+        self.status = Some(create_text(world, &font, "status".to_string(), 0, false));
+        self.rows = rows
+            .enumerate()
+            .map(|(index, row)| {
+                let button = !matches!(row, Row::Axis(_) | Row::Action(_));
+                let entity = create_text(world, &font, format!("{:?}", row), index + 1, button);
+                (entity, row)
+            })
+            .collect();
+        self.set_status(
+            world,
+            "click a binding and press the new key, escape cancels",
+        );
+        self.refresh(world);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let entities: Vec<_> = self
+            .rows
+            .drain(..)
+            .map(|(entity, _)| entity)
+            .chain(self.status.take())
+            .collect();
+        if let Err(e) = data.world.delete_entities(&entities) {
+            error!("failed to remove the options menu: {}", e);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        let world = data.world;
+        match event {
+            StateEvent::Window(event) => {
+                if is_close_requested(&event) {
+                    return Trans::Quit;
+                }
+                let (button, modifiers) = match pressed_button(&event) {
+                    Some(pressed) => pressed,
+                    None => return Trans::None,
+                };
+                let capture = match self.capture.take() {
+                    Some(capture) => capture,
+                    None if button == Button::Key(VirtualKeyCode::Escape)
+                        || button == Button::Key(MENU_KEY) =>
+                    {
+                        return Trans::Pop
+                    }
+                    None => return Trans::None,
+                };
+                if button == Button::Key(VirtualKeyCode::Escape) {
+                    self.set_status(world, "cancelled");
+                } else {
+                    self.rebind(world, capture, button, modifiers);
+                }
+                self.refresh(world);
+            }
+            StateEvent::Ui(UiEvent {
+                event_type: UiEventType::Click,
+                target,
+            }) => {
+                let row = match self.rows.iter().find(|(entity, _)| *entity == target) {
+                    Some((_, row)) => row.clone(),
+                    None => return Trans::None,
+                };
+                match row {
+                    Row::Axis(axis) => match self.bindings.axis(&axis).and_then(axis_keys) {
+                        Some(_) => {
+                            self.set_status(world, &format!("press the positive key of {}", axis));
+                            self.capture = Some(Capture::AxisPos(axis));
+                        }
+                        _ => self.set_status(world, &format!("{} can't be changed", axis)),
+                    },
+                    Row::Action(action) => {
+                        self.set_status(world, &format!("press the new key of {}", action));
+                        self.capture = Some(Capture::Action(action));
+                    }
+                    Row::Preset(layout) => self.apply_preset(world, layout),
+                    Row::Save => match application_root_dir()
+                        .map_err(amethyst::Error::from)
+                        .and_then(|root| save_bindings(&root, &self.bindings))
+                    {
+                        Ok(()) => self.set_status(world, "saved the bindings"),
+                        Err(e) => {
+                            error!("failed to save the bindings: {}", e);
+                            self.set_status(world, "failed to save the bindings");
+                        }
+                    },
+                    Row::Back => return Trans::Pop,
+                }
+                self.refresh(world);
+            }
+            _ => {}
+        }
+        Trans::None
+    }
+}
+
+impl OptionsMenu {
+    fn rebind(
+        &mut self,
+        world: &mut World,
+        capture: Capture,
+        button: Button,
+        modifiers: ModifiersState,
+    ) {
+        let mut bindings = self.bindings.clone();
+        let result = match capture {
+            Capture::AxisPos(axis) => {
+                self.set_status(world, &format!("press the negative key of {}", axis));
+                self.capture = Some(Capture::AxisNeg(axis, button));
+                return;
+            }
+            Capture::AxisNeg(axis, pos) => {
+                // the sticks of the axis stay
+                let value = match bindings.remove_axis(&axis) {
+                    Some(value) => with_axis_keys(&value, pos.clone(), button.clone()),
+                    None => None,
+                }
+                .unwrap_or(Axis::Emulated { pos, neg: button });
+                bindings.insert_axis(axis, value).map(|_| ())
+            }
+            Capture::Action(action) => {
+                // the buttons of the gamepad stay
+                let old: Vec<Vec<Button>> = bindings
+                    .action_bindings(&action)
+                    .filter(|combo| {
+                        !combo
+                            .iter()
+                            .any(|button| matches!(button, Button::Controller(..)))
+                    })
+                    .map(<[_]>::to_vec)
+                    .collect();
+                for combo in old {
+                    bindings.remove_action_binding(&action, &combo);
+                }
+                bindings.insert_action_binding(action, combo(button, modifiers))
+            }
+        };
+        // a conflict keeps the old binding
+        match result
+            .map_err(|e| e.to_string())
+            .and_then(|()| bindings.check_invariants().map_err(|e| e.to_string()))
+        {
+            Ok(()) => {
+                self.set_status(world, "changed, save to keep the bindings");
+                self.apply(world, bindings);
+            }
+            Err(e) => self.set_status(world, &format!("conflict: {}", e)),
+        }
+    }
+
+    fn apply_preset(&mut self, world: &mut World, layout: Layout) {
+        let bindings = application_root_dir()
+            .map_err(amethyst::Error::from)
+            .and_then(|root| shipped_bindings(&root))
+            .and_then(|shipped| layout.bindings(&shipped));
+        match bindings {
+            Ok(bindings) => {
+                self.set_status(world, &format!("{} layout, save to keep it", layout));
+                self.apply(world, bindings);
+            }
+            Err(e) => {
+                error!("failed to load the {} layout: {}", layout, e);
+                self.set_status(world, &format!("failed to load the {} layout", layout));
+            }
+        }
+    }
+
+    fn apply(&mut self, world: &mut World, bindings: Bindings<MovementBindingTypes>) {
+        world
+            .write_resource::<InputHandler<MovementBindingTypes>>()
+            .bindings = bindings.clone();
+        self.bindings = bindings;
+    }
+
+    fn set_status(&self, world: &World, status: &str) {
+        info!("{}", status);
+        let mut texts = world.write_storage::<UiText>();
+        if let Some(text) = self.status.and_then(|entity| texts.get_mut(entity)) {
+            text.text = status.to_string();
+        }
+    }
+
+    // writes the current bindings into the rows
+    fn refresh(&self, world: &World) {
+        let mut texts = world.write_storage::<UiText>();
+        for (entity, row) in &self.rows {
+            let text = match row {
+                Row::Axis(axis) => {
+                    let value = self.bindings.axis(axis).map(axis_name);
+                    format!("{}: {}", axis, value.unwrap_or_default())
+                }
+                Row::Action(action) => {
+                    let combos: Vec<_> = self
+                        .bindings
+                        .action_bindings(action)
+                        .map(combo_name)
+                        .collect();
+                    format!("{}: {}", action, combos.join(" or "))
+                }
+                Row::Preset(layout) => format!("[use the {} layout]", layout),
+                Row::Save => "[save]".to_string(),
+                Row::Back => "[back]".to_string(),
+            };
+            let waiting = match (&self.capture, row) {
+                (Some(Capture::Action(captured)), Row::Action(action)) => captured == action,
+                (Some(Capture::AxisPos(captured)), Row::Axis(axis))
+                | (Some(Capture::AxisNeg(captured, _)), Row::Axis(axis)) => captured == axis,
+                _ => false,
+            };
+            if let Some(ui_text) = texts.get_mut(*entity) {
+                ui_text.text = if waiting {
+                    format!("{} ...", text)
+                } else {
+                    text
+                };
+            }
+        }
+    }
+}
+
+// the row at the index, counted from the status line
+fn create_text(
+    world: &mut World,
+    font: &FontHandle,
+    id: String,
+    index: usize,
+    button: bool,
+) -> Entity {
+    let (column, row) = if index == 0 {
+        (0, 0)
+    } else {
+        (
+            (index - 1) / ROWS_PER_COLUMN,
+            (index - 1) % ROWS_PER_COLUMN + 2,
+        )
+    };
+    let transform = UiTransform::new(
+        id,
+        Anchor::TopLeft,
+        Anchor::TopLeft,
+        MARGIN + column as f32 * COLUMN_WIDTH,
+        -(MARGIN + row as f32 * ROW_HEIGHT),
+        1.0,
+        COLUMN_WIDTH,
+        ROW_HEIGHT,
+    );
+    let text = UiText::new(
+        font.clone(),
+        String::new(),
+        if button { BUTTON_COLOR } else { TEXT_COLOR },
+        FONT_SIZE,
+        LineMode::Single,
+        Anchor::MiddleLeft,
+    );
+    world
+        .create_entity()
+        .with(transform)
+        .with(text)
+        .with(Interactable)
+        .build()
+}
+
+// the key or mouse button which was pressed with the held modifiers, modifiers alone
+// are part of the next combination
+fn pressed_button(event: &Event) -> Option<(Button, ModifiersState)> {
+    match event {
+        Event::WindowEvent {
+            event:
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(key),
+                            modifiers,
+                            ..
+                        },
+                    ..
+                },
+            ..
+        } => match key {
+            VirtualKeyCode::LControl
+            | VirtualKeyCode::RControl
+            | VirtualKeyCode::LShift
+            | VirtualKeyCode::RShift
+            | VirtualKeyCode::LAlt
+            | VirtualKeyCode::RAlt => None,
+            key => Some((Button::Key(*key), *modifiers)),
+        },
+        Event::WindowEvent {
+            event:
+                WindowEvent::MouseInput {
+                    state: ElementState::Pressed,
+                    button,
+                    modifiers,
+                    ..
+                },
+            ..
+        } => Some((Button::Mouse(*button), *modifiers)),
+        _ => None,
+    }
+}
+
+// e.g. A / U + LeftX
+fn axis_name(axis: &Axis) -> String {
+    match axis {
+        Axis::Emulated { pos, neg } => format!(
+            "{} / {}",
+            combo_name(&[pos.clone()]),
+            combo_name(&[neg.clone()])
+        ),
+        Axis::Controller { axis, .. } => format!("{:?}", axis),
+        Axis::MouseWheel { .. } => "mouse wheel".to_string(),
+        Axis::Multiple(axes) => axes.iter().map(axis_name).collect::<Vec<_>>().join(" + "),
+        axis => format!("{:?}", axis),
+    }
+}
+
+fn combo(button: Button, modifiers: ModifiersState) -> Vec<Button> {
+    let mut combo = Vec::new();
+    if modifiers.ctrl {
+        combo.push(Button::Key(VirtualKeyCode::LControl));
+    }
+    if modifiers.shift {
+        combo.push(Button::Key(VirtualKeyCode::LShift));
+    }
+    if modifiers.alt {
+        combo.push(Button::Key(VirtualKeyCode::LAlt));
+    }
+    combo.push(button);
+    combo
+}
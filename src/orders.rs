@@ -0,0 +1,107 @@
+// orders are the only way the player changes the game-state,
+// input is translated to orders by the SelectionSystem, the LockstepSystem hands them
+// to the simulation in the tick all players agreed on and the OrderSystem applies them
+
+use amethyst::ecs::Entity;
+use serde::{Deserialize, Serialize};
+use std::mem;
+
+#[derive(Clone, Debug)]
+pub struct Order {
+    // team that issued the order
+    pub team: u8,
+    pub kind: OrderKind,
+    // given by a script of the scenario, which gives it again when a replay is watched
+    pub scripted: bool,
+}
+
+// entities are replaced by their SimulationId to store or send orders
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum OrderKind<E = Entity> {
+    Train {
+        building: E,
+        unit_type: String,
+    },
+    CancelTraining {
+        building: E,
+    },
+    SetRallyPoint {
+        building: E,
+        x: i16,
+        z: i16,
+    },
+    Move {
+        units: Vec<E>,
+        x: i16,
+        z: i16,
+    },
+    // on tower-defense maps
+    BuildTower {
+        x: i16,
+        z: i16,
+    },
+}
+
+impl Order {
+    pub fn new(team: u8, kind: OrderKind) -> Self {
+        Order {
+            team,
+            kind,
+            scripted: false,
+        }
+    }
+
+    pub fn scripted(team: u8, kind: OrderKind) -> Self {
+        Order {
+            team,
+            kind,
+            scripted: true,
+        }
+    }
+}
+
+// orders of the local player which the simulation has not taken yet
+#[derive(Debug, Default)]
+pub struct LocalOrders {
+    orders: Vec<Order>,
+}
+
+impl LocalOrders {
+    pub fn push(&mut self, order: Order) {
+        self.orders.push(order);
+    }
+
+    pub fn take(&mut self) -> Vec<Order> {
+        mem::take(&mut self.orders)
+    }
+}
+
+impl<E> OrderKind<E> {
+    // the same order for other entities, units which can't be converted are left out
+    // and orders for a building which can't be converted are dropped
+    pub fn map_entities<F>(self, mut convert: impl FnMut(E) -> Option<F>) -> Option<OrderKind<F>> {
+        Some(match self {
+            OrderKind::Train {
+                building,
+                unit_type,
+            } => OrderKind::Train {
+                building: convert(building)?,
+                unit_type,
+            },
+            OrderKind::CancelTraining { building } => OrderKind::CancelTraining {
+                building: convert(building)?,
+            },
+            OrderKind::SetRallyPoint { building, x, z } => OrderKind::SetRallyPoint {
+                building: convert(building)?,
+                x,
+                z,
+            },
+            OrderKind::Move { units, x, z } => OrderKind::Move {
+                units: units.into_iter().filter_map(convert).collect(),
+                x,
+                z,
+            },
+            OrderKind::BuildTower { x, z } => OrderKind::BuildTower { x, z },
+        })
+    }
+}
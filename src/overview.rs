@@ -0,0 +1,100 @@
+// Save a picture of the whole arena and quit, for the thumbnails of a lobby
+
+use amethyst::{
+    core::Transform,
+    ecs::{Entity, Join},
+    prelude::*,
+    renderer::Camera,
+    window::ScreenDimensions,
+};
+use log::error;
+use std::f32::consts::PI;
+
+use crate::{
+    capture::{Captures, OverviewFile},
+    components::{CameraBorders, CameraControlTag, OrthographicExtent},
+    minions::{initialize_arena, WorldBorders},
+};
+
+// the meshes and textures of the arena are loaded by then
+const WARMUP_FRAMES: u32 = 10;
+
+#[derive(Default)]
+pub struct Overview {
+    frames: u32,
+    requested: bool,
+}
+
+impl SimpleState for Overview {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        initialize_arena(data.world);
+        frame_arena(data.world);
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        let world = &data.world;
+        if self.frames < WARMUP_FRAMES {
+            self.frames += 1;
+            return Trans::None;
+        }
+        let mut captures = world.write_resource::<Captures>();
+        if !self.requested {
+            let path = world.read_resource::<OverviewFile>().0.clone();
+            captures.request(path);
+            self.requested = true;
+            return Trans::None;
+        }
+        if captures.is_requested() {
+            return Trans::None;
+        }
+        captures.finish();
+        Trans::Quit
+    }
+}
+
+// the camera of the game looks straight down on the arena, its borders fill the window
+fn frame_arena(world: &mut World) {
+    let camera: Option<Entity> = (&world.entities(), &world.read_storage::<CameraControlTag>())
+        .join()
+        .map(|(entity, _)| entity)
+        .next();
+    let camera = match camera {
+        Some(camera) => camera,
+        None => return,
+    };
+    // nothing moves or zooms it anymore
+    world.write_storage::<CameraControlTag>().remove(camera);
+    world.write_storage::<CameraBorders>().remove(camera);
+    world.write_storage::<OrthographicExtent>().remove(camera);
+
+    let (center_x, center_z, extent_x, extent_z) = {
+        let borders = world.read_resource::<WorldBorders>();
+        (
+            (borders.left + borders.right) / 2.0,
+            (borders.bottom + borders.top) / 2.0,
+            (borders.right - borders.left) / 2.0,
+            (borders.top - borders.bottom) / 2.0,
+        )
+    };
+    let aspect = world.read_resource::<ScreenDimensions>().aspect_ratio();
+    let height = extent_z.max(extent_x / aspect);
+    let width = height * aspect;
+    let projection = Camera::orthographic(
+        -width,
+        width,
+        -height,
+        height,
+        0.1,
+        2.0 * OrthographicExtent::HEIGHT + 100.0,
+    );
+
+    let mut transform = Transform::default();
+    transform.set_translation_xyz(center_x, OrthographicExtent::HEIGHT, center_z);
+    transform.set_rotation_x_axis(-PI / 2.0);
+    if let Err(e) = world.write_storage::<Camera>().insert(camera, projection) {
+        error!("failed to frame the arena: {}", e);
+    }
+    if let Err(e) = world.write_storage::<Transform>().insert(camera, transform) {
+        error!("failed to frame the arena: {}", e);
+    }
+}
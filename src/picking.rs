@@ -0,0 +1,92 @@
+// ray-casting against the terrain-meshes
+
+use amethyst::core::{
+    geometry::{Plane, Ray},
+    math::Point3,
+};
+
+use crate::{minions::WorldBorders, resources::TerrainMap};
+
+// fraction of a tile the ray advances per step before the hit is refined
+const RAY_STEP: f32 = 0.125;
+const REFINEMENTS: usize = 8;
+
+// height of the terrain at a world-position, the same surface the tile-meshes show:
+// every tile is a fan of triangles around its center
+pub fn terrain_height(
+    terrain: &TerrainMap,
+    world_borders: &WorldBorders,
+    tile_size: f32,
+    x: f32,
+    z: f32,
+) -> f32 {
+    let tile = match world_borders.tile_at(x, z, tile_size) {
+        Some(tile) => tile,
+        None => return 0.0,
+    };
+    // position relative to the center of the tile, in tiles
+    let (center_x, center_z) = world_borders.tile_center(tile.0, tile.1, tile_size);
+    let (u, v) = ((x - center_x) / tile_size, (z - center_z) / tile_size);
+
+    let center = terrain.height(tile.0, tile.1);
+    let corners = terrain.topology().corners();
+    for (i, &(u1, v1)) in corners.iter().enumerate() {
+        let next = (i + 1) % corners.len();
+        let (u2, v2) = corners[next];
+
+        // barycentric coordinates relative to the center of the tile
+        let determinant = u1 * v2 - u2 * v1;
+        let a = (u * v2 - u2 * v) / determinant;
+        let b = (u1 * v - u * v1) / determinant;
+        if a >= -1e-4 && b >= -1e-4 {
+            let (h1, h2) = (
+                terrain.corner_height(tile, i),
+                terrain.corner_height(tile, next),
+            );
+            return center + a * (h1 - center) + b * (h2 - center);
+        }
+    }
+    center
+}
+
+// first point where the ray hits the terrain, None if it never comes down to the ground
+pub fn pick(
+    ray: &Ray<f32>,
+    terrain: &TerrainMap,
+    world_borders: &WorldBorders,
+    tile_size: f32,
+) -> Option<Point3<f32>> {
+    let (lowest, highest) = terrain.height_range();
+    let end = ray.intersect_plane(&Plane::with_y(lowest))?;
+    let start = ray
+        .intersect_plane(&Plane::with_y(highest))
+        .unwrap_or(0.0)
+        .max(0.0);
+    let step = RAY_STEP * tile_size / ray.direction.norm();
+
+    let below = |distance: f32| {
+        let point = ray.at_distance(distance);
+        point.y <= terrain_height(terrain, world_borders, tile_size, point.x, point.z)
+    };
+
+    let mut previous = start;
+    let mut distance = start;
+    while distance < end {
+        distance = (distance + step).min(end);
+        if below(distance) {
+            // bisect between the last point above and the first point below the terrain
+            let (mut above, mut under) = (previous, distance);
+            for _ in 0..REFINEMENTS {
+                let middle = (above + under) / 2.0;
+                if below(middle) {
+                    under = middle;
+                } else {
+                    above = middle;
+                }
+            }
+            return Some(ray.at_distance(under));
+        }
+        previous = distance;
+    }
+    Some(ray.at_distance(end))
+}
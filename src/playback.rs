@@ -0,0 +1,91 @@
+// Watch a replay, the recorded orders are applied to a new simulation of the match
+
+use amethyst::{
+    ecs::{Dispatcher, Entity, Join},
+    prelude::*,
+};
+use log::{error, info};
+
+use crate::{
+    components::SimulationId,
+    config::PlayerConfig,
+    minions::{initialize_arena, initialize_buildings, initialize_scenario},
+    resources::{ReplayPlayer, Simulation, Treasury},
+    rng::Rng,
+    simulation,
+};
+
+#[derive(Default)]
+pub struct Playback {
+    simulation: Option<Dispatcher<'static, 'static>>,
+    // fractions of ticks left over at slow speeds
+    owed_ticks: f32,
+}
+
+impl SimpleState for Playback {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        initialize_arena(data.world);
+        initialize_scenario(data.world);
+        self.simulation = Some(simulation::build_dispatcher(data.world));
+        let end = data.world.read_resource::<ReplayPlayer>().end();
+        info!(
+            "playing a replay of {}s: space pauses, ,/. change the speed, \
+             page up/down seek",
+            end / simulation::TICKS_PER_SECOND as u64
+        );
+    }
+
+    fn fixed_update(&mut self, data: StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        let dispatcher = match &mut self.simulation {
+            Some(dispatcher) => dispatcher,
+            None => return Trans::None,
+        };
+        let world = data.world;
+
+        let seek = world.write_resource::<ReplayPlayer>().seek.take();
+        if let Some(target) = seek {
+            if target < world.read_resource::<Simulation>().tick() {
+                restart(world);
+            }
+            while world.read_resource::<Simulation>().tick() < target {
+                simulation::step(dispatcher, world);
+            }
+            self.owed_ticks = 0.0;
+            return Trans::None;
+        }
+
+        self.owed_ticks += world.read_resource::<ReplayPlayer>().speed();
+        while self.owed_ticks >= 1.0 {
+            simulation::step(dispatcher, world);
+            self.owed_ticks -= 1.0;
+        }
+        Trans::None
+    }
+}
+
+// puts the simulation back to its first tick, the arena itself never changes
+fn restart(world: &mut World) {
+    let simulated: Vec<Entity> = (&world.entities(), &world.read_storage::<SimulationId>())
+        .join()
+        .map(|(entity, _)| entity)
+        .collect();
+    if let Err(e) = world.delete_entities(&simulated) {
+        error!("failed to delete the simulated entities: {}", e);
+    }
+    world.maintain();
+
+    let (teams, starting_gold) = {
+        let players = world.read_resource::<PlayerConfig>();
+        (players.teams, players.starting_gold)
+    };
+    let seed = {
+        let mut player = world.write_resource::<ReplayPlayer>();
+        player.next_order = 0;
+        player.replay.seed
+    };
+    world.insert(Simulation::default());
+    world.insert(Treasury::new(teams, starting_gold));
+    world.insert(Rng::new(seed));
+    initialize_buildings(world);
+    initialize_scenario(world);
+}
@@ -0,0 +1,26 @@
+use amethyst::{
+    assets::Handle,
+    renderer::{Material, Mesh},
+};
+use std::collections::HashMap;
+
+use crate::components::{Terrain, TileMaterials};
+
+// handles shared by every spawned minion, building and gold mine
+pub struct MinionAssets {
+    pub mesh: Handle<Mesh>,
+    pub team_materials: Vec<Handle<Material>>,
+    pub gold_material: Handle<Material>,
+}
+
+impl MinionAssets {
+    pub fn team_material(&self, team: u8) -> Handle<Material> {
+        self.team_materials[team as usize % self.team_materials.len()].clone()
+    }
+}
+
+// all tiles of one terrain share their materials
+#[derive(Default)]
+pub struct TerrainAssets {
+    pub materials: HashMap<Terrain, TileMaterials>,
+}
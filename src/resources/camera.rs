@@ -0,0 +1,32 @@
+use amethyst::core::math::Vector3;
+
+// the F-keys which store a camera position
+pub const BOOKMARKS: usize = 3;
+
+// camera positions the player stored and the point of the last alert
+#[derive(Debug, Default)]
+pub struct CameraBookmarks {
+    positions: [Option<Vector3<f32>>; BOOKMARKS],
+    // on the ground, where the camera has to look to see what happened
+    pub alert: Option<Vector3<f32>>,
+    // position of the camera the CameraSystem glides to
+    pub target: Option<Vector3<f32>>,
+}
+
+impl CameraBookmarks {
+    pub fn store(&mut self, bookmark: u8, position: Vector3<f32>) {
+        if let Some(slot) = self.positions.get_mut(bookmark as usize) {
+            *slot = Some(position);
+        }
+    }
+
+    pub fn get(&self, bookmark: u8) -> Option<Vector3<f32>> {
+        self.positions.get(bookmark as usize).copied().flatten()
+    }
+}
+
+// true while the camera keeps the selection in the centre of the screen
+#[derive(Debug, Default)]
+pub struct CameraFollow {
+    pub active: bool,
+}
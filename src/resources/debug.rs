@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+// what the DebugSystem draws over the arena, each layer has its own key
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DebugLayer {
+    // the x, y and z axes at the origin
+    Axes,
+    Grid,
+    // the WorldBorders and the CameraBorders
+    Borders,
+    // from the minions to their move-orders
+    Paths,
+    // attack ranges of the minions and towers
+    Ranges,
+    // of the hovered tile, next to the cursor
+    Coordinates,
+    // from the camera through the cursor to the hovered point
+    HoverRay,
+}
+
+impl DebugLayer {
+    pub const ALL: [DebugLayer; 7] = [
+        DebugLayer::Axes,
+        DebugLayer::Grid,
+        DebugLayer::Borders,
+        DebugLayer::Paths,
+        DebugLayer::Ranges,
+        DebugLayer::Coordinates,
+        DebugLayer::HoverRay,
+    ];
+}
+
+#[derive(Debug)]
+pub struct DebugLayers {
+    shown: HashSet<DebugLayer>,
+}
+
+impl Default for DebugLayers {
+    // debug builds start with the axes and the grid, release builds without any layer
+    fn default() -> Self {
+        let shown = if cfg!(debug_assertions) {
+            vec![DebugLayer::Axes, DebugLayer::Grid]
+        } else {
+            Vec::new()
+        };
+        DebugLayers {
+            shown: shown.into_iter().collect(),
+        }
+    }
+}
+
+impl DebugLayers {
+    pub fn is_shown(&self, layer: DebugLayer) -> bool {
+        self.shown.contains(&layer)
+    }
+
+    // returns whether the layer is shown now
+    pub fn toggle(&mut self, layer: DebugLayer) -> bool {
+        if !self.shown.remove(&layer) {
+            self.shown.insert(layer);
+        }
+        self.is_shown(layer)
+    }
+}
@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+
+use crate::{config::DefenseConfig, simulation};
+
+// progress of the waves of a tower-defense map
+#[derive(Debug, Default)]
+pub struct Defense {
+    pub lives: u32,
+    // index of the next wave and the attackers of it which were spawned
+    pub wave: usize,
+    pub spawned: u32,
+    // tick the next attacker spawns in, the tick of the last one after the last wave
+    pub next_spawn: u64,
+    // the next tile towards the goal, computed whenever the buildings change
+    pub flow: HashMap<(i16, i16), (i16, i16)>,
+    pub buildings: usize,
+}
+
+impl Defense {
+    pub fn new(config: &DefenseConfig) -> Self {
+        Defense {
+            lives: config.lives,
+            next_spawn: config
+                .waves
+                .first()
+                .map_or(0, |wave| u64::from(simulation::ticks(wave.delay))),
+            ..Defense::default()
+        }
+    }
+
+    // true once the attackers of every wave were spawned
+    pub fn waves_over(&self, config: &DefenseConfig) -> bool {
+        self.wave >= config.waves.len()
+    }
+
+    // seconds until the next wave, None while a wave is spawning or after the last one
+    pub fn countdown(&self, config: &DefenseConfig, tick: u64) -> Option<u64> {
+        if self.spawned > 0 || self.waves_over(config) {
+            return None;
+        }
+        Some(self.next_spawn.saturating_sub(tick) / u64::from(simulation::TICKS_PER_SECOND))
+    }
+}
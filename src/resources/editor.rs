@@ -0,0 +1,172 @@
+use std::{collections::VecDeque, path::PathBuf};
+
+use crate::{
+    components::Terrain,
+    config::{BuildingConfig, GoldMineConfig},
+    resources::TerrainMap,
+};
+
+// edits which can be undone
+const MAX_HISTORY: usize = 100;
+const MAX_BRUSH_SIZE: u8 = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tool {
+    Paint(Terrain),
+    Raise,
+    Lower,
+    // places or removes a gold mine on the hovered tile
+    GoldMine,
+    // places or removes the spawn point of the next team
+    Spawn,
+}
+
+impl Tool {
+    // in the order of the tool-slots
+    pub const ALL: [Tool; 6] = [
+        Tool::Paint(Terrain::Grass),
+        Tool::Paint(Terrain::Rock),
+        Tool::Raise,
+        Tool::Lower,
+        Tool::GoldMine,
+        Tool::Spawn,
+    ];
+
+    // brushes paint every tile they are dragged over, the others act once per click
+    pub fn is_brush(self) -> bool {
+        match self {
+            Tool::Paint(_) | Tool::Raise | Tool::Lower => true,
+            Tool::GoldMine | Tool::Spawn => false,
+        }
+    }
+}
+
+// everything about the map the editor can change
+#[derive(Clone)]
+pub struct MapSnapshot {
+    pub terrain: TerrainMap,
+    pub gold_mines: Vec<GoldMineConfig>,
+    pub buildings: Vec<BuildingConfig>,
+}
+
+pub struct MapEditor {
+    // the map-file the editor saves to
+    pub path: PathBuf,
+    pub tool: Tool,
+    // radius of the brush in tiles, 1 is a single tile
+    pub brush_size: u8,
+    undo: VecDeque<MapSnapshot>,
+    redo: Vec<MapSnapshot>,
+}
+
+impl MapEditor {
+    pub fn new(path: PathBuf) -> Self {
+        MapEditor {
+            path,
+            tool: Tool::ALL[0],
+            brush_size: 1,
+            undo: VecDeque::with_capacity(MAX_HISTORY),
+            redo: Vec::new(),
+        }
+    }
+
+    pub fn resize_brush(&mut self, change: i8) {
+        self.brush_size = (self.brush_size as i8 + change)
+            .max(1)
+            .min(MAX_BRUSH_SIZE as i8) as u8;
+    }
+
+    // remembers the map before an edit, this drops everything that was undone
+    pub fn record(&mut self, before: MapSnapshot) {
+        self.push_undo(before);
+        self.redo.clear();
+    }
+
+    // the map before the last edit, None if there is nothing to undo
+    pub fn undo(&mut self, current: MapSnapshot) -> Option<MapSnapshot> {
+        let previous = self.undo.pop_back()?;
+        self.redo.push(current);
+        Some(previous)
+    }
+
+    pub fn redo(&mut self, current: MapSnapshot) -> Option<MapSnapshot> {
+        let next = self.redo.pop()?;
+        self.push_undo(current);
+        Some(next)
+    }
+
+    // the oldest edit is forgotten once the history is full
+    fn push_undo(&mut self, snapshot: MapSnapshot) {
+        if self.undo.len() >= MAX_HISTORY {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(snapshot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Topology;
+
+    // the snapshots are told apart by their only gold mine
+    fn snapshot(id: i16) -> MapSnapshot {
+        MapSnapshot {
+            terrain: TerrainMap::new(2, 2, Topology::Square),
+            gold_mines: vec![GoldMineConfig {
+                x: id,
+                z: 0,
+                amount: 0,
+            }],
+            buildings: Vec::new(),
+        }
+    }
+
+    fn id(snapshot: Option<MapSnapshot>) -> Option<i16> {
+        snapshot.map(|snapshot| snapshot.gold_mines[0].x)
+    }
+
+    #[test]
+    fn undo_then_redo_returns_to_the_same_map() {
+        let mut editor = MapEditor::new(PathBuf::from("map.ron"));
+        assert_eq!(id(editor.undo(snapshot(0))), None);
+        editor.record(snapshot(0));
+        editor.record(snapshot(1));
+        assert_eq!(id(editor.undo(snapshot(2))), Some(1));
+        assert_eq!(id(editor.undo(snapshot(1))), Some(0));
+        assert_eq!(id(editor.undo(snapshot(0))), None);
+        assert_eq!(id(editor.redo(snapshot(0))), Some(1));
+        assert_eq!(id(editor.redo(snapshot(1))), Some(2));
+        assert_eq!(id(editor.redo(snapshot(2))), None);
+        assert_eq!(id(editor.undo(snapshot(2))), Some(1));
+    }
+
+    #[test]
+    fn a_new_edit_clears_the_redo() {
+        let mut editor = MapEditor::new(PathBuf::from("map.ron"));
+        editor.record(snapshot(0));
+        assert_eq!(id(editor.undo(snapshot(1))), Some(0));
+        editor.record(snapshot(0));
+        assert_eq!(id(editor.redo(snapshot(2))), None);
+        assert_eq!(id(editor.undo(snapshot(2))), Some(0));
+    }
+
+    #[test]
+    fn the_history_forgets_the_oldest_edit() {
+        let mut editor = MapEditor::new(PathBuf::from("map.ron"));
+        for i in 0..=MAX_HISTORY as i16 {
+            editor.record(snapshot(i));
+        }
+        let current = MAX_HISTORY as i16 + 1;
+        for i in (1..=MAX_HISTORY as i16).rev() {
+            assert_eq!(id(editor.undo(snapshot(i + 1))), Some(i));
+        }
+        assert_eq!(id(editor.undo(snapshot(1))), None);
+
+        // everything that was undone comes back
+        for i in 1..current {
+            assert_eq!(id(editor.redo(snapshot(i))), Some(i + 1));
+        }
+        assert_eq!(editor.undo.len(), MAX_HISTORY);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::components::Visibility;
+
+// what every team knows about the tiles of the arena
+#[derive(Debug, Default)]
+pub struct FogOfWar {
+    width: i16,
+    depth: i16,
+    teams: Vec<Vec<Visibility>>,
+    // teams which see every tile until the end of the match
+    revealed: Vec<bool>,
+    // whether the tiles changed outside the FogSystem, e.g. by a new match or a reveal
+    changed: bool,
+}
+
+impl FogOfWar {
+    pub fn new(width: i16, depth: i16, teams: u8) -> Self {
+        let tiles = (width.max(0) as usize) * (depth.max(0) as usize);
+        FogOfWar {
+            width,
+            depth,
+            teams: vec![vec![Visibility::Unexplored; tiles]; teams as usize],
+            revealed: vec![false; teams as usize],
+            changed: true,
+        }
+    }
+
+    fn index(&self, x: i16, z: i16) -> Option<usize> {
+        if 0 <= x && x < self.width && 0 <= z && z < self.depth {
+            Some(z as usize * self.width as usize + x as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn visibility(&self, team: u8, x: i16, z: i16) -> Visibility {
+        match (self.teams.get(team as usize), self.index(x, z)) {
+            (Some(tiles), Some(index)) => tiles[index],
+            _ => Visibility::Unexplored,
+        }
+    }
+
+    pub fn is_visible(&self, team: u8, x: i16, z: i16) -> bool {
+        self.visibility(team, x, z) == Visibility::Visible
+    }
+
+    // every visible tile of the team becomes explored, called before sight is recalculated
+    pub fn fade(&mut self, team: u8) {
+        if self.revealed.get(team as usize) == Some(&true) {
+            return;
+        }
+        if let Some(tiles) = self.teams.get_mut(team as usize) {
+            for tile in tiles.iter_mut() {
+                if *tile == Visibility::Visible {
+                    *tile = Visibility::Explored;
+                }
+            }
+        }
+    }
+
+    pub fn reveal(&mut self, team: u8, x: i16, z: i16) {
+        if let Some(index) = self.index(x, z) {
+            if let Some(tiles) = self.teams.get_mut(team as usize) {
+                tiles[index] = Visibility::Visible;
+            }
+        }
+    }
+
+    // the fog of the team never comes back
+    pub fn reveal_all(&mut self, team: u8) {
+        if let (Some(tiles), Some(revealed)) = (
+            self.teams.get_mut(team as usize),
+            self.revealed.get_mut(team as usize),
+        ) {
+            for tile in tiles.iter_mut() {
+                *tile = Visibility::Visible;
+            }
+            *revealed = true;
+            self.changed = true;
+        }
+    }
+
+    // whether the tiles changed since the last call
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::replace(&mut self.changed, false)
+    }
+
+    pub fn teams(&self) -> u8 {
+        self.teams.len() as u8
+    }
+}
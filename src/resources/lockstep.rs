@@ -0,0 +1,437 @@
+use log::{error, info, warn};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
+
+use crate::{
+    network::{Connection, Message},
+    orders::OrderKind,
+    resources::Simulation,
+};
+
+// commands of one team for one tick, entities are referred to by their SimulationId
+type Commands = Vec<OrderKind<u32>>;
+
+// ticks beyond the input delay a player may send commands for, the others can be
+// up to one input delay behind. commands even further ahead are rejected so nobody
+// can fill the memory of the others
+const COMMAND_WINDOW: u64 = 30;
+
+// why a lan match can't go on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LockstepError {
+    HostLost,
+    // the first team whose checksum differs and the tick it differed at
+    Desync { team: u8, tick: u64 },
+}
+
+impl Display for LockstepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockstepError::HostLost => write!(f, "lost the connection to the host"),
+            LockstepError::Desync { team, tick } => {
+                write!(f, "out of sync with team {} since tick {}", team, tick)
+            }
+        }
+    }
+}
+
+// the commands every player issued for the coming ticks, a tick may only be simulated
+// once the commands of every team still playing arrived
+pub struct Lockstep {
+    // the team of the local player, None on the dedicated server
+    team: Option<u8>,
+    pub input_delay: u64,
+    // the host is connected to every other player, the others only to the host
+    host: bool,
+    connections: Vec<Connection>,
+    // the teams of all players, a team which left sends no commands after the tick
+    teams: BTreeMap<u8, Option<u64>>,
+    commands: BTreeMap<u64, BTreeMap<u8, Commands>>,
+    // the last tick each team sent commands for
+    last_ticks: BTreeMap<u8, u64>,
+    // (team, tick, checksum) of the other players which are not compared yet
+    checksums: Vec<(u8, u64, u64)>,
+    error: Option<LockstepError>,
+}
+
+impl Lockstep {
+    pub fn new(
+        team: Option<u8>,
+        teams: u8,
+        input_delay: u64,
+        host: bool,
+        connections: Vec<Connection>,
+    ) -> Self {
+        Lockstep {
+            team,
+            input_delay,
+            host,
+            connections,
+            teams: (0..teams).map(|team| (team, None)).collect(),
+            commands: BTreeMap::new(),
+            last_ticks: BTreeMap::new(),
+            checksums: Vec::new(),
+            error: None,
+        }
+    }
+
+    // receives the commands of the other players, the host checks and passes them on,
+    // `tick` is the next tick to simulate
+    pub fn poll(&mut self, tick: u64) {
+        let mut received = Vec::new();
+        let mut lost = Vec::new();
+        for (index, connection) in self.connections.iter_mut().enumerate() {
+            match connection.receive() {
+                Ok(messages) => {
+                    let team = connection.team;
+                    received.extend(messages.into_iter().map(|m| (index, team, m)))
+                }
+                Err(e) => {
+                    warn!("lost the connection to team {}: {}", connection.team, e);
+                    lost.push(index);
+                }
+            }
+        }
+
+        for (from, sender, message) in received {
+            match message {
+                Message::Commands {
+                    team,
+                    tick: commands_tick,
+                    orders,
+                    checksum,
+                } => {
+                    let latest = tick + 2 * self.input_delay + COMMAND_WINDOW;
+                    if commands_tick > latest {
+                        warn!(
+                            "team {} sent commands for tick {}, the latest allowed is {}",
+                            sender, commands_tick, latest
+                        );
+                        continue;
+                    }
+                    if self.host {
+                        // nobody may send the commands of another team or change them
+                        // once they are sent, whether the team may give the orders
+                        // is up to the OrderSystem
+                        if team != sender {
+                            warn!("team {} sent commands for team {}", sender, team);
+                            continue;
+                        }
+                        let sent = self
+                            .commands
+                            .get(&commands_tick)
+                            .map_or(false, |commands| commands.contains_key(&team));
+                        if commands_tick < tick || sent {
+                            warn!(
+                                "team {} sent commands for tick {} again",
+                                team, commands_tick
+                            );
+                            continue;
+                        }
+                        let message = Message::Commands {
+                            team,
+                            tick: commands_tick,
+                            orders: orders.clone(),
+                            checksum,
+                        };
+                        self.broadcast(&message, Some(from));
+                    }
+                    self.add_commands(team, commands_tick, orders, checksum);
+                }
+                Message::Left { team, last_tick } => self.leave(team, last_tick),
+                message => warn!("unexpected message {:?}", message),
+            }
+        }
+
+        // removed back to front so the indices stay valid
+        for index in lost.into_iter().rev() {
+            let team = self.connections.remove(index).team;
+            if !self.host {
+                self.fail(LockstepError::HostLost);
+                continue;
+            }
+            let last_tick = self
+                .last_ticks
+                .get(&team)
+                .copied()
+                .unwrap_or(self.input_delay.saturating_sub(1));
+            self.broadcast(&Message::Left { team, last_tick }, None);
+            self.leave(team, last_tick);
+        }
+    }
+
+    // true once the commands of every team for the tick arrived
+    pub fn ready(&self, tick: u64) -> bool {
+        if self.error.is_some() {
+            return false;
+        }
+        tick < self.input_delay
+            || self.teams.iter().all(|(team, left)| {
+                left.map_or(false, |last_tick| tick > last_tick)
+                    || self
+                        .commands
+                        .get(&tick)
+                        .map_or(false, |commands| commands.contains_key(team))
+            })
+    }
+
+    // sends the commands of the local player for a coming tick to the others
+    pub fn submit(&mut self, tick: u64, orders: Commands, checksum: Option<(u64, u64)>) {
+        let team = match self.team {
+            Some(team) => team,
+            None => return,
+        };
+        let message = Message::Commands {
+            team,
+            tick,
+            orders: orders.clone(),
+            checksum,
+        };
+        self.broadcast(&message, None);
+        self.commands.entry(tick).or_default().insert(team, orders);
+    }
+
+    // false once every other player left, a client only counts the host
+    pub fn is_connected(&self) -> bool {
+        !self.connections.is_empty()
+    }
+
+    // set once the match can't go on, it has to end then
+    pub fn error(&self) -> Option<LockstepError> {
+        self.error
+    }
+
+    // removes the commands of every team for the tick, sorted by team
+    pub fn take(&mut self, tick: u64) -> Vec<(u8, Commands)> {
+        self.commands
+            .remove(&tick)
+            .map(|commands| commands.into_iter().collect())
+            .unwrap_or_default()
+    }
+
+    // compares the checksums of the other players with the own ones
+    pub fn verify(&mut self, simulation: &Simulation) {
+        let mut desyncs = Vec::new();
+        self.checksums.retain(|&(team, tick, checksum)| {
+            if tick >= simulation.tick() {
+                return true;
+            }
+            if let Some(own) = simulation.checksum(tick) {
+                if own != checksum {
+                    desyncs.push((team, tick));
+                }
+            }
+            false
+        });
+        if let Some(&(team, tick)) = desyncs.first() {
+            self.fail(LockstepError::Desync { team, tick });
+        }
+    }
+
+    // only the first error is kept, the later ones follow from it
+    fn fail(&mut self, error: LockstepError) {
+        if self.error.is_none() {
+            error!("{}, the match can't go on", error);
+            self.error = Some(error);
+        }
+    }
+
+    fn add_commands(
+        &mut self,
+        team: u8,
+        tick: u64,
+        orders: Commands,
+        checksum: Option<(u64, u64)>,
+    ) {
+        self.commands.entry(tick).or_default().insert(team, orders);
+        self.last_ticks.insert(team, tick);
+        if let Some((tick, checksum)) = checksum {
+            self.checksums.push((team, tick, checksum));
+        }
+    }
+
+    fn leave(&mut self, team: u8, last_tick: u64) {
+        info!("team {} left the match after tick {}", team, last_tick);
+        self.teams.insert(team, Some(last_tick));
+    }
+
+    fn broadcast(&mut self, message: &Message, except: Option<usize>) {
+        for (index, connection) in self.connections.iter_mut().enumerate() {
+            if Some(index) == except {
+                continue;
+            }
+            // a lost connection is noticed by the next poll
+            if let Err(e) = connection.send(message) {
+                warn!("failed to send to team {}: {}", connection.team, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        net::{TcpListener, TcpStream},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    const DELAY: u64 = 2;
+
+    // a dedicated server for two teams and the connections of both players to it
+    fn server() -> (Lockstep, Vec<Connection>) {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let address = listener.local_addr().unwrap();
+        let mut hosted = Vec::new();
+        let mut players = Vec::new();
+        for team in 0..2 {
+            let player = Connection::new(TcpStream::connect(address).unwrap(), 0).unwrap();
+            let (stream, _) = listener.accept().unwrap();
+            let connection = Connection::new(stream, team).unwrap();
+            connection.set_nonblocking().unwrap();
+            hosted.push(connection);
+            players.push(player);
+        }
+        (Lockstep::new(None, 2, DELAY, true, hosted), players)
+    }
+
+    fn commands(team: u8, tick: u64, checksum: Option<(u64, u64)>) -> Message {
+        Message::Commands {
+            team,
+            tick,
+            orders: Vec::new(),
+            checksum,
+        }
+    }
+
+    fn has_commands(lockstep: &Lockstep, team: u8, tick: u64) -> bool {
+        lockstep
+            .commands
+            .get(&tick)
+            .map_or(false, |commands| commands.contains_key(&team))
+    }
+
+    // polls at `tick` until the condition holds, the messages take a moment
+    fn poll_until(lockstep: &mut Lockstep, tick: u64, condition: impl Fn(&Lockstep) -> bool) {
+        let start = Instant::now();
+        while !condition(lockstep) {
+            assert!(start.elapsed() < Duration::from_secs(5), "nothing arrived");
+            lockstep.poll(tick);
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn a_tick_waits_for_every_team() {
+        let (mut lockstep, mut players) = server();
+        // nobody can send commands for the ticks of the input delay
+        assert!(lockstep.ready(0));
+        assert!(lockstep.ready(DELAY - 1));
+        assert!(!lockstep.ready(DELAY));
+
+        players[0].send(&commands(0, DELAY, None)).unwrap();
+        poll_until(&mut lockstep, 0, |lockstep| {
+            has_commands(lockstep, 0, DELAY)
+        });
+        assert!(!lockstep.ready(DELAY));
+
+        players[1].send(&commands(1, DELAY, None)).unwrap();
+        poll_until(&mut lockstep, 0, |lockstep| {
+            has_commands(lockstep, 1, DELAY)
+        });
+        assert!(lockstep.ready(DELAY));
+        assert!(!lockstep.ready(DELAY + 1));
+        let teams: Vec<u8> = lockstep
+            .take(DELAY)
+            .into_iter()
+            .map(|(team, _)| team)
+            .collect();
+        assert_eq!(teams, vec![0, 1]);
+    }
+
+    #[test]
+    fn commands_outside_the_window_are_rejected() {
+        let (mut lockstep, mut players) = server();
+        let tick = DELAY + 5;
+        let latest = tick + 2 * DELAY + COMMAND_WINDOW;
+        // too late, for another team, too early and sent twice
+        players[0].send(&commands(0, tick - 1, None)).unwrap();
+        players[0].send(&commands(1, tick, None)).unwrap();
+        players[0].send(&commands(0, latest + 1, None)).unwrap();
+        players[0].send(&commands(0, tick, None)).unwrap();
+        players[0]
+            .send(&commands(0, tick, Some((tick - 1, 1))))
+            .unwrap();
+        // the last one is accepted, so the others were handled before it
+        players[0].send(&commands(0, latest, None)).unwrap();
+        poll_until(&mut lockstep, tick, |lockstep| {
+            has_commands(lockstep, 0, latest)
+        });
+
+        assert!(!lockstep.commands.contains_key(&(tick - 1)));
+        assert!(!has_commands(&lockstep, 1, tick));
+        assert!(!lockstep.commands.contains_key(&(latest + 1)));
+        // the second commands for the tick didn't replace the first
+        assert!(has_commands(&lockstep, 0, tick));
+        assert!(lockstep.checksums.is_empty());
+        assert_eq!(lockstep.error(), None);
+    }
+
+    #[test]
+    fn different_checksums_are_a_desync() {
+        let (mut lockstep, mut players) = server();
+        let mut simulation = Simulation::default();
+        for checksum in &[10, 11, 12] {
+            simulation.finish_tick(*checksum);
+        }
+
+        players[0].send(&commands(0, DELAY, Some((1, 11)))).unwrap();
+        players[1].send(&commands(1, DELAY, Some((1, 11)))).unwrap();
+        poll_until(&mut lockstep, 0, |lockstep| lockstep.ready(DELAY));
+        lockstep.verify(&simulation);
+        assert_eq!(lockstep.error(), None);
+
+        // a tick which isn't simulated yet is compared later
+        players[0]
+            .send(&commands(0, DELAY + 1, Some((3, 13))))
+            .unwrap();
+        players[1]
+            .send(&commands(1, DELAY + 1, Some((2, 99))))
+            .unwrap();
+        poll_until(&mut lockstep, 0, |lockstep| lockstep.checksums.len() == 2);
+        lockstep.verify(&simulation);
+        assert_eq!(
+            lockstep.error(),
+            Some(LockstepError::Desync { team: 1, tick: 2 })
+        );
+        assert_eq!(lockstep.checksums, vec![(0, 3, 13)]);
+        // the match stops at the desync
+        assert!(!lockstep.ready(DELAY + 1));
+    }
+
+    #[test]
+    fn a_team_that_left_is_not_waited_for() {
+        let (mut lockstep, mut players) = server();
+        players[1].send(&commands(1, DELAY, None)).unwrap();
+        poll_until(&mut lockstep, 0, |lockstep| {
+            has_commands(lockstep, 1, DELAY)
+        });
+        drop(players.remove(1));
+        poll_until(&mut lockstep, 0, |lockstep| lockstep.teams[&1].is_some());
+        // it left after the last tick it sent commands for
+        assert_eq!(lockstep.teams[&1], Some(DELAY));
+        assert!(lockstep.is_connected());
+
+        players[0].send(&commands(0, DELAY, None)).unwrap();
+        players[0].send(&commands(0, DELAY + 1, None)).unwrap();
+        poll_until(&mut lockstep, 0, |lockstep| {
+            has_commands(lockstep, 0, DELAY + 1)
+        });
+        assert!(lockstep.ready(DELAY));
+        lockstep.take(DELAY);
+        assert!(lockstep.ready(DELAY + 1));
+    }
+}
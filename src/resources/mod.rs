@@ -0,0 +1,33 @@
+mod assets;
+mod camera;
+mod debug;
+mod defense;
+mod editor;
+mod fog;
+mod lockstep;
+mod player;
+mod profiler;
+mod replay;
+mod script;
+mod selection;
+mod simulation;
+mod terrain;
+mod units;
+mod victory;
+
+pub use self::assets::{MinionAssets, TerrainAssets};
+pub use self::camera::{CameraBookmarks, CameraFollow};
+pub use self::debug::{DebugLayer, DebugLayers};
+pub use self::defense::Defense;
+pub use self::editor::{MapEditor, MapSnapshot, Tool};
+pub use self::fog::FogOfWar;
+pub use self::lockstep::{Lockstep, LockstepError};
+pub use self::player::{LocalPlayer, Treasury};
+pub use self::profiler::{trace_path, Profiler, SystemTimer, Trace, FRAME_HISTORY, TRACE_DIR};
+pub use self::replay::{RecordedOrder, Replay, ReplayPlayer, ReplayRecorder, SPEEDS};
+pub use self::script::{ScriptCommand, ScriptContext, ScriptMessages, Scripts};
+pub use self::selection::{ControlGroups, HoveredTile, Selection, VirtualCursor};
+pub use self::simulation::{Simulation, SimulationSpeed};
+pub use self::terrain::TerrainMap;
+pub use self::units::{UnitRegistry, UnitType};
+pub use self::victory::{PlayerResult, Victory};
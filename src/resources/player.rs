@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+
+// the team controlled by this client
+#[derive(Debug, Default)]
+pub struct LocalPlayer {
+    pub team: u8,
+}
+
+// gold of every team
+#[derive(Debug, Default)]
+pub struct Treasury {
+    gold: HashMap<u8, u32>,
+}
+
+impl Treasury {
+    pub fn new(teams: u8, starting_gold: u32) -> Self {
+        Treasury {
+            gold: (0..teams).map(|team| (team, starting_gold)).collect(),
+        }
+    }
+
+    pub fn gold(&self, team: u8) -> u32 {
+        self.gold.get(&team).copied().unwrap_or(0)
+    }
+
+    // returns false and keeps the gold if the team can't afford it
+    pub fn spend(&mut self, team: u8, amount: u32) -> bool {
+        let gold = self.gold.entry(team).or_insert(0);
+        if *gold < amount {
+            return false;
+        }
+        *gold -= amount;
+        true
+    }
+
+    // gold of every team sorted by team
+    pub fn balances(&self) -> Vec<(u8, u32)> {
+        let mut balances: Vec<_> = self
+            .gold
+            .iter()
+            .map(|(&team, &gold)| (team, gold))
+            .collect();
+        balances.sort();
+        balances
+    }
+
+    pub fn refund(&mut self, team: u8, amount: u32) {
+        *self.gold.entry(team).or_insert(0) += amount;
+    }
+
+    pub fn earn(&mut self, team: u8, amount: u32) {
+        *self.gold.entry(team).or_insert(0) += amount;
+    }
+}
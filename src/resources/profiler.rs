@@ -0,0 +1,297 @@
+use amethyst::{utils::application_root_dir, Error};
+use log::warn;
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread::{self, ThreadId},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+// relative to the application root
+pub const TRACE_DIR: &str = "traces";
+// frames in the graph of the performance overlay
+pub const FRAME_HISTORY: usize = 120;
+
+// the averages of the systems are taken over this period, so the overlay is readable
+const AVERAGE_PERIOD: Duration = Duration::from_secs(1);
+// about ten minutes of the game, a trace stops growing after that
+const MAX_TRACE_EVENTS: usize = 4_000_000;
+
+// the time the systems of the MinionsBundle and the simulation take, see systems::Profiled.
+// the systems only time themselves while the overlay is shown or a trace is recorded,
+// each writes to its own SystemTimer and the PerformanceSystem collects them once a frame.
+// a clone shares the timings
+#[derive(Clone, Default)]
+pub struct Profiler {
+    overlay: Arc<AtomicBool>,
+    tracing: Arc<AtomicBool>,
+    timings: Arc<Mutex<Timings>>,
+}
+
+// what a system measured since the last frame, nothing but the system itself and the
+// collection of the frame touch it, so the systems don't wait for each other
+#[derive(Default)]
+pub struct SystemTimer {
+    // in nanoseconds
+    total: AtomicU64,
+    // (thread, start, duration) of the runs while a trace is recorded
+    runs: Mutex<Vec<(ThreadId, Instant, Duration)>>,
+}
+
+impl SystemTimer {
+    fn runs(&self) -> MutexGuard<'_, Vec<(ThreadId, Instant, Duration)>> {
+        self.runs.lock().expect("profiler is poisoned")
+    }
+}
+
+#[derive(Default)]
+struct Timings {
+    // of every system, see Profiler::register
+    systems: Vec<(&'static str, Arc<SystemTimer>)>,
+    // of the current period, by system
+    totals: HashMap<&'static str, Duration>,
+    frames: u32,
+    period_start: Option<Instant>,
+    // per frame over the last period, the slowest system first
+    averages: Vec<(&'static str, Duration)>,
+    last_frame: Option<Instant>,
+    frame_times: VecDeque<Duration>,
+    trace: Option<Trace>,
+}
+
+impl Profiler {
+    // the timer a system records its runs with
+    pub fn register(&self, name: &'static str) -> Arc<SystemTimer> {
+        let timer = Arc::new(SystemTimer::default());
+        self.lock().systems.push((name, timer.clone()));
+        timer
+    }
+
+    // whether the systems have to time themselves
+    pub fn enabled(&self) -> bool {
+        self.overlay.load(Ordering::Relaxed) || self.tracing.load(Ordering::Relaxed)
+    }
+
+    // the performance overlay shows the averages of the systems
+    pub fn show_overlay(&self, shown: bool) {
+        self.overlay.store(shown, Ordering::Relaxed);
+    }
+
+    // the system ran from the start until now
+    pub fn record(&self, timer: &SystemTimer, start: Instant) {
+        let duration = start.elapsed();
+        timer
+            .total
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+        if self.tracing.load(Ordering::Relaxed) {
+            timer.runs().push((thread::current().id(), start, duration));
+        }
+    }
+
+    // called once a frame by the PerformanceSystem
+    pub fn frame(&self) {
+        let now = Instant::now();
+        let enabled = self.enabled();
+        let mut timings = self.lock();
+        let timings = &mut *timings;
+        if let Some(last_frame) = timings.last_frame {
+            let frame_time = now - last_frame;
+            if timings.frame_times.len() == FRAME_HISTORY {
+                timings.frame_times.pop_front();
+            }
+            timings.frame_times.push_back(frame_time);
+            if let Some(trace) = &mut timings.trace {
+                trace.push("frame", None, last_frame, frame_time);
+            }
+        }
+        timings.last_frame = Some(now);
+
+        timings.collect_runs();
+        for (name, timer) in &timings.systems {
+            let nanos = timer.total.swap(0, Ordering::Relaxed);
+            *timings.totals.entry(*name).or_default() += Duration::from_nanos(nanos);
+        }
+        // the averages start over once the systems are timed again
+        if !enabled {
+            timings.totals.clear();
+            timings.frames = 0;
+            timings.period_start = None;
+            return;
+        }
+        timings.frames += 1;
+
+        let period_start = *timings.period_start.get_or_insert(now);
+        if now - period_start >= AVERAGE_PERIOD {
+            let frames = timings.frames;
+            let mut averages: Vec<_> = timings
+                .totals
+                .drain()
+                .map(|(name, total)| (name, total / frames))
+                .collect();
+            averages.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+            timings.averages = averages;
+            timings.frames = 0;
+            timings.period_start = Some(now);
+        }
+    }
+
+    // oldest first
+    pub fn frame_times(&self) -> Vec<Duration> {
+        self.lock().frame_times.iter().copied().collect()
+    }
+
+    pub fn averages(&self) -> Vec<(&'static str, Duration)> {
+        self.lock().averages.clone()
+    }
+
+    // number of events of the running trace
+    pub fn tracing(&self) -> Option<usize> {
+        self.lock().trace.as_ref().map(|trace| trace.events.len())
+    }
+
+    pub fn start_trace(&self) {
+        self.lock().trace = Some(Trace::new());
+        self.tracing.store(true, Ordering::Relaxed);
+    }
+
+    pub fn stop_trace(&self) -> Option<Trace> {
+        self.tracing.store(false, Ordering::Relaxed);
+        let mut timings = self.lock();
+        timings.collect_runs();
+        timings.trace.take()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Timings> {
+        self.timings.lock().expect("profiler is poisoned")
+    }
+}
+
+impl Timings {
+    // moves the runs the systems buffered into the trace
+    fn collect_runs(&mut self) {
+        for (name, timer) in &self.systems {
+            let mut runs = timer.runs();
+            match &mut self.trace {
+                Some(trace) => {
+                    for (thread, start, duration) in runs.drain(..) {
+                        trace.push(*name, Some(thread), start, duration);
+                    }
+                }
+                None => runs.clear(),
+            }
+        }
+    }
+}
+
+// every run of a system while the trace was recorded
+pub struct Trace {
+    start: Instant,
+    // small numbers for the threads of the dispatchers, the frames get their own
+    threads: HashMap<ThreadId, usize>,
+    events: Vec<TraceEvent>,
+    full: bool,
+}
+
+struct TraceEvent {
+    name: &'static str,
+    thread: usize,
+    start: Duration,
+    duration: Duration,
+}
+
+impl Trace {
+    fn new() -> Self {
+        Trace {
+            start: Instant::now(),
+            threads: HashMap::new(),
+            events: Vec::new(),
+            full: false,
+        }
+    }
+
+    // the frames have a row of their own, the systems one per thread
+    fn push(
+        &mut self,
+        name: &'static str,
+        thread: Option<ThreadId>,
+        start: Instant,
+        duration: Duration,
+    ) {
+        if self.events.len() == MAX_TRACE_EVENTS {
+            if !self.full {
+                warn!("the trace is full, later frames are left out");
+                self.full = true;
+            }
+            return;
+        }
+        let thread = match thread {
+            Some(thread) => {
+                let next = self.threads.len() + 1;
+                *self.threads.entry(thread).or_insert(next)
+            }
+            None => 0,
+        };
+        self.events.push(TraceEvent {
+            name,
+            thread,
+            start: start.saturating_duration_since(self.start),
+            duration,
+        });
+    }
+
+    // the trace event format of chrome://tracing and perfetto, times in microseconds
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let mut file = BufWriter::new(File::create(path)?);
+        write!(file, "{{\"traceEvents\":[")?;
+        let mut threads: Vec<_> = self.threads.values().copied().collect();
+        threads.sort();
+        let names = std::iter::once((0, "frames".to_string())).chain(
+            threads
+                .into_iter()
+                .map(|thread| (thread, format!("worker {}", thread))),
+        );
+        for (i, (thread, name)) in names.enumerate() {
+            if i > 0 {
+                write!(file, ",")?;
+            }
+            write!(
+                file,
+                "\n{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":1,\"tid\":{},\"args\":{{\"name\":\"{}\"}}}}",
+                thread, name
+            )?;
+        }
+        for event in &self.events {
+            write!(
+                file,
+                ",\n{{\"name\":\"{}\",\"ph\":\"X\",\"pid\":1,\"tid\":{},\"ts\":{:.3},\"dur\":{:.3}}}",
+                event.name,
+                event.thread,
+                event.start.as_secs_f64() * 1e6,
+                event.duration.as_secs_f64() * 1e6
+            )?;
+        }
+        writeln!(file, "\n]}}")?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+// a new file in the trace folder named after the current time
+pub fn trace_path() -> Result<PathBuf, Error> {
+    let directory = application_root_dir()?.join(TRACE_DIR);
+    fs::create_dir_all(&directory)?;
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Ok(directory.join(format!(
+        "minions-{}-{:03}.json",
+        time.as_secs(),
+        time.subsec_millis()
+    )))
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::{
+    config::{ArenaConfig, PlayerConfig},
+    orders::OrderKind,
+};
+
+// speeds of the playback, the player cycles through them
+pub const SPEEDS: [f32; 4] = [0.5, 1.0, 2.0, 8.0];
+const NORMAL_SPEED: usize = 1;
+
+// a recorded match, everything the simulation needs to compute it again
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Replay {
+    // seed of the rng of the simulation
+    pub seed: u64,
+    pub arena: ArenaConfig,
+    pub players: PlayerConfig,
+    // sorted by tick
+    pub orders: Vec<RecordedOrder>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RecordedOrder {
+    // the tick in which the order was applied
+    pub tick: u64,
+    pub team: u8,
+    // entities are referred to by their SimulationId
+    pub kind: OrderKind<u32>,
+}
+
+// records the orders of the running match, the replay is written to `path` when the game ends
+#[derive(Debug)]
+pub struct ReplayRecorder {
+    pub path: PathBuf,
+    pub replay: Replay,
+}
+
+// the replay which is played back and how fast
+#[derive(Debug)]
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    // index of the first order which has not been applied yet
+    pub next_order: usize,
+    // index into SPEEDS
+    pub speed: usize,
+    pub paused: bool,
+    // tick to jump to, the simulation restarts to jump back
+    pub seek: Option<u64>,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        ReplayPlayer {
+            replay,
+            next_order: 0,
+            speed: NORMAL_SPEED,
+            paused: false,
+            seek: None,
+        }
+    }
+
+    // ticks to simulate per fixed step
+    pub fn speed(&self) -> f32 {
+        if self.paused {
+            0.0
+        } else {
+            SPEEDS[self.speed]
+        }
+    }
+
+    // the tick of the last order
+    pub fn end(&self) -> u64 {
+        self.replay.orders.last().map_or(0, |order| order.tick)
+    }
+}
@@ -0,0 +1,329 @@
+// scripts turn a map into a scenario, they are written in rhai and listed in the map-file.
+// the statements at the top of a script run in the first tick, afterwards the script
+// reacts to the events of the simulation by defining functions named after them:
+//     on_unit_died(unit, team)
+//     on_tile_entered(unit, team, x, z)
+//     on_timer(name)
+// units and buildings are passed by their SimulationId, teams and tiles as integers.
+// every client runs the scripts in the same tick, so they may only learn about the
+// simulation through the functions registered below
+
+use amethyst::utils::application_root_dir;
+use log::{error, info, warn};
+use rhai::{
+    Array, Dynamic, Engine, EvalAltResult, FuncArgs, ImmutableString, Scope, AST, FLOAT, INT,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::{components::Building, orders::OrderKind, resources::TerrainMap, simulation::ticks};
+
+// keeps a script stuck in a loop from freezing the match
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+// what a script asked for, applied by the ScriptSystem once the scripts ran
+#[derive(Debug)]
+pub enum ScriptCommand {
+    Spawn {
+        unit_type: String,
+        team: u8,
+        x: i16,
+        z: i16,
+    },
+    Order {
+        team: u8,
+        kind: OrderKind<u32>,
+    },
+    Message(String),
+}
+
+// what the scripts told the players, only inserted where somebody watches the match
+#[derive(Debug, Default)]
+pub struct ScriptMessages {
+    pending: Vec<String>,
+}
+
+impl ScriptMessages {
+    pub fn push(&mut self, text: String) {
+        self.pending.push(text);
+    }
+
+    // oldest first
+    pub fn take(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+// the state of the simulation the scripts see, taken before they run
+#[derive(Default)]
+pub struct ScriptContext {
+    pub tick: u64,
+    pub terrain: TerrainMap,
+    // units on each tile by SimulationId, in ascending order
+    pub units: HashMap<(i16, i16), Vec<u32>>,
+    pub teams: HashMap<u32, u8>,
+    pub buildings: Vec<(u32, Building)>,
+    pub gold: Vec<(u8, u32)>,
+    pub commands: Vec<ScriptCommand>,
+    timers: Vec<Timer>,
+    // values scripts keep between two calls, functions of rhai can't see global variables
+    values: BTreeMap<String, Dynamic>,
+}
+
+#[derive(Debug)]
+struct Timer {
+    name: String,
+    due: u64,
+    // ticks between two elapses of a repeating timer
+    interval: Option<u64>,
+}
+
+pub struct Scripts {
+    engine: Engine,
+    scripts: Vec<(String, AST)>,
+    context: Arc<Mutex<ScriptContext>>,
+    started: bool,
+    // (team, tile) of every unit after the last tick, by SimulationId
+    pub unit_tiles: BTreeMap<u32, (u8, (i16, i16))>,
+}
+
+impl Scripts {
+    // scripts which fail to load are left out
+    pub fn new(paths: &[String]) -> Self {
+        let context = Arc::new(Mutex::new(ScriptContext::default()));
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        register_functions(&mut engine, &context);
+
+        let scripts = paths
+            .iter()
+            .filter_map(|path| match load(&engine, path) {
+                Ok(ast) => {
+                    info!("loaded the script {}", path);
+                    Some((path.clone(), ast))
+                }
+                Err(e) => {
+                    error!("failed to load the script {}: {}", path, e);
+                    None
+                }
+            })
+            .collect();
+        Scripts {
+            engine,
+            scripts,
+            context,
+            started: false,
+            unit_tiles: BTreeMap::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+
+    pub fn context(&self) -> MutexGuard<'_, ScriptContext> {
+        lock(&self.context)
+    }
+
+    // runs the top of every script the first time it is called
+    pub fn start(&mut self) {
+        if self.started {
+            return;
+        }
+        self.started = true;
+        for (path, ast) in &self.scripts {
+            if let Err(e) = self.engine.consume_ast(ast) {
+                error!("script {} failed: {}", path, e);
+            }
+        }
+    }
+
+    // removes the timers which elapse in the tick, in the order they were started
+    pub fn elapsed_timers(&self, tick: u64) -> Vec<String> {
+        let mut context = self.context();
+        let mut elapsed = Vec::new();
+        for timer in &mut context.timers {
+            if timer.due <= tick {
+                elapsed.push(timer.name.clone());
+                if let Some(interval) = timer.interval {
+                    timer.due = tick + interval;
+                }
+            }
+        }
+        context.timers.retain(|timer| timer.due > tick);
+        elapsed
+    }
+
+    // calls the function in every script which defines it
+    pub fn call(&self, function: &str, args: impl FuncArgs + Clone) {
+        for (path, ast) in &self.scripts {
+            let result: Result<Dynamic, _> =
+                self.engine
+                    .call_fn(&mut Scope::new(), ast, function, args.clone());
+            if let Err(e) = result {
+                if let EvalAltResult::ErrorFunctionNotFound(signature, _) = &*e {
+                    if signature.starts_with(function) {
+                        continue;
+                    }
+                }
+                error!("script {} failed in {}: {}", path, function, e);
+            }
+        }
+    }
+}
+
+fn load(engine: &Engine, path: &str) -> amethyst::Result<AST> {
+    let source = fs::read_to_string(application_root_dir()?.join(path))?;
+    engine
+        .compile(&source)
+        .map_err(|e| amethyst::Error::from_string(e.to_string()))
+}
+
+fn register_functions(engine: &mut Engine, context: &Arc<Mutex<ScriptContext>>) {
+    // queries
+    let c = Arc::clone(context);
+    engine.register_fn("tick", move || lock(&c).tick as INT);
+    let c = Arc::clone(context);
+    engine.register_fn("terrain", move |x: INT, z: INT| {
+        lock(&c)
+            .terrain
+            .get(x as i16, z as i16)
+            .map(|terrain| format!("{:?}", terrain))
+            .unwrap_or_default()
+    });
+    let c = Arc::clone(context);
+    engine.register_fn("height", move |x: INT, z: INT| {
+        lock(&c).terrain.height(x as i16, z as i16) as FLOAT
+    });
+    let c = Arc::clone(context);
+    engine.register_fn("units_at", move |x: INT, z: INT| -> Array {
+        lock(&c)
+            .units
+            .get(&(x as i16, z as i16))
+            .map(|units| units.iter().map(|&id| Dynamic::from(id as INT)).collect())
+            .unwrap_or_default()
+    });
+    // -1 if there is no building on the tile
+    let c = Arc::clone(context);
+    engine.register_fn("building_at", move |x: INT, z: INT| {
+        lock(&c)
+            .buildings
+            .iter()
+            .find(|(_, building)| building.contains(x as i16, z as i16))
+            .map_or(-1, |&(id, _)| id as INT)
+    });
+    // -1 if the unit or building doesn't exist
+    let c = Arc::clone(context);
+    engine.register_fn("team_of", move |id: INT| {
+        lock(&c)
+            .teams
+            .get(&(id as u32))
+            .map_or(-1, |&team| team as INT)
+    });
+    let c = Arc::clone(context);
+    engine.register_fn("gold", move |team: INT| {
+        lock(&c)
+            .gold
+            .iter()
+            .find(|&&(other, _)| other as INT == team)
+            .map_or(0, |&(_, gold)| gold as INT)
+    });
+
+    // actions, applied after the scripts ran
+    let c = Arc::clone(context);
+    engine.register_fn(
+        "spawn",
+        move |unit_type: ImmutableString, team: INT, x: INT, z: INT| {
+            lock(&c).commands.push(ScriptCommand::Spawn {
+                unit_type: unit_type.to_string(),
+                team: team as u8,
+                x: x as i16,
+                z: z as i16,
+            })
+        },
+    );
+    let c = Arc::clone(context);
+    engine.register_fn(
+        "move_units",
+        move |team: INT, units: Array, x: INT, z: INT| {
+            let units = units
+                .into_iter()
+                .filter_map(|unit| unit.try_cast::<INT>())
+                .map(|id| id as u32)
+                .collect();
+            lock(&c).commands.push(ScriptCommand::Order {
+                team: team as u8,
+                kind: OrderKind::Move {
+                    units,
+                    x: x as i16,
+                    z: z as i16,
+                },
+            })
+        },
+    );
+    let c = Arc::clone(context);
+    engine.register_fn(
+        "train",
+        move |team: INT, building: INT, unit_type: ImmutableString| {
+            lock(&c).commands.push(ScriptCommand::Order {
+                team: team as u8,
+                kind: OrderKind::Train {
+                    building: building as u32,
+                    unit_type: unit_type.to_string(),
+                },
+            })
+        },
+    );
+    let c = Arc::clone(context);
+    engine.register_fn("message", move |text: ImmutableString| {
+        lock(&c)
+            .commands
+            .push(ScriptCommand::Message(text.to_string()))
+    });
+
+    // timers call on_timer(name) once or every `seconds`
+    let c = Arc::clone(context);
+    engine.register_fn("after", move |seconds: FLOAT, name: ImmutableString| {
+        start_timer(&mut lock(&c), seconds, &name, false)
+    });
+    let c = Arc::clone(context);
+    engine.register_fn("every", move |seconds: FLOAT, name: ImmutableString| {
+        start_timer(&mut lock(&c), seconds, &name, true)
+    });
+
+    // values kept between calls, () if the key was never set
+    let c = Arc::clone(context);
+    engine.register_fn("set", move |key: ImmutableString, value: Dynamic| {
+        lock(&c).values.insert(key.to_string(), value);
+    });
+    let c = Arc::clone(context);
+    engine.register_fn("get", move |key: ImmutableString| {
+        lock(&c)
+            .values
+            .get(key.as_str())
+            .cloned()
+            .unwrap_or_else(|| Dynamic::from(()))
+    });
+}
+
+// the functions never panic while they hold the lock
+fn lock(context: &Mutex<ScriptContext>) -> MutexGuard<'_, ScriptContext> {
+    context.lock().expect("script context is poisoned")
+}
+
+fn start_timer(context: &mut ScriptContext, seconds: FLOAT, name: &str, repeat: bool) {
+    // a timer elapses one tick later at the earliest
+    let interval = u64::from(ticks(seconds as f32).max(1));
+    if repeat && context.timers.iter().any(|timer| timer.name == name) {
+        warn!("the timer {} is already running", name);
+        return;
+    }
+    context.timers.push(Timer {
+        name: name.to_string(),
+        due: context.tick + interval,
+        interval: if repeat { Some(interval) } else { None },
+    });
+}
@@ -0,0 +1,54 @@
+use amethyst::ecs::Entity;
+
+// tile under the mouse cursor, written by the HoverSystem
+#[derive(Debug, Default)]
+pub struct HoveredTile(pub Option<(i16, i16)>);
+
+// position of the cursor a gamepad moves, in pixels. None while the mouse is used
+#[derive(Debug, Default)]
+pub struct VirtualCursor(pub Option<(f32, f32)>);
+
+// entities selected by the local player
+#[derive(Debug, Default)]
+pub struct Selection {
+    pub entities: Vec<Entity>,
+}
+
+impl Selection {
+    pub fn clear(&mut self) {
+        self.entities.clear();
+    }
+
+    pub fn set(&mut self, entities: Vec<Entity>) {
+        self.entities = entities;
+    }
+}
+
+// the number keys the player can bind a selection to
+pub const CONTROL_GROUPS: usize = 10;
+
+// selections bound to the number keys, they stay until the player binds another one
+#[derive(Debug, Default)]
+pub struct ControlGroups {
+    groups: [Vec<Entity>; CONTROL_GROUPS],
+}
+
+impl ControlGroups {
+    pub fn assign(&mut self, group: u8, entities: Vec<Entity>) {
+        if let Some(slot) = self.groups.get_mut(group as usize) {
+            *slot = entities;
+        }
+    }
+
+    pub fn get(&self, group: u8) -> &[Entity] {
+        self.groups
+            .get(group as usize)
+            .map_or(&[], |entities| entities.as_slice())
+    }
+
+    pub fn retain(&mut self, mut alive: impl FnMut(Entity) -> bool) {
+        for group in &mut self.groups {
+            group.retain(|entity| alive(*entity));
+        }
+    }
+}
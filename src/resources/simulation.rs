@@ -0,0 +1,58 @@
+use std::collections::VecDeque;
+
+use crate::components::SimulationId;
+
+// ticks whose checksums are kept to compare them with other clients
+const MAX_CHECKSUMS: usize = 600;
+
+// progress of the fixed-tick simulation
+#[derive(Debug, Default)]
+pub struct Simulation {
+    tick: u64,
+    // (tick, checksum of the state after the tick), oldest first
+    checksums: VecDeque<(u64, u64)>,
+    next_id: u32,
+}
+
+// ticks of the simulation per fixed update of a match, the console changes it
+#[derive(Debug)]
+pub struct SimulationSpeed(pub f32);
+
+impl SimulationSpeed {
+    pub const MAX: f32 = 16.0;
+}
+
+impl Default for SimulationSpeed {
+    fn default() -> Self {
+        SimulationSpeed(1.0)
+    }
+}
+
+impl Simulation {
+    // number of finished ticks
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    // None if the tick is not finished or too old
+    pub fn checksum(&self, tick: u64) -> Option<u64> {
+        self.checksums
+            .iter()
+            .find(|&&(finished, _)| finished == tick)
+            .map(|&(_, checksum)| checksum)
+    }
+
+    // ids are handed out in the order the simulation creates the entities
+    pub fn new_id(&mut self) -> SimulationId {
+        self.next_id += 1;
+        SimulationId(self.next_id)
+    }
+
+    pub fn finish_tick(&mut self, checksum: u64) {
+        if self.checksums.len() >= MAX_CHECKSUMS {
+            self.checksums.pop_front();
+        }
+        self.checksums.push_back((self.tick, checksum));
+        self.tick += 1;
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{components::Terrain, grid::Topology};
+
+// terrain and height of every tile of the arena
+#[derive(Clone, Debug, Default)]
+pub struct TerrainMap {
+    width: i16,
+    depth: i16,
+    topology: Topology,
+    tiles: Vec<Terrain>,
+    heights: Vec<f32>,
+}
+
+impl TerrainMap {
+    pub fn new(width: i16, depth: i16, topology: Topology) -> Self {
+        let tiles = (width.max(0) as usize) * (depth.max(0) as usize);
+        TerrainMap {
+            width,
+            depth,
+            topology,
+            tiles: vec![Terrain::default(); tiles],
+            heights: vec![0.0; tiles],
+        }
+    }
+
+    pub fn width(&self) -> i16 {
+        self.width
+    }
+
+    pub fn depth(&self) -> i16 {
+        self.depth
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    fn index(&self, x: i16, z: i16) -> Option<usize> {
+        if 0 <= x && x < self.width && 0 <= z && z < self.depth {
+            Some(z as usize * self.width as usize + x as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn contains(&self, x: i16, z: i16) -> bool {
+        self.index(x, z).is_some()
+    }
+
+    pub fn get(&self, x: i16, z: i16) -> Option<Terrain> {
+        self.index(x, z).map(|index| self.tiles[index])
+    }
+
+    pub fn set(&mut self, x: i16, z: i16, terrain: Terrain) {
+        if let Some(index) = self.index(x, z) {
+            self.tiles[index] = terrain;
+        }
+    }
+
+    // height of the center of a tile, the ground around the arena is at y = 0
+    pub fn height(&self, x: i16, z: i16) -> f32 {
+        self.index(x, z).map_or(0.0, |index| self.heights[index])
+    }
+
+    pub fn set_height(&mut self, x: i16, z: i16, height: f32) {
+        if let Some(index) = self.index(x, z) {
+            self.heights[index] = height;
+        }
+    }
+
+    // height of a corner of a tile (see Topology::corners), the mean of
+    // the tiles sharing the corner so neighbours are connected by slopes
+    pub fn corner_height(&self, (x, z): (i16, i16), corner: usize) -> f32 {
+        let (center_x, center_z) = self.topology.center((x, z));
+        let (dx, dz) = self.topology.corners()[corner];
+        let (corner_x, corner_z) = (center_x + dx, center_z + dz);
+        // every tile sharing the corner has its center as far away from it as this one
+        let radius = (dx * dx + dz * dz).sqrt() + 0.01;
+        let adjacent: Vec<f32> = (x - 1..=x + 1)
+            .flat_map(|x| (z - 1..=z + 1).map(move |z| (x, z)))
+            .filter(|&tile| {
+                let (x, z) = self.topology.center(tile);
+                (x - corner_x).powi(2) + (z - corner_z).powi(2) <= radius * radius
+            })
+            .filter_map(|(x, z)| self.index(x, z))
+            .map(|index| self.heights[index])
+            .collect();
+        if adjacent.is_empty() {
+            0.0
+        } else {
+            adjacent.iter().sum::<f32>() / adjacent.len() as f32
+        }
+    }
+
+    // lowest and highest point of the terrain
+    pub fn height_range(&self) -> (f32, f32) {
+        self.heights.iter().fold((0.0, 0.0), |(min, max), &height| {
+            (height.min(min), height.max(max))
+        })
+    }
+
+    // tiles outside of the arena block the view as well
+    pub fn blocks_sight(&self, x: i16, z: i16) -> bool {
+        self.get(x, z).map_or(true, Terrain::blocks_sight)
+    }
+}
@@ -0,0 +1,70 @@
+use amethyst::{
+    assets::{Handle, Prefab},
+    ecs::{world::EntitiesRes, world::LazyBuilder, LazyUpdate},
+    renderer::Material,
+};
+use std::collections::BTreeMap;
+
+use crate::components::{GridPosition, Health, Minion, Sight, Team, UnitPrefabData, UnitStats};
+
+pub struct UnitType {
+    // None on the dedicated server, which loads nothing but the stats
+    pub prefab: Option<Handle<Prefab<UnitPrefabData>>>,
+    pub stats: UnitStats,
+    // the prefab has no material of its own
+    pub team_colored: bool,
+}
+
+// every unit archetype found in assets/units/, keyed by the file name
+#[derive(Default)]
+pub struct UnitRegistry {
+    types: BTreeMap<String, UnitType>,
+}
+
+impl UnitRegistry {
+    pub fn insert(&mut self, id: String, unit_type: UnitType) {
+        self.types.insert(id, unit_type);
+    }
+
+    pub fn get(&self, id: &str) -> Option<&UnitType> {
+        self.types.get(id)
+    }
+
+    pub fn stats(&self, id: &str) -> Option<&UnitStats> {
+        self.get(id).map(|unit_type| &unit_type.stats)
+    }
+
+    // ids sorted by name so the command cards are the same on every run
+    pub fn ids(&self) -> Vec<String> {
+        self.types.keys().cloned().collect()
+    }
+
+    // creates a unit, the components of the prefab are attached by the PrefabLoaderSystem
+    pub fn spawn<'a>(
+        &self,
+        lazy: &'a LazyUpdate,
+        entities: &EntitiesRes,
+        id: &str,
+        team: u8,
+        position: GridPosition,
+        team_material: Option<Handle<Material>>,
+    ) -> Option<LazyBuilder<'a>> {
+        let unit_type = self.get(id)?;
+        let mut builder = lazy.create_entity(entities);
+        if let Some(prefab) = &unit_type.prefab {
+            builder = builder.with(prefab.clone());
+        }
+        builder = builder
+            .with(position)
+            .with(Minion {
+                unit_type: id.to_string(),
+            })
+            .with(Team(team))
+            .with(Sight(unit_type.stats.sight))
+            .with(Health(unit_type.stats.health));
+        if let (true, Some(team_material)) = (unit_type.team_colored, team_material) {
+            builder = builder.with(team_material);
+        }
+        Some(builder)
+    }
+}
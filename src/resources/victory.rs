@@ -0,0 +1,35 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+// how a team did, shown once the match is over
+#[derive(Clone, Copy, Debug)]
+pub struct PlayerResult {
+    pub team: u8,
+    pub won: bool,
+    pub gold: u32,
+    pub units: usize,
+    pub buildings: usize,
+}
+
+// progress towards the victory conditions of the arena
+#[derive(Debug, Default)]
+pub struct Victory {
+    // ticks a team held the control points of a condition, by (condition, team)
+    pub held: BTreeMap<(usize, u8), u32>,
+    pub defeated: BTreeSet<u8>,
+    results: Option<Vec<PlayerResult>>,
+}
+
+impl Victory {
+    pub fn is_over(&self) -> bool {
+        self.results.is_some()
+    }
+
+    // None while the match goes on
+    pub fn results(&self) -> Option<&[PlayerResult]> {
+        self.results.as_deref()
+    }
+
+    pub fn finish(&mut self, results: Vec<PlayerResult>) {
+        self.results = Some(results);
+    }
+}
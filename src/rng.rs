@@ -0,0 +1,62 @@
+// seeded random numbers which are the same on every platform (splitmix64)
+
+#[derive(Clone, Debug, Default, Hash)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // uniform in [0, bound), bound must not be 0
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    // uniform in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // replays and other clients depend on these exact numbers
+    #[test]
+    fn sequence_of_a_seed() {
+        let mut rng = Rng::new(0);
+        assert_eq!(rng.next_u64(), 0xE220_A839_7B1D_CDAF);
+        assert_eq!(rng.next_u64(), 0x6E78_9E6A_A1B9_65F4);
+        assert_eq!(rng.next_u64(), 0x06C4_5D18_8009_454F);
+
+        let mut rng = Rng::new(42);
+        assert_eq!(rng.next_u64(), 0xBDD7_3226_2FEB_6E95);
+        assert_eq!(rng.next_u64(), 0x28EF_E333_B266_F103);
+        assert_eq!(rng.next_u64(), 0x4752_6757_130F_9F52);
+        assert_eq!(rng.next_u64(), 0x581C_E1FF_0E4A_E394);
+        assert_eq!(rng.below(10), 0);
+        assert_eq!(rng.next_f32(), 14_566_449.0 / 16_777_216.0);
+    }
+
+    #[test]
+    fn clones_continue_the_same_sequence() {
+        let mut rng = Rng::new(7);
+        rng.next_u64();
+        let mut clone = rng.clone();
+        for _ in 0..100 {
+            assert_eq!(rng.next_u64(), clone.next_u64());
+        }
+    }
+}
@@ -0,0 +1,397 @@
+// the dedicated server hosts lan matches without a window, one after the other fills its
+// lobby, every match runs the simulation as well to tell which player is out of sync
+
+use amethyst::{
+    core::Transform,
+    ecs::{Builder, Dispatcher, World, WorldExt},
+    Error,
+};
+use log::{info, warn};
+use serde::Deserialize;
+use std::{
+    fs,
+    io::ErrorKind,
+    net::TcpListener,
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    components::{Sight, UnitStats},
+    minions::{create_building, initialize_scenario, terrain_map, world_borders},
+    network::{self, Connection, Lobby, MatchSetup},
+    orders::LocalOrders,
+    resources::{Lockstep, LockstepError, Simulation, Treasury, UnitRegistry, UnitType, Victory},
+    rng::Rng,
+    simulation::{self, TICKS_PER_SECOND},
+};
+
+// the parts of a unit prefab the server needs
+#[derive(Deserialize)]
+struct UnitFile {
+    entities: Vec<UnitEntity>,
+}
+
+#[derive(Deserialize)]
+struct UnitEntity {
+    data: Option<UnitData>,
+}
+
+#[derive(Deserialize)]
+struct UnitData {
+    stats: UnitStats,
+}
+
+pub struct Server {
+    listener: TcpListener,
+    setup: MatchSetup,
+    // stats of every unit by its id
+    units: Vec<(String, UnitStats)>,
+    max_matches: usize,
+    // players waiting for the next match
+    lobby: Lobby,
+    matches: Vec<Match>,
+    matches_started: usize,
+}
+
+impl Server {
+    pub fn new(
+        port: u16,
+        setup: MatchSetup,
+        units_dir: &Path,
+        max_matches: usize,
+    ) -> Result<Self, Error> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        let units = load_units(units_dir)?;
+        info!(
+            "listening on port {}, matches are for {} players",
+            port, setup.players.teams
+        );
+        Ok(Server {
+            listener,
+            lobby: Lobby::new(0, setup.players.teams),
+            setup,
+            units,
+            max_matches,
+            matches: Vec::new(),
+            matches_started: 0,
+        })
+    }
+
+    pub fn run(&mut self) -> Result<(), Error> {
+        loop {
+            self.poll()?;
+            thread::sleep(Duration::from_millis(1000 / TICKS_PER_SECOND as u64 / 2));
+        }
+    }
+
+    // lets new players join and simulates the matches as far as they are due
+    fn poll(&mut self) -> Result<(), Error> {
+        self.accept()?;
+        for game in &mut self.matches {
+            game.update();
+        }
+        self.matches.retain(|game| {
+            let error = game.error();
+            if let Some(error) = error {
+                warn!("match {} ended: {}", game.id, error);
+            } else if game.is_over() {
+                info!("match {} is decided", game.id);
+            } else if !game.is_connected() {
+                info!("match {} ended, every player left", game.id);
+            }
+            error.is_none() && game.is_connected() && !game.is_over()
+        });
+        Ok(())
+    }
+
+    fn accept(&mut self) -> Result<(), Error> {
+        loop {
+            let (stream, address) = match self.listener.accept() {
+                Ok(accepted) => accepted,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e.into()),
+            };
+            if self.matches.len() >= self.max_matches {
+                let reason = format!("{} matches are running", self.matches.len());
+                network::refuse(stream, address, &reason);
+                continue;
+            }
+            self.lobby.join(stream, address, &self.setup);
+            if self.lobby.is_full() {
+                self.start_match();
+            }
+        }
+        // a player who leaves before the match starts frees the team
+        self.lobby.remove_lost();
+        Ok(())
+    }
+
+    fn start_match(&mut self) {
+        // a player who left since joining is noticed by the lockstep like any other
+        let connections = self.lobby.start();
+        self.matches_started += 1;
+        info!("match {} started", self.matches_started);
+        self.matches.push(Match::new(
+            self.matches_started,
+            &self.setup,
+            &self.units,
+            connections,
+        ));
+    }
+}
+
+// a running match, its world holds nothing but the simulation
+struct Match {
+    id: usize,
+    world: World,
+    dispatcher: Dispatcher<'static, 'static>,
+    started: Instant,
+}
+
+impl Match {
+    fn new(
+        id: usize,
+        setup: &MatchSetup,
+        units: &[(String, UnitStats)],
+        connections: Vec<Connection>,
+    ) -> Self {
+        let mut world = World::new();
+        // components the game attaches but no simulation system reads
+        world.register::<Transform>();
+        world.register::<Sight>();
+
+        let mut unit_registry = UnitRegistry::default();
+        for (id, stats) in units {
+            let unit_type = UnitType {
+                prefab: None,
+                stats: stats.clone(),
+                team_colored: false,
+            };
+            unit_registry.insert(id.clone(), unit_type);
+        }
+        let unit_ids = unit_registry.ids();
+        let teams = setup.players.teams;
+        world.insert(unit_registry);
+        world.insert(terrain_map(&setup.arena));
+        world.insert(world_borders(&setup.arena));
+        world.insert(setup.arena.clone());
+        world.insert(setup.players.clone());
+        world.insert(Treasury::new(teams, setup.players.starting_gold));
+        world.insert(Rng::new(setup.seed));
+        world.insert(Simulation::default());
+        world.insert(LocalOrders::default());
+        world.insert(Lockstep::new(
+            None,
+            teams,
+            setup.input_delay,
+            true,
+            connections,
+        ));
+
+        initialize_scenario(&mut world);
+        let dispatcher = simulation::build_dispatcher(&mut world);
+        for config in setup.arena.buildings.clone() {
+            create_building(&mut world, config, &unit_ids).build();
+        }
+        Match {
+            id,
+            world,
+            dispatcher,
+            started: Instant::now(),
+        }
+    }
+
+    // simulates the ticks which are due and whose commands arrived
+    fn update(&mut self) {
+        let due = (self.started.elapsed().as_secs_f64() * TICKS_PER_SECOND as f64) as u64;
+        let tick = self.world.read_resource::<Simulation>().tick();
+        self.world.write_resource::<Lockstep>().poll(tick);
+        loop {
+            if self.is_over() || self.error().is_some() {
+                return;
+            }
+            let tick = self.world.read_resource::<Simulation>().tick();
+            if tick >= due || !self.world.read_resource::<Lockstep>().ready(tick) {
+                return;
+            }
+            simulation::step(&mut self.dispatcher, &mut self.world);
+            self.world
+                .write_resource::<Lockstep>()
+                .verify(&self.world.read_resource::<Simulation>());
+        }
+    }
+
+    fn is_over(&self) -> bool {
+        self.world.read_resource::<Victory>().is_over()
+    }
+
+    // the players are disconnected once the match is dropped
+    fn error(&self) -> Option<LockstepError> {
+        self.world.read_resource::<Lockstep>().error()
+    }
+
+    fn is_connected(&self) -> bool {
+        self.world.read_resource::<Lockstep>().is_connected()
+    }
+}
+
+// the stats of the units in assets/units/, the server has no use for the rest of the prefabs
+fn load_units(units_dir: &Path) -> Result<Vec<(String, UnitStats)>, Error> {
+    let mut units = Vec::new();
+    for path in fs::read_dir(units_dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+    {
+        if path
+            .extension()
+            .map_or(true, |extension| extension != "ron")
+        {
+            continue;
+        }
+        let id = match path.file_stem() {
+            Some(id) => id.to_string_lossy().into_owned(),
+            None => continue,
+        };
+        let file: UnitFile = ron::de::from_bytes(&fs::read(&path)?)
+            .map_err(|e| Error::from_string(format!("failed to load {:?}: {}", path, e)))?;
+        match file.entities.into_iter().find_map(|entity| entity.data) {
+            Some(data) => units.push((id, data.stats)),
+            None => warn!("the prefab of unit {} has no data", id),
+        }
+    }
+    info!(
+        "loaded units: {:?}",
+        units.iter().map(|(id, _)| id).collect::<Vec<_>>()
+    );
+    Ok(units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{GeneratorConfig, PlayerConfig},
+        grid::Topology,
+        mapgen,
+    };
+    use std::{
+        net::TcpStream,
+        sync::mpsc::{self, Receiver},
+    };
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    // a server for matches of two players on a generated arena and its address
+    fn server(max_matches: usize) -> (Server, String) {
+        let setup = MatchSetup {
+            seed: 1,
+            arena: mapgen::generate(1, &GeneratorConfig::default(), 1.0, Topology::Square),
+            players: PlayerConfig {
+                teams: 2,
+                starting_gold: 100,
+            },
+            input_delay: 2,
+        };
+        let units_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("assets")
+            .join("units");
+        let server = Server::new(0, setup, &units_dir, max_matches).unwrap();
+        let address = format!("127.0.0.1:{}", server.listener.local_addr().unwrap().port());
+        (server, address)
+    }
+
+    // network::join blocks until the match starts, the server has to run meanwhile
+    fn join(address: &str) -> Receiver<Result<(u8, MatchSetup, Connection), String>> {
+        let (sender, receiver) = mpsc::channel();
+        let address = address.to_string();
+        thread::spawn(move || {
+            let joined = network::join(&address).map_err(|e| e.to_string());
+            let _ = sender.send(joined);
+        });
+        receiver
+    }
+
+    fn poll_until(server: &mut Server, mut condition: impl FnMut(&mut Server) -> bool) {
+        let start = Instant::now();
+        while !condition(server) {
+            assert!(start.elapsed() < TIMEOUT, "the server got stuck");
+            server.poll().unwrap();
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn two_players_reach_the_first_tick() {
+        let (mut server, address) = server(1);
+        // somebody who leaves the lobby before the match gives the team up
+        let quitter = TcpStream::connect(&address).unwrap();
+        poll_until(&mut server, |server| server.lobby.len() == 1);
+        drop(quitter);
+        poll_until(&mut server, |server| server.lobby.is_empty());
+
+        let joining = vec![join(&address), join(&address)];
+        let mut players = Vec::new();
+        poll_until(&mut server, |_| {
+            players.extend(
+                joining
+                    .iter()
+                    .filter_map(|receiver| receiver.try_recv().ok()),
+            );
+            players.len() == 2
+        });
+        let mut players: Vec<_> = players.into_iter().map(Result::unwrap).collect();
+        players.sort_by_key(|&(team, ..)| team);
+        let teams: Vec<u8> = players.iter().map(|&(team, ..)| team).collect();
+        assert_eq!(teams, vec![0, 1]);
+        assert_eq!(server.matches.len(), 1);
+
+        // the first tick after the input delay waits for the commands of both
+        let delay = server.setup.input_delay;
+        let mut locksteps: Vec<Lockstep> = players
+            .into_iter()
+            .map(|(team, setup, connection)| {
+                Lockstep::new(
+                    Some(team),
+                    setup.players.teams,
+                    setup.input_delay,
+                    false,
+                    vec![connection],
+                )
+            })
+            .collect();
+        poll_until(&mut server, |server| {
+            server.matches[0].world.read_resource::<Simulation>().tick() == delay
+        });
+        for lockstep in &mut locksteps {
+            lockstep.submit(delay, Vec::new(), None);
+        }
+        poll_until(&mut server, |server| {
+            server.matches[0].world.read_resource::<Simulation>().tick() > delay
+        });
+        // the server passed the commands of each player on to the other
+        poll_until(&mut server, |_| {
+            locksteps.iter_mut().all(|lockstep| {
+                lockstep.poll(delay);
+                lockstep.ready(delay)
+            })
+        });
+        assert_eq!(server.matches[0].error(), None);
+    }
+
+    #[test]
+    fn players_beyond_the_matches_are_refused() {
+        let (mut server, address) = server(0);
+        let joining = join(&address);
+        let mut refused = None;
+        poll_until(&mut server, |_| {
+            refused = joining.try_recv().ok();
+            refused.is_some()
+        });
+        let error = refused.unwrap().err().expect("the player was let in");
+        assert!(error.contains("0 matches are running"), "{}", error);
+        assert!(server.lobby.is_empty());
+    }
+}
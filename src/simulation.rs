@@ -0,0 +1,140 @@
+// the gameplay systems run on a fixed tick in their own dispatcher,
+// so every client computes the same state no matter its frame rate
+
+use amethyst::{
+    core::SystemDesc,
+    ecs::{
+        world::EntitiesRes, Dispatcher, DispatcherBuilder, Entity, Join, ReadStorage, World,
+        WorldExt,
+    },
+};
+use std::collections::HashMap;
+
+use crate::{
+    components::SimulationId,
+    systems::{
+        add_profiled, profiler, ChecksumSystem, CombatSystem, LockstepSystem, MovementSystem,
+        OrderSystemDesc, PlaybackSystem, ProductionSystem, RecordSystemDesc, ScriptSystem,
+        TowerSystem, VictorySystem, WaveSystem,
+    },
+};
+
+pub const TICKS_PER_SECOND: u32 = 60;
+
+// number of ticks closest to a duration in seconds
+pub fn ticks(seconds: f32) -> u32 {
+    (seconds * TICKS_PER_SECOND as f32).round() as u32
+}
+
+pub fn build_dispatcher(world: &mut World) -> Dispatcher<'static, 'static> {
+    // timed like the systems of the bundle
+    let profiler = profiler(world);
+    let mut builder = DispatcherBuilder::new();
+    // the orders of the players and of replays are fed in before they are applied
+    add_profiled(
+        &mut builder,
+        &profiler,
+        LockstepSystem,
+        "lockstep_system",
+        &[],
+    );
+    add_profiled(
+        &mut builder,
+        &profiler,
+        PlaybackSystem,
+        "playback_system",
+        &[],
+    );
+    add_profiled(
+        &mut builder,
+        &profiler,
+        OrderSystemDesc::default().build(world),
+        "order_system",
+        &["lockstep_system", "playback_system"],
+    );
+    add_profiled(
+        &mut builder,
+        &profiler,
+        RecordSystemDesc::default().build(world),
+        "record_system",
+        &["lockstep_system", "playback_system"],
+    );
+    add_profiled(
+        &mut builder,
+        &profiler,
+        ProductionSystem,
+        "production_system",
+        &["order_system"],
+    );
+    add_profiled(
+        &mut builder,
+        &profiler,
+        MovementSystem,
+        "movement_system",
+        &["order_system"],
+    );
+    add_profiled(
+        &mut builder,
+        &profiler,
+        WaveSystem,
+        "wave_system",
+        &["production_system", "movement_system"],
+    );
+    add_profiled(
+        &mut builder,
+        &profiler,
+        TowerSystem,
+        "tower_system",
+        &["wave_system"],
+    );
+    add_profiled(
+        &mut builder,
+        &profiler,
+        CombatSystem,
+        "combat_system",
+        &["tower_system"],
+    );
+    // scripts see the state after the tick
+    add_profiled(
+        &mut builder,
+        &profiler,
+        ScriptSystem,
+        "script_system",
+        &["combat_system"],
+    );
+    add_profiled(
+        &mut builder,
+        &profiler,
+        VictorySystem,
+        "victory_system",
+        &["script_system"],
+    );
+    add_profiled(
+        &mut builder,
+        &profiler,
+        ChecksumSystem,
+        "checksum_system",
+        &["victory_system"],
+    );
+    let mut dispatcher = builder.build();
+    dispatcher.setup(world);
+    dispatcher
+}
+
+// runs one tick
+pub fn step(dispatcher: &mut Dispatcher<'static, 'static>, world: &mut World) {
+    dispatcher.dispatch(world);
+    // spawned units have to exist before the next tick
+    world.maintain();
+}
+
+// the entities of the buildings and units by their SimulationId
+pub fn entities_by_id(
+    entities: &EntitiesRes,
+    ids: &ReadStorage<'_, SimulationId>,
+) -> HashMap<u32, Entity> {
+    (entities, ids)
+        .join()
+        .map(|(entity, id)| (id.0, entity))
+        .collect()
+}
@@ -0,0 +1,92 @@
+use amethyst::{
+    core::{Time, Transform},
+    derive::SystemDesc,
+    ecs::{Entities, Entity, Join, Read, ReadStorage, System, SystemData, Write},
+};
+use log::info;
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    components::{Building, Health, Team},
+    resources::{CameraBookmarks, LocalPlayer},
+};
+
+// a burst of alerts is only logged once
+const ALERT_LOG_SECONDS: f64 = 5.0;
+
+#[derive(Default, SystemDesc)]
+pub struct AlertSystem {
+    // health of the units of the local player in the last frame
+    healths: HashMap<Entity, u32>,
+    // buildings of the local player in the last frame, None before the first frame
+    buildings: Option<HashSet<Entity>>,
+    last_logged: Option<f64>,
+}
+
+// this system remembers where the last thing happened the local player should know
+// about: a unit under attack or a finished building
+impl<'s> System<'s> for AlertSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Team>,
+        ReadStorage<'s, Health>,
+        ReadStorage<'s, Building>,
+        ReadStorage<'s, Transform>,
+        Read<'s, LocalPlayer>,
+        Read<'s, Time>,
+        Write<'s, CameraBookmarks>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, teams, healths, buildings, transforms, local_player, time, mut bookmarks): Self::SystemData,
+    ) {
+        let team = local_player.team;
+        let mut alert = None;
+
+        let current: HashMap<Entity, u32> = (&entities, &teams, &healths)
+            .join()
+            .filter(|(_, owner, _)| owner.0 == team)
+            .map(|(entity, _, health)| (entity, health.0))
+            .collect();
+        for (entity, health) in &current {
+            if self
+                .healths
+                .get(entity)
+                .map_or(false, |previous| health < previous)
+            {
+                alert = Some((*entity, "a unit is under attack"));
+            }
+        }
+        self.healths = current;
+
+        let current: HashSet<Entity> = (&entities, &buildings, &teams)
+            .join()
+            .filter(|(_, _, owner)| owner.0 == team)
+            .map(|(entity, _, _)| entity)
+            .collect();
+        // the buildings of the arena aren't news
+        if let Some(previous) = &self.buildings {
+            if let Some(entity) = current.difference(previous).next() {
+                alert = Some((*entity, "a building is finished"));
+            }
+        }
+        self.buildings = Some(current);
+
+        let (entity, message) = match alert {
+            Some(alert) => alert,
+            None => return,
+        };
+        if let Some(transform) = transforms.get(entity) {
+            bookmarks.alert = Some(*transform.translation());
+        }
+        let now = time.absolute_real_time_seconds();
+        if self
+            .last_logged
+            .map_or(true, |logged| now - logged >= ALERT_LOG_SECONDS)
+        {
+            info!("{}", message);
+            self.last_logged = Some(now);
+        }
+    }
+}
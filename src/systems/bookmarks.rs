@@ -0,0 +1,86 @@
+use amethyst::{
+    core::{SystemDesc, Transform},
+    ecs::{Join, Read, ReadStorage, System, SystemData, World, Write},
+    input::{InputEvent, InputHandler},
+    shrev::{EventChannel, ReaderId},
+};
+
+use crate::{
+    components::CameraControlTag,
+    input::{debug_layer_down, ActionBinding, MovementBindingTypes},
+    resources::{CameraBookmarks, CameraFollow},
+    systems::camera::looking_at,
+};
+
+#[derive(Default, Debug)]
+pub struct BookmarkSystemDesc;
+
+impl<'a, 'b> SystemDesc<'a, 'b, BookmarkSystem> for BookmarkSystemDesc {
+    fn build(self, world: &mut World) -> BookmarkSystem {
+        <BookmarkSystem as System<'_>>::SystemData::setup(world);
+        let reader_id = world
+            .fetch_mut::<EventChannel<InputEvent<MovementBindingTypes>>>()
+            .register_reader();
+        BookmarkSystem { reader_id }
+    }
+}
+
+// this system stores the position of the camera in bookmarks, starts the jumps
+// to them and to the last alert and toggles following the selection,
+// the CameraSystem moves the camera
+pub struct BookmarkSystem {
+    reader_id: ReaderId<InputEvent<MovementBindingTypes>>,
+}
+
+impl<'s> System<'s> for BookmarkSystem {
+    type SystemData = (
+        Read<'s, EventChannel<InputEvent<MovementBindingTypes>>>,
+        Read<'s, InputHandler<MovementBindingTypes>>,
+        ReadStorage<'s, CameraControlTag>,
+        ReadStorage<'s, Transform>,
+        Write<'s, CameraBookmarks>,
+        Write<'s, CameraFollow>,
+    );
+
+    fn run(
+        &mut self,
+        (input_events, input, camera_tags, transforms, mut bookmarks, mut follow): Self::SystemData,
+    ) {
+        let camera = match (&camera_tags, &transforms).join().next() {
+            Some((_, transform)) => transform,
+            None => {
+                // the events of the frame are stale by the time the camera exists
+                input_events.read(&mut self.reader_id).for_each(drop);
+                return;
+            }
+        };
+        for event in input_events.read(&mut self.reader_id) {
+            match event {
+                InputEvent::ActionPressed(ActionBinding::StoreBookmark(bookmark)) => {
+                    bookmarks.store(*bookmark, *camera.translation());
+                }
+                InputEvent::ActionPressed(ActionBinding::Bookmark(bookmark)) => {
+                    // the key is part of the combinations which store the bookmark
+                    // and toggle a debug layer
+                    let storing = input
+                        .action_is_down(&ActionBinding::StoreBookmark(*bookmark))
+                        .unwrap_or(false);
+                    if !storing && !debug_layer_down(&input) {
+                        if let Some(position) = bookmarks.get(*bookmark) {
+                            bookmarks.target = Some(position);
+                        }
+                    }
+                }
+                InputEvent::ActionPressed(ActionBinding::JumpToAlert) => {
+                    if let Some(alert) = bookmarks.alert {
+                        bookmarks.target = looking_at(camera, alert.x, alert.y, alert.z);
+                    }
+                }
+                InputEvent::ActionPressed(ActionBinding::FollowSelection) => {
+                    follow.active = !follow.active;
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -1,52 +1,55 @@
 use crate::{
-    components::{CameraBorders, CameraControlTag},
-    config::{ArenaConfig, CameraConfig},
+    components::{perspective_camera, CameraBorders, CameraControlTag, OrthographicExtent},
+    config::CameraConfig,
     input::AxisBinding,
     input::MovementBindingTypes,
     minions::WorldBorders,
+    resources::{CameraBookmarks, CameraFollow, Selection, TerrainMap},
 };
 use amethyst::{
     controls::WindowFocus,
     core::{
         geometry::Plane,
-        math::{Point2, Vector2},
+        math::{Point2, Vector2, Vector3},
         Time, Transform,
     },
     derive::SystemDesc,
-    ecs::{Entities, Join, Read, ReadStorage, System, SystemData, WriteStorage},
+    ecs::{Entities, Entity, Join, Read, ReadStorage, System, SystemData, Write, WriteStorage},
     input::InputHandler,
     renderer::camera::{ActiveCamera, Camera},
     shred::ReadExpect,
     window::ScreenDimensions,
 };
 
-#[derive(SystemDesc)]
-pub struct BorderSystem{
-	first_run: bool,
-}
+// units per second while a trigger of a gamepad is held down
+const ZOOM_SPEED: f32 = 6.0;
+// change of the orthographic extent per step of the mouse wheel
+const ORTHOGRAPHIC_ZOOM: f32 = 0.5;
+// fraction of the way to a bookmark the camera covers per second, roughly
+const JUMP_RATE: f32 = 8.0;
+// in units, the jump ends once the camera is this close
+const JUMP_PRECISION: f32 = 0.01;
 
-impl Default for BorderSystem{
-	fn default() -> Self{
-		BorderSystem{
-			// so that the system runs on startup
-			first_run: true
-		}
-	}
+#[derive(Default, SystemDesc)]
+pub struct BorderSystem {
+    // screen size, height and orthographic extent of the camera the borders belong to,
+    // None on startup
+    calculated_for: Option<(f32, f32, f32, f32)>,
 }
 
-// this System calculates the borderes of the camera
-// they have to change every-time the user zooms in/out
+// this System calculates the borders of the camera from the corners of the screen,
+// they change with the zoom and the size of the window
 impl<'s> System<'s> for BorderSystem {
     type SystemData = (
         Entities<'s>,
         ReadStorage<'s, Transform>,
-        ReadStorage<'s, Camera>,
+        WriteStorage<'s, Camera>,
+        ReadStorage<'s, OrthographicExtent>,
         Read<'s, ActiveCamera>,
         Read<'s, WorldBorders>,
-        Read<'s, ArenaConfig>,
+        Read<'s, TerrainMap>,
         ReadExpect<'s, ScreenDimensions>,
         WriteStorage<'s, CameraBorders>,
-        Read<'s, InputHandler<MovementBindingTypes>>,
     );
 
     fn run(
@@ -54,53 +57,101 @@ impl<'s> System<'s> for BorderSystem {
         (
             entities,
             transforms,
-            cameras,
+            mut cameras,
+            extents,
             active_camera,
             world_borders,
-            arena_config,
+            terrain,
             screen_dimensions,
             mut camera_borders,
-            input,
         ): Self::SystemData,
     ) {
-        let (width, height) = { (screen_dimensions.width(), screen_dimensions.height()) };
-        let zoom = input.axis_value(&AxisBinding::Zoom).unwrap_or(0.0);
-        // only recalculate borders when there is zoom-change
-        if zoom != 0.0 || self.first_run {
-			self.first_run = false; // this runs every loop but is only needed once
-            let mut camera_join = (&cameras, &transforms, &mut camera_borders).join();
-            if let Some((camera, camera_transform, mut camera_border)) = active_camera
-                .entity
-                .and_then(|a| camera_join.get(a, &entities))
-                .or_else(|| camera_join.next())
-            {
-                // bot and top borders
-                let ray = camera.screen_ray(
-                    Point2::new(width / 2.0, height),
-                    Vector2::new(width, height),
-                    &camera_transform,
-                );
-                let camera_translation = camera_transform.translation();
-                let d = ray.intersect_plane(&Plane::with_y(0.0)).unwrap();
-                let distance = camera_translation.z - ray.at_distance(d).z + arena_config.tile_size;
-                camera_border.top = world_borders.top + distance;
-                camera_border.bottom = world_borders.bottom + distance;
-
-                // right and left borders
-                let ray = camera.screen_ray(
-                    Point2::new(0.0, height),
-                    Vector2::new(width, height),
-                    &camera_transform,
-                );
-                let d = ray.intersect_plane(&Plane::with_y(0.0)).unwrap();
-                let distance = camera_translation.x - ray.at_distance(d).x;
-                camera_border.left = world_borders.left + distance.abs() - arena_config.tile_size;
-                camera_border.right = world_borders.right - distance.abs() + arena_config.tile_size;
-            }
+        let (width, height) = (screen_dimensions.width(), screen_dimensions.height());
+        let mut camera_join = (
+            &mut cameras,
+            &transforms,
+            (&extents).maybe(),
+            &mut camera_borders,
+        )
+            .join();
+        let (camera, transform, extent, camera_border) = match active_camera
+            .entity
+            .and_then(|a| camera_join.get(a, &entities))
+            .or_else(|| camera_join.next())
+        {
+            Some(joined) => joined,
+            None => return,
+        };
+
+        let state = (
+            width,
+            height,
+            transform.translation().y,
+            extent.map_or(0.0, |extent| extent.0),
+        );
+        if self.calculated_for == Some(state) {
+            return;
+        }
+        // the projection follows the aspect ratio of the window
+        let resized = self
+            .calculated_for
+            .map_or(true, |(old_width, old_height, ..)| {
+                (old_width, old_height) != (width, height)
+            });
+        if resized {
+            let aspect = screen_dimensions.aspect_ratio();
+            *camera = match extent {
+                Some(extent) => extent.camera(aspect),
+                None => perspective_camera(aspect),
+            };
+        }
+        self.calculated_for = Some(state);
+
+        // where the corners of the screen meet the ground, relative to the camera
+        let (ground, _) = terrain.height_range();
+        let translation = transform.translation();
+        let corners: Vec<(f32, f32)> = [(0.0, 0.0), (width, 0.0), (0.0, height), (width, height)]
+            .iter()
+            .filter_map(|&(x, y)| {
+                let ray =
+                    camera.screen_ray(Point2::new(x, y), Vector2::new(width, height), transform);
+                // corners above the horizon show the sky
+                let distance = ray
+                    .intersect_plane(&Plane::with_y(ground))
+                    .filter(|&distance| distance > 0.0)?;
+                let hit = ray.at_distance(distance);
+                Some((hit.x - translation.x, hit.z - translation.z))
+            })
+            .collect();
+        if corners.is_empty() {
+            return;
         }
+        let (mut min_x, mut max_x) = (f32::INFINITY, f32::NEG_INFINITY);
+        let (mut min_z, mut max_z) = (f32::INFINITY, f32::NEG_INFINITY);
+        for &(x, z) in &corners {
+            min_x = min_x.min(x);
+            max_x = max_x.max(x);
+            min_z = min_z.min(z);
+            max_z = max_z.max(z);
+        }
+
+        // the screen shows no void beyond the arena. if it shows more than the arena,
+        // the camera moves between the two views which show one of its edges
+        let (left, right) = span(world_borders.left - min_x, world_borders.right - max_x);
+        let (bottom, top) = span(world_borders.bottom - min_z, world_borders.top - max_z);
+        *camera_border = CameraBorders {
+            right,
+            left,
+            top,
+            bottom,
+        };
     }
 }
 
+fn span(a: f32, b: f32) -> (f32, f32) {
+    (a.min(b), a.max(b))
+}
+
 #[derive(SystemDesc)]
 pub struct CameraSystem;
 
@@ -113,37 +164,113 @@ impl<'s> System<'s> for CameraSystem {
         Read<'s, CameraConfig>,
         Read<'s, Time>,
         ReadStorage<'s, CameraBorders>,
+        Read<'s, Selection>,
+        Write<'s, CameraBookmarks>,
+        Write<'s, CameraFollow>,
+        WriteStorage<'s, Camera>,
+        WriteStorage<'s, OrthographicExtent>,
+        ReadExpect<'s, ScreenDimensions>,
     );
 
     fn run(
         &mut self,
-        (mut transforms, camera_tag, focus, input, config, time, camera_borders): Self::SystemData,
+        (
+            mut transforms,
+            camera_tag,
+            focus,
+            input,
+            config,
+            time,
+            camera_borders,
+            selection,
+            mut bookmarks,
+            mut follow,
+            mut cameras,
+            mut extents,
+            screen_dimensions,
+        ): Self::SystemData,
     ) {
         let focused = focus.is_focused;
-        for (transform, _, camera_borders) in (&mut transforms, &camera_tag, &camera_borders).join()
+        let center = if follow.active {
+            centroid(&selection.entities, &transforms)
+        } else {
+            None
+        };
+        // the followed units are gone
+        if center.is_none() {
+            follow.active = false;
+        }
+        for (transform, _, camera_borders, extent, camera) in (
+            &mut transforms,
+            &camera_tag,
+            &camera_borders,
+            (&mut extents).maybe(),
+            (&mut cameras).maybe(),
+        )
+            .join()
         {
 			// window-focus
             if focused {
                 let time_delta = time.delta_seconds();
-				let zoom = input.axis_value(&AxisBinding::Zoom).unwrap_or(0.0);
+				let zoom_held = input.axis_value(&AxisBinding::ZoomHeld).unwrap_or(0.0);
+				let zoom = input.axis_value(&AxisBinding::Zoom).unwrap_or(0.0)
+					+ zoom_held * ZOOM_SPEED * time_delta;
 				
+				// an orthographic camera stays at its height
+				let (min_height, max_height) = match extent {
+					Some(_) => (OrthographicExtent::HEIGHT, OrthographicExtent::HEIGHT),
+					None => (1.0, 10.0),
+				};
+
 				let z =  transform.translation().z;
 				let height = transform.translation().y;
-				if !( height >= 10.0 && zoom < 0.0) && !(height <= 1.0 && zoom > 0.0) 
+				if let (Some(extent), Some(camera)) = (extent, camera) {
+					// it shows more or less of the arena instead of moving
+					if zoom != 0.0 {
+						extent.0 = (extent.0 - zoom * ORTHOGRAPHIC_ZOOM)
+							.clamp(OrthographicExtent::MIN, OrthographicExtent::MAX);
+						*camera = extent.camera(screen_dimensions.aspect_ratio());
+					}
+				} else if !( height >= 10.0 && zoom < 0.0) && !(height <= 1.0 && zoom > 0.0) 
 				&& !( z >= camera_borders.top && zoom < 0.0) && !(z <= camera_borders.bottom && zoom > 0.0 ) {
                     transform.move_forward(zoom);
                 }
+                let follow_target =
+                    center.and_then(|center| looking_at(transform, center.x, center.y, center.z));
                 let translation = transform.translation_mut();
-                translation.y = translation.y.clamp(1.0, 10.0);
+                translation.y = translation.y.clamp(min_height, max_height);
 
                 let right = input.axis_value(&AxisBinding::Right).unwrap_or(0.0);
                 translation.x += right * config.movement_factor * time_delta;
-                translation.x = translation
-                    .x
-                    .clamp(camera_borders.left, camera_borders.right);
 
                 let forward = input.axis_value(&AxisBinding::Forward).unwrap_or(0.0);
                 translation.z += -forward * config.movement_factor * time_delta;
+
+                // panning breaks off a jump to a bookmark or alert and following the selection
+                if right != 0.0 || forward != 0.0 {
+                    bookmarks.target = None;
+                    follow.active = false;
+                } else if follow_target.is_some() {
+                    bookmarks.target = follow_target;
+                }
+                if let Some(target) = bookmarks.target {
+                    // the borders may keep the camera from reaching the target
+                    let target = Vector3::new(
+                        target.x.clamp(camera_borders.left, camera_borders.right),
+                        target.y.clamp(min_height, max_height),
+                        target.z.clamp(camera_borders.bottom, camera_borders.top),
+                    );
+                    let step = 1.0 - (-JUMP_RATE * time_delta).exp();
+                    *translation += (target - *translation) * step;
+                    if (target - *translation).norm() < JUMP_PRECISION {
+                        *translation = target;
+                        bookmarks.target = None;
+                    }
+                }
+
+                translation.x = translation
+                    .x
+                    .clamp(camera_borders.left, camera_borders.right);
                 translation.z = translation
                     .z
                     .clamp(camera_borders.bottom, camera_borders.top);
@@ -151,3 +278,42 @@ impl<'s> System<'s> for CameraSystem {
         }
     }
 }
+
+// moves the camera so it looks at the point on the ground, the CameraSystem keeps it
+// inside of its borders
+pub fn look_at(transform: &mut Transform, x: f32, y: f32, z: f32) {
+    if let Some(position) = looking_at(transform, x, y, z) {
+        *transform.translation_mut() = position;
+    }
+}
+
+// the position at the same height from which the camera looks at the point
+pub fn looking_at(transform: &Transform, x: f32, y: f32, z: f32) -> Option<Vector3<f32>> {
+    let direction = transform.rotation() * -Vector3::z();
+    if direction.y >= 0.0 {
+        return None;
+    }
+    let translation = transform.translation();
+    let distance = (translation.y - y) / -direction.y;
+    Some(Vector3::new(
+        x - direction.x * distance,
+        translation.y,
+        z - direction.z * distance,
+    ))
+}
+
+// the center of the entities, None if none of them has a position
+pub fn centroid(
+    entities: &[Entity],
+    transforms: &WriteStorage<'_, Transform>,
+) -> Option<Vector3<f32>> {
+    let points: Vec<_> = entities
+        .iter()
+        .filter_map(|entity| transforms.get(*entity))
+        .map(|transform| *transform.translation())
+        .collect();
+    if points.is_empty() {
+        return None;
+    }
+    Some(points.iter().sum::<Vector3<f32>>() / points.len() as f32)
+}
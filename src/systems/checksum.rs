@@ -0,0 +1,191 @@
+use amethyst::{
+    derive::SystemDesc,
+    ecs::{Entities, Join, Read, ReadStorage, System, SystemData, Write},
+};
+use log::debug;
+use std::hash::{Hash, Hasher};
+
+use crate::{
+    components::{GridPosition, Health, MoveOrder, ProductionQueue, SimulationId, Team},
+    resources::{Simulation, Treasury},
+    rng::Rng,
+    simulation::TICKS_PER_SECOND,
+};
+
+#[derive(SystemDesc)]
+pub struct ChecksumSystem;
+
+// this system hashes the state of the simulation at the end of every tick,
+// two clients with different checksums for the same tick are out of sync
+impl<'s> System<'s> for ChecksumSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, SimulationId>,
+        ReadStorage<'s, GridPosition>,
+        ReadStorage<'s, MoveOrder>,
+        ReadStorage<'s, Health>,
+        ReadStorage<'s, ProductionQueue>,
+        ReadStorage<'s, Team>,
+        Read<'s, Treasury>,
+        Read<'s, Rng>,
+        Write<'s, Simulation>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            ids,
+            positions,
+            move_orders,
+            healths,
+            production_queues,
+            teams,
+            treasury,
+            rng,
+            mut simulation,
+        ): Self::SystemData,
+    ) {
+        let mut hasher = Fnv::default();
+        simulation.tick().hash(&mut hasher);
+        // entities are numbered differently on every client, the SimulationIds are not
+        let mut simulated: Vec<_> = (&entities, &ids, &positions, &teams).join().collect();
+        simulated.sort_by_key(|&(_, id, _, _)| id.0);
+        for (entity, id, position, team) in simulated {
+            id.hash(&mut hasher);
+            position.hash(&mut hasher);
+            team.0.hash(&mut hasher);
+            if let Some(order) = move_orders.get(entity) {
+                (order.x, order.z).hash(&mut hasher);
+            }
+            if let Some(health) = healths.get(entity) {
+                health.0.hash(&mut hasher);
+            }
+        }
+        let mut queues: Vec<_> = (&ids, &production_queues).join().collect();
+        queues.sort_by_key(|&(id, _)| id.0);
+        for (id, queue) in queues {
+            id.hash(&mut hasher);
+            for unit_type in &queue.queue {
+                unit_type.hash(&mut hasher);
+            }
+            queue.progress.hash(&mut hasher);
+            queue.rally_point.hash(&mut hasher);
+        }
+        for balance in treasury.balances() {
+            balance.hash(&mut hasher);
+        }
+        rng.hash(&mut hasher);
+
+        let checksum = hasher.finish();
+        if simulation.tick() % TICKS_PER_SECOND as u64 == 0 {
+            debug!("checksum of tick {}: {:016x}", simulation.tick(), checksum);
+        }
+        simulation.finish_tick(checksum);
+    }
+}
+
+// fnv-1a, integers are hashed as little endian so every platform gets the same checksum
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, n: u16) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, n: u32) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        self.write_u64(n as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixed::Fixed;
+    use amethyst::ecs::{Builder, RunNow, World, WorldExt};
+
+    #[test]
+    fn fnv_1a() {
+        let mut hasher = Fnv::default();
+        assert_eq!(hasher.finish(), 0xcbf2_9ce4_8422_2325);
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+        let mut hasher = Fnv::default();
+        hasher.write(b"foobar");
+        assert_eq!(hasher.finish(), 0x8594_4171_f739_67e8);
+    }
+
+    // the entities are created in the given order
+    fn checksum(ids: &[u32]) -> u64 {
+        let mut world = World::new();
+        world.register::<SimulationId>();
+        world.register::<GridPosition>();
+        world.register::<MoveOrder>();
+        world.register::<Health>();
+        world.register::<ProductionQueue>();
+        world.register::<Team>();
+        world.insert(Treasury::new(2, 100));
+        world.insert(Rng::new(7));
+        world.insert(Simulation::default());
+        for &id in ids {
+            let entity = world.create_entity().with(SimulationId(id));
+            let entity = if id == 1 {
+                entity
+                    .with(GridPosition {
+                        x: Fixed::from_int(2),
+                        z: Fixed::from_int(3),
+                    })
+                    .with(Team(0))
+                    .with(Health(50))
+                    .with(MoveOrder { x: 4, z: -1 })
+            } else {
+                entity
+                    .with(GridPosition {
+                        x: Fixed::from_f32(-1.5),
+                        z: Fixed::from_int(0),
+                    })
+                    .with(Team(1))
+            };
+            entity.build();
+        }
+        ChecksumSystem.run_now(&world);
+        let simulation = world.read_resource::<Simulation>();
+        assert_eq!(simulation.tick(), 1);
+        simulation.checksum(0).expect("the tick has no checksum")
+    }
+
+    #[test]
+    fn stable_checksum() {
+        assert_eq!(checksum(&[1, 2]), 0x7368_04e0_3cab_a13d);
+    }
+
+    #[test]
+    fn checksum_ignores_the_entity_order() {
+        assert_eq!(checksum(&[2, 1]), checksum(&[1, 2]));
+    }
+}
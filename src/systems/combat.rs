@@ -0,0 +1,126 @@
+use amethyst::{
+    derive::SystemDesc,
+    ecs::{Entities, Join, Read, ReadStorage, System, SystemData, WriteStorage},
+};
+use log::{error, info};
+
+use crate::{
+    components::{
+        AttackCooldown, Attacker, Building, GridPosition, Health, Minion, SimulationId, Team,
+    },
+    fixed::Fixed,
+    minions::WorldBorders,
+    resources::UnitRegistry,
+    simulation,
+};
+
+#[derive(SystemDesc)]
+pub struct CombatSystem;
+
+// this system lets every unit attack the closest enemy building in its range,
+// a building without health is destroyed. the attackers of a tower-defense map
+// only walk to the goal
+impl<'s> System<'s> for CombatSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, SimulationId>,
+        ReadStorage<'s, Minion>,
+        ReadStorage<'s, Attacker>,
+        ReadStorage<'s, Team>,
+        ReadStorage<'s, GridPosition>,
+        ReadStorage<'s, Building>,
+        WriteStorage<'s, Health>,
+        WriteStorage<'s, AttackCooldown>,
+        Read<'s, UnitRegistry>,
+        Read<'s, WorldBorders>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            ids,
+            minions,
+            attackers,
+            teams,
+            positions,
+            buildings,
+            mut healths,
+            mut cooldowns,
+            unit_registry,
+            world_borders,
+        ): Self::SystemData,
+    ) {
+        let topology = world_borders.topology;
+        // the centers of the tiles of every building
+        let targets: Vec<_> = (&entities, &ids, &buildings, &teams)
+            .join()
+            .map(|(entity, id, building, team)| {
+                let tiles: Vec<_> = (building.x..building.x + building.width)
+                    .flat_map(|x| (building.z..building.z + building.depth).map(move |z| (x, z)))
+                    .map(|tile| GridPosition::center(topology, tile))
+                    .collect();
+                (entity, id.0, team.0, tiles)
+            })
+            .collect();
+        if targets.is_empty() {
+            return;
+        }
+
+        // the unit with the lowest id strikes first
+        let mut units: Vec<_> = (&entities, &ids, &minions, &teams, &positions, !&attackers)
+            .join()
+            .map(|(entity, id, minion, team, position, _)| {
+                (id.0, entity, minion, team.0, *position)
+            })
+            .collect();
+        units.sort_by_key(|&(id, ..)| id);
+
+        for (_, entity, minion, team, position) in units {
+            if let Some(cooldown) = cooldowns.get_mut(entity) {
+                if cooldown.0 > 0 {
+                    cooldown.0 -= 1;
+                    continue;
+                }
+            }
+            let attack = match unit_registry.get(&minion.unit_type) {
+                Some(unit_type) if unit_type.stats.attack.damage > 0 => unit_type.stats.attack,
+                _ => continue,
+            };
+            let range = Fixed::from_f32(attack.range);
+            let target = targets
+                .iter()
+                .filter(|&&(building, _, owner, _)| {
+                    owner != team && healths.get(building).map_or(false, |health| health.0 > 0)
+                })
+                .filter_map(|(building, id, owner, tiles)| {
+                    tiles
+                        .iter()
+                        .map(|tile| Fixed::hypot(tile.x - position.x, tile.z - position.z))
+                        .min()
+                        .map(|distance| (distance, *id, *building, *owner))
+                })
+                .filter(|&(distance, ..)| distance <= range)
+                .min_by_key(|&(distance, id, ..)| (distance, id));
+            let (building, owner) = match target {
+                Some((_, _, building, owner)) => (building, owner),
+                None => continue,
+            };
+
+            if let Err(e) =
+                cooldowns.insert(entity, AttackCooldown(simulation::ticks(attack.cooldown)))
+            {
+                error!("failed to start the cooldown of an attack: {}", e);
+            }
+            if let Some(health) = healths.get_mut(building) {
+                health.0 = health.0.saturating_sub(attack.damage);
+                if health.0 == 0 {
+                    info!("a building of team {} was destroyed", owner);
+                    if let Err(e) = entities.delete(building) {
+                        error!("failed to remove a destroyed building: {}", e);
+                    }
+                }
+            }
+        }
+    }
+}
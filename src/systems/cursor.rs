@@ -0,0 +1,57 @@
+use amethyst::{
+    core::Time,
+    derive::SystemDesc,
+    ecs::{Read, ReadExpect, System, SystemData, Write},
+    input::InputHandler,
+    window::ScreenDimensions,
+};
+
+use crate::{
+    input::{AxisBinding, MovementBindingTypes},
+    resources::VirtualCursor,
+};
+
+// pixels per second with the stick at full tilt
+const CURSOR_SPEED: f32 = 800.0;
+
+#[derive(Default, SystemDesc)]
+pub struct CursorSystem {
+    // to notice when the mouse takes over again
+    mouse_position: Option<(f32, f32)>,
+}
+
+// this system moves the virtual cursor with the right stick of a gamepad,
+// the HoverSystem picks the tile below it instead of the one below the mouse
+impl<'s> System<'s> for CursorSystem {
+    type SystemData = (
+        Read<'s, InputHandler<MovementBindingTypes>>,
+        Read<'s, Time>,
+        ReadExpect<'s, ScreenDimensions>,
+        Write<'s, VirtualCursor>,
+    );
+
+    fn run(&mut self, (input, time, screen_dimensions, mut cursor): Self::SystemData) {
+        let mouse_position = input.mouse_position();
+        if mouse_position != self.mouse_position {
+            self.mouse_position = mouse_position;
+            cursor.0 = None;
+        }
+
+        let right = input.axis_value(&AxisBinding::CursorRight).unwrap_or(0.0);
+        let down = input.axis_value(&AxisBinding::CursorDown).unwrap_or(0.0);
+        if right == 0.0 && down == 0.0 {
+            return;
+        }
+        let (width, height) = (screen_dimensions.width(), screen_dimensions.height());
+        // the cursor starts where the mouse left off
+        let (x, y) = cursor
+            .0
+            .or(mouse_position)
+            .unwrap_or((width / 2.0, height / 2.0));
+        let distance = CURSOR_SPEED * time.delta_seconds();
+        cursor.0 = Some((
+            (x + right * distance).clamp(0.0, width),
+            (y + down * distance).clamp(0.0, height),
+        ));
+    }
+}
@@ -0,0 +1,364 @@
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    core::{
+        math::{Point2, Point3, Vector2, Vector3},
+        SystemDesc, Transform,
+    },
+    derive::SystemDesc,
+    ecs::{
+        Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, SystemData, World, Write,
+        WriteStorage,
+    },
+    input::{InputEvent, InputHandler},
+    renderer::{
+        camera::Camera,
+        debug_drawing::{DebugLines, DebugLinesComponent},
+        palette::Srgba,
+        Hidden,
+    },
+    shrev::{EventChannel, ReaderId},
+    ui::{get_default_font, Anchor, FontAsset, LineMode, UiText, UiTransform},
+    window::ScreenDimensions,
+};
+use log::info;
+use std::f32::consts::PI;
+
+use crate::{
+    components::{
+        Building, CameraBorders, CameraControlTag, GridLines, MoveOrder, Tower, UnitStats,
+    },
+    config::ArenaConfig,
+    input::{ActionBinding, MovementBindingTypes},
+    minions::{grid_lines, WorldBorders},
+    picking::{pick, terrain_height},
+    resources::{DebugLayer, DebugLayers, HoveredTile, TerrainMap, VirtualCursor},
+};
+
+// lines float above the ground so the terrain doesn't hide them
+const LIFT: f32 = 0.01;
+const CIRCLE_SEGMENTS: usize = 32;
+// half of the size of the cross where the hover ray hits, in tiles
+const CROSS_SIZE: f32 = 0.1;
+
+const WORLD_BORDER_COLOR: (f32, f32, f32, f32) = (1.0, 0.2, 0.2, 1.0);
+const CAMERA_BORDER_COLOR: (f32, f32, f32, f32) = (0.2, 0.4, 1.0, 1.0);
+const PATH_COLOR: (f32, f32, f32, f32) = (0.9, 0.9, 0.2, 1.0);
+const RANGE_COLOR: (f32, f32, f32, f32) = (1.0, 0.5, 0.1, 1.0);
+const RAY_COLOR: (f32, f32, f32, f32) = (1.0, 0.2, 1.0, 1.0);
+
+#[derive(Default, Debug)]
+pub struct DebugSystemDesc;
+
+impl<'a, 'b> SystemDesc<'a, 'b, DebugSystem> for DebugSystemDesc {
+    fn build(self, world: &mut World) -> DebugSystem {
+        <DebugSystem as System<'_>>::SystemData::setup(world);
+        let reader_id = world
+            .fetch_mut::<EventChannel<InputEvent<MovementBindingTypes>>>()
+            .register_reader();
+        DebugSystem {
+            reader_id,
+            grid_shown: false,
+        }
+    }
+}
+
+// this system toggles the debug layers and draws them, the grid is kept in the
+// GridLines entity, everything else is drawn anew every frame
+pub struct DebugSystem {
+    reader_id: ReaderId<InputEvent<MovementBindingTypes>>,
+    // whether the GridLines hold the grid
+    grid_shown: bool,
+}
+
+impl<'s> System<'s> for DebugSystem {
+    type SystemData = (
+        Read<'s, EventChannel<InputEvent<MovementBindingTypes>>>,
+        Write<'s, DebugLayers>,
+        // inserted once the arena is initialized
+        Option<Write<'s, DebugLines>>,
+        WriteStorage<'s, DebugLinesComponent>,
+        ReadStorage<'s, GridLines>,
+        ReadStorage<'s, CameraControlTag>,
+        ReadStorage<'s, Camera>,
+        ReadStorage<'s, CameraBorders>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, MoveOrder>,
+        ReadStorage<'s, UnitStats>,
+        ReadStorage<'s, Tower>,
+        ReadStorage<'s, Building>,
+        ReadStorage<'s, Hidden>,
+        Read<'s, TerrainMap>,
+        Read<'s, WorldBorders>,
+        Read<'s, ArenaConfig>,
+        Read<'s, VirtualCursor>,
+        Read<'s, InputHandler<MovementBindingTypes>>,
+        ReadExpect<'s, ScreenDimensions>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            input_events,
+            mut layers,
+            debug_lines,
+            mut debug_lines_components,
+            grid_lines_tags,
+            camera_tags,
+            cameras,
+            camera_borders,
+            transforms,
+            move_orders,
+            unit_stats,
+            towers,
+            buildings,
+            hidden,
+            terrain,
+            world_borders,
+            arena_config,
+            cursor,
+            input,
+            screen_dimensions,
+        ): Self::SystemData,
+    ) {
+        for event in input_events.read(&mut self.reader_id) {
+            if let InputEvent::ActionPressed(ActionBinding::ToggleDebugLayer(layer)) = event {
+                let shown = layers.toggle(*layer);
+                info!(
+                    "debug layer {:?} {}",
+                    layer,
+                    if shown { "shown" } else { "hidden" }
+                );
+            }
+        }
+        let mut debug_lines = match debug_lines {
+            Some(debug_lines) => debug_lines,
+            None => return,
+        };
+        let tile_size = arena_config.tile_size;
+        let ground =
+            |x: f32, z: f32| terrain_height(&terrain, &world_borders, tile_size, x, z) + LIFT;
+
+        if layers.is_shown(DebugLayer::Grid) != self.grid_shown {
+            self.grid_shown = layers.is_shown(DebugLayer::Grid);
+            for (lines, _) in (&mut debug_lines_components, &grid_lines_tags).join() {
+                *lines = if self.grid_shown {
+                    grid_lines(&terrain, &world_borders, tile_size)
+                } else {
+                    DebugLinesComponent::new()
+                };
+            }
+        }
+
+        if layers.is_shown(DebugLayer::Axes) {
+            let origin = Point3::new(0.0, LIFT, 0.0);
+            debug_lines.draw_direction(origin, Vector3::x(), Srgba::new(1.0, 0.0, 0.23, 1.0));
+            debug_lines.draw_direction(origin, Vector3::y(), Srgba::new(0.5, 0.85, 0.1, 1.0));
+            debug_lines.draw_direction(origin, Vector3::z(), Srgba::new(0.2, 0.75, 0.93, 1.0));
+        }
+
+        if layers.is_shown(DebugLayer::Borders) {
+            let (lowest, _) = terrain.height_range();
+            rectangle(
+                &mut debug_lines,
+                (world_borders.left, world_borders.right),
+                (world_borders.bottom, world_borders.top),
+                lowest + LIFT,
+                Srgba::from_components(WORLD_BORDER_COLOR),
+            );
+            // where the camera itself may go, not what it sees
+            for borders in camera_borders.join() {
+                rectangle(
+                    &mut debug_lines,
+                    (borders.left, borders.right),
+                    (borders.bottom, borders.top),
+                    lowest + LIFT,
+                    Srgba::from_components(CAMERA_BORDER_COLOR),
+                );
+            }
+        }
+
+        if layers.is_shown(DebugLayer::Paths) {
+            // the minions walk straight to the tile of their move-order
+            for (move_order, transform, _) in (&move_orders, &transforms, !&hidden).join() {
+                let (x, z) = world_borders.tile_center(move_order.x, move_order.z, tile_size);
+                let start = transform.translation();
+                debug_lines.draw_line(
+                    Point3::new(start.x, ground(start.x, start.z), start.z),
+                    Point3::new(x, ground(x, z), z),
+                    Srgba::from_components(PATH_COLOR),
+                );
+            }
+        }
+
+        if layers.is_shown(DebugLayer::Ranges) {
+            for (stats, transform, _) in (&unit_stats, &transforms, !&hidden).join() {
+                let center = transform.translation();
+                circle(
+                    &mut debug_lines,
+                    (center.x, center.z),
+                    stats.attack.range * tile_size,
+                    &ground,
+                );
+            }
+            if let Some(config) = &arena_config.defense {
+                for (building, _) in (&buildings, &towers).join() {
+                    let (x, z) = building.center(world_borders.topology);
+                    circle(
+                        &mut debug_lines,
+                        (
+                            world_borders.left + x * tile_size,
+                            world_borders.bottom + z * tile_size,
+                        ),
+                        config.tower.range * tile_size,
+                        &ground,
+                    );
+                }
+            }
+        }
+
+        if layers.is_shown(DebugLayer::HoverRay) {
+            let camera = (&camera_tags, &cameras, &transforms).join().next();
+            let mouse_position = cursor.0.or_else(|| input.mouse_position());
+            if let (Some((_, camera, transform)), Some((x, y))) = (camera, mouse_position) {
+                let ray = camera.screen_ray(
+                    Point2::new(x, y),
+                    Vector2::new(screen_dimensions.width(), screen_dimensions.height()),
+                    transform,
+                );
+                if let Some(hit) = pick(&ray, &terrain, &world_borders, tile_size) {
+                    // the ray itself is a point from where it starts, the cross shows its end
+                    let color = Srgba::from_components(RAY_COLOR);
+                    debug_lines.draw_line(ray.origin, hit, color);
+                    let size = CROSS_SIZE * tile_size;
+                    for &offset in &[Vector3::x() * size, Vector3::z() * size] {
+                        debug_lines.draw_line(hit - offset, hit + offset, color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default, SystemDesc)]
+pub struct TileLabelSystem {
+    // the text next to the cursor, created when the layer is first shown
+    label: Option<Entity>,
+}
+
+// this system shows the coordinates and the height of the hovered tile next to the cursor
+impl<'s> System<'s> for TileLabelSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, UiTransform>,
+        WriteStorage<'s, UiText>,
+        ReadExpect<'s, Loader>,
+        Read<'s, AssetStorage<FontAsset>>,
+        Read<'s, DebugLayers>,
+        Read<'s, HoveredTile>,
+        Read<'s, TerrainMap>,
+        Read<'s, VirtualCursor>,
+        Read<'s, InputHandler<MovementBindingTypes>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut ui_transforms,
+            mut ui_texts,
+            loader,
+            fonts,
+            layers,
+            hovered_tile,
+            terrain,
+            cursor,
+            input,
+        ): Self::SystemData,
+    ) {
+        let shown = layers.is_shown(DebugLayer::Coordinates);
+        let label = match self.label {
+            Some(label) => label,
+            None if shown => {
+                let transform = UiTransform::new(
+                    "tile_coordinates".to_string(),
+                    Anchor::TopLeft,
+                    Anchor::TopLeft,
+                    0.0,
+                    0.0,
+                    1.0,
+                    200.0,
+                    20.0,
+                );
+                let text = UiText::new(
+                    get_default_font(&loader, &fonts),
+                    String::new(),
+                    [1.0, 1.0, 1.0, 1.0],
+                    16.0,
+                    LineMode::Single,
+                    Anchor::MiddleLeft,
+                );
+                let label = entities
+                    .build_entity()
+                    .with(transform, &mut ui_transforms)
+                    .with(text, &mut ui_texts)
+                    .build();
+                self.label = Some(label);
+                label
+            }
+            None => return,
+        };
+
+        let text = match (shown, hovered_tile.0) {
+            (true, Some((x, z))) => format!("{} {} height {:.2}", x, z, terrain.height(x, z)),
+            _ => String::new(),
+        };
+        if let Some(ui_text) = ui_texts.get_mut(label) {
+            ui_text.text = text;
+        }
+        // a little to the right of the cursor, y counts down from the top
+        if let (Some(ui_transform), Some((x, y))) = (
+            ui_transforms.get_mut(label),
+            cursor.0.or_else(|| input.mouse_position()),
+        ) {
+            ui_transform.local_x = x + 16.0;
+            ui_transform.local_y = -y;
+        }
+    }
+}
+
+// a rectangle on the plane at the height
+fn rectangle(
+    debug_lines: &mut DebugLines,
+    (left, right): (f32, f32),
+    (bottom, top): (f32, f32),
+    height: f32,
+    color: Srgba,
+) {
+    let corners = [
+        Point3::new(left, height, bottom),
+        Point3::new(right, height, bottom),
+        Point3::new(right, height, top),
+        Point3::new(left, height, top),
+    ];
+    for i in 0..corners.len() {
+        debug_lines.draw_line(corners[i], corners[(i + 1) % corners.len()], color);
+    }
+}
+
+// a circle following the terrain
+fn circle(
+    debug_lines: &mut DebugLines,
+    (x, z): (f32, f32),
+    radius: f32,
+    ground: &impl Fn(f32, f32) -> f32,
+) {
+    let color = Srgba::from_components(RANGE_COLOR);
+    let point = |segment: usize| {
+        let angle = segment as f32 / CIRCLE_SEGMENTS as f32 * 2.0 * PI;
+        let (point_x, point_z) = (x + radius * angle.cos(), z + radius * angle.sin());
+        Point3::new(point_x, ground(point_x, point_z), point_z)
+    };
+    for segment in 0..CIRCLE_SEGMENTS {
+        debug_lines.draw_line(point(segment), point(segment + 1), color);
+    }
+}
@@ -0,0 +1,298 @@
+use amethyst::{
+    derive::SystemDesc,
+    ecs::{Entities, Join, LazyUpdate, Read, ReadStorage, System, SystemData, Write, WriteStorage},
+};
+use log::{error, info, warn};
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    components::{Attacker, Building, GridPosition, Health, MoveOrder, SimulationId, Team, Tower},
+    config::{ArenaConfig, DefenseConfig},
+    fixed::Fixed,
+    grid,
+    minions::WorldBorders,
+    resources::{Defense, MinionAssets, Simulation, TerrainMap, Treasury, UnitRegistry},
+    simulation,
+    systems::production::unit_transform,
+};
+
+#[derive(SystemDesc)]
+pub struct WaveSystem;
+
+// this system spawns the waves of a tower-defense map and walks the attackers to the goal
+impl<'s> System<'s> for WaveSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Building>,
+        ReadStorage<'s, Attacker>,
+        ReadStorage<'s, GridPosition>,
+        WriteStorage<'s, MoveOrder>,
+        Read<'s, ArenaConfig>,
+        Read<'s, WorldBorders>,
+        Read<'s, TerrainMap>,
+        // not inserted on the dedicated server
+        Option<Read<'s, MinionAssets>>,
+        Read<'s, UnitRegistry>,
+        Read<'s, LazyUpdate>,
+        Write<'s, Simulation>,
+        Option<Write<'s, Defense>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            buildings,
+            attackers,
+            positions,
+            mut move_orders,
+            arena_config,
+            world_borders,
+            terrain,
+            minion_assets,
+            unit_registry,
+            lazy,
+            mut simulation,
+            defense,
+        ): Self::SystemData,
+    ) {
+        let (mut defense, config) = match (defense, &arena_config.defense) {
+            (Some(defense), Some(config)) => (defense, config),
+            _ => return,
+        };
+        let topology = world_borders.topology;
+        let tick = simulation.tick();
+
+        // the way to the goal changes with every tower
+        let building_count = buildings.join().count();
+        if defense.flow.is_empty() || building_count != defense.buildings {
+            defense.flow = flow_field(config, &world_borders, &terrain, &blocked_tiles(&buildings));
+            defense.buildings = building_count;
+        }
+
+        if let Some(wave) = config.waves.get(defense.wave) {
+            if tick >= defense.next_spawn && !config.spawns.is_empty() {
+                if defense.spawned == 0 {
+                    info!("wave {} of {}", defense.wave + 1, config.waves.len());
+                }
+                let spawn = config.spawns[defense.spawned as usize % config.spawns.len()];
+                let builder = unit_registry.spawn(
+                    &lazy,
+                    &entities,
+                    &wave.unit_type,
+                    config.attackers,
+                    GridPosition::center(topology, spawn),
+                    minion_assets
+                        .as_ref()
+                        .map(|minion_assets| minion_assets.team_material(config.attackers)),
+                );
+                match builder {
+                    Some(builder) => {
+                        let stats = unit_registry.stats(&wave.unit_type);
+                        let health = config
+                            .difficulty
+                            .health(stats.map_or(1, |stats| stats.health), defense.wave);
+                        let transform =
+                            unit_transform(&world_borders, &terrain, arena_config.tile_size, spawn);
+                        builder
+                            .with(transform)
+                            .with(simulation.new_id())
+                            .with(Attacker {
+                                bounty: wave.bounty,
+                            })
+                            // replaces the health of the unit type
+                            .with(Health(health))
+                            .build();
+                    }
+                    None => warn!("the wave has the unknown unit {}", wave.unit_type),
+                }
+
+                defense.spawned += 1;
+                if defense.spawned >= config.difficulty.wave_count(wave.count) {
+                    defense.wave += 1;
+                    defense.spawned = 0;
+                    defense.next_spawn = match config.waves.get(defense.wave) {
+                        Some(next) => tick + u64::from(simulation::ticks(next.delay)),
+                        // the last attacker exists once the tick is over
+                        None => tick,
+                    };
+                } else {
+                    defense.next_spawn = tick + u64::from(simulation::ticks(wave.interval));
+                }
+            }
+        }
+
+        let mut arrived = Vec::new();
+        for (entity, _, position) in (&entities, &attackers, &positions).join() {
+            if move_orders.contains(entity) {
+                continue;
+            }
+            let tile = position.tile(topology);
+            if tile == config.goal {
+                arrived.push(entity);
+            } else if let Some(&(x, z)) = defense.flow.get(&tile) {
+                // the unit is still alive, the storage can't fail
+                let _ = move_orders.insert(entity, MoveOrder { x, z });
+            }
+        }
+        for entity in arrived {
+            if let Err(e) = entities.delete(entity) {
+                error!("failed to remove an attacker at the goal: {}", e);
+            }
+            defense.lives = defense.lives.saturating_sub(1);
+            info!("an attacker reached the goal, {} lives left", defense.lives);
+        }
+    }
+}
+
+#[derive(SystemDesc)]
+pub struct TowerSystem;
+
+// this system lets every tower shoot at the closest attacker in its range
+impl<'s> System<'s> for TowerSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, SimulationId>,
+        ReadStorage<'s, Building>,
+        WriteStorage<'s, Tower>,
+        ReadStorage<'s, Team>,
+        ReadStorage<'s, Attacker>,
+        ReadStorage<'s, GridPosition>,
+        WriteStorage<'s, Health>,
+        Read<'s, ArenaConfig>,
+        Read<'s, WorldBorders>,
+        Write<'s, Treasury>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            ids,
+            buildings,
+            mut towers,
+            teams,
+            attackers,
+            positions,
+            mut healths,
+            arena_config,
+            world_borders,
+            mut treasury,
+        ): Self::SystemData,
+    ) {
+        let config = match &arena_config.defense {
+            Some(config) => config,
+            None => return,
+        };
+        let range = Fixed::from_f32(config.tower.range);
+        let cooldown = simulation::ticks(config.tower.cooldown);
+
+        let targets: Vec<_> = (&entities, &ids, &attackers, &teams, &positions)
+            .join()
+            .map(|(entity, id, attacker, team, position)| {
+                (entity, id.0, attacker.bounty, team.0, *position)
+            })
+            .collect();
+        // a unit shot by two towers in the same tick pays the tower with the lower id
+        let mut shooters: Vec<_> = (&ids, &buildings, &mut towers, &teams)
+            .join()
+            .map(|(id, building, tower, team)| (id.0, building, tower, team.0))
+            .collect();
+        shooters.sort_by_key(|&(id, ..)| id);
+
+        for (_, building, tower, team) in shooters {
+            if tower.cooldown > 0 {
+                tower.cooldown -= 1;
+                continue;
+            }
+            let (x, z) = building.center(world_borders.topology);
+            let (x, z) = (Fixed::from_f32(x), Fixed::from_f32(z));
+            let target = targets
+                .iter()
+                .filter(|&&(entity, _, _, owner, _)| {
+                    owner != team && healths.get(entity).map_or(false, |health| health.0 > 0)
+                })
+                .map(|&(entity, id, bounty, _, position)| {
+                    (
+                        Fixed::hypot(position.x - x, position.z - z),
+                        id,
+                        entity,
+                        bounty,
+                    )
+                })
+                .filter(|&(distance, ..)| distance <= range)
+                .min_by_key(|&(distance, id, ..)| (distance, id));
+
+            let (entity, bounty) = match target {
+                Some((_, _, entity, bounty)) => (entity, bounty),
+                None => continue,
+            };
+            tower.cooldown = cooldown;
+            if let Some(health) = healths.get_mut(entity) {
+                health.0 = health.0.saturating_sub(config.tower.damage);
+                if health.0 == 0 {
+                    if let Err(e) = entities.delete(entity) {
+                        error!("failed to remove a killed attacker: {}", e);
+                    }
+                    treasury.earn(team, bounty);
+                }
+            }
+        }
+    }
+}
+
+// true if a tower may stand on the tile, the attackers need a way from every spawn point,
+// `built` are the towers which don't exist yet
+pub fn can_build_tower(
+    config: &DefenseConfig,
+    world_borders: &WorldBorders,
+    terrain: &TerrainMap,
+    buildings: &ReadStorage<'_, Building>,
+    occupied: &HashSet<(i16, i16)>,
+    built: &HashSet<(i16, i16)>,
+    tile: (i16, i16),
+) -> bool {
+    let (x, z) = tile;
+    if !world_borders.contains_tile(x, z)
+        || !terrain
+            .get(x, z)
+            .map_or(false, |terrain| terrain.is_passable())
+        || occupied.contains(&tile)
+        || tile == config.goal
+        || config.spawns.contains(&tile)
+    {
+        return false;
+    }
+    let mut blocked = blocked_tiles(buildings);
+    blocked.extend(built);
+    if !blocked.insert(tile) {
+        return false;
+    }
+    let flow = flow_field(config, world_borders, terrain, &blocked);
+    config.spawns.iter().all(|spawn| flow.contains_key(spawn))
+}
+
+fn blocked_tiles(buildings: &ReadStorage<'_, Building>) -> HashSet<(i16, i16)> {
+    buildings
+        .join()
+        .flat_map(|building| {
+            (building.x..building.x + building.width)
+                .flat_map(move |x| (building.z..building.z + building.depth).map(move |z| (x, z)))
+        })
+        .collect()
+}
+
+fn flow_field(
+    config: &DefenseConfig,
+    world_borders: &WorldBorders,
+    terrain: &TerrainMap,
+    blocked: &HashSet<(i16, i16)>,
+) -> HashMap<(i16, i16), (i16, i16)> {
+    grid::flow_field(world_borders.topology, config.goal, |x, z| {
+        world_borders.contains_tile(x, z)
+            && terrain
+                .get(x, z)
+                .map_or(false, |terrain| terrain.is_passable())
+            && !blocked.contains(&(x, z))
+    })
+}
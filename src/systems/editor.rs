@@ -0,0 +1,392 @@
+use amethyst::{
+    assets::{AssetLoaderSystemData, Handle},
+    config::Config,
+    core::SystemDesc,
+    ecs::{
+        Entities, Join, LazyUpdate, Read, ReadStorage, System, SystemData, World, Write,
+        WriteExpect, WriteStorage,
+    },
+    input::{InputEvent, InputHandler},
+    renderer::{debug_drawing::DebugLinesComponent, Material, Mesh},
+    shrev::{EventChannel, ReaderId},
+};
+use log::{error, info};
+use std::collections::HashSet;
+
+use crate::{
+    components::{Building, GoldMine, GridLines, PieceInfo, Team, Terrain},
+    config::{ArenaConfig, BuildingConfig, GoldMineConfig, HeightConfig, TerrainConfig},
+    grid,
+    input::{debug_layer_down, ActionBinding, MovementBindingTypes},
+    minions::{self, WorldBorders},
+    resources::{
+        DebugLayer, DebugLayers, HoveredTile, MapEditor, MapSnapshot, MinionAssets, TerrainAssets,
+        TerrainMap, Tool,
+    },
+};
+
+// height a brush adds or removes per stroke
+const HEIGHT_STEP: f32 = 0.25;
+const GOLD_MINE_AMOUNT: u32 = 1500;
+// width and depth of a spawn point in tiles
+const SPAWN_SIZE: i16 = 2;
+
+#[derive(Default, Debug)]
+pub struct EditorSystemDesc;
+
+impl<'a, 'b> SystemDesc<'a, 'b, EditorSystem> for EditorSystemDesc {
+    fn build(self, world: &mut World) -> EditorSystem {
+        <EditorSystem as System<'_>>::SystemData::setup(world);
+        let reader_id = world
+            .fetch_mut::<EventChannel<InputEvent<MovementBindingTypes>>>()
+            .register_reader();
+        EditorSystem {
+            reader_id,
+            stroke: HashSet::new(),
+        }
+    }
+}
+
+// this system applies the tool of the map editor to the hovered tiles and keeps
+// the tiles, grid-lines, gold mines and spawn points in sync with the edited map
+pub struct EditorSystem {
+    reader_id: ReaderId<InputEvent<MovementBindingTypes>>,
+    // tiles the current stroke of a brush has already changed
+    stroke: HashSet<(i16, i16)>,
+}
+
+impl<'s> System<'s> for EditorSystem {
+    type SystemData = (
+        Entities<'s>,
+        Read<'s, EventChannel<InputEvent<MovementBindingTypes>>>,
+        Read<'s, InputHandler<MovementBindingTypes>>,
+        Read<'s, HoveredTile>,
+        WriteExpect<'s, MapEditor>,
+        Write<'s, TerrainMap>,
+        Write<'s, ArenaConfig>,
+        Read<'s, WorldBorders>,
+        Read<'s, TerrainAssets>,
+        // inserted once the arena is initialized
+        Option<Read<'s, MinionAssets>>,
+        AssetLoaderSystemData<'s, Mesh>,
+        WriteStorage<'s, PieceInfo>,
+        WriteStorage<'s, Handle<Material>>,
+        WriteStorage<'s, Handle<Mesh>>,
+        WriteStorage<'s, DebugLinesComponent>,
+        ReadStorage<'s, GridLines>,
+        Read<'s, DebugLayers>,
+        ReadStorage<'s, Building>,
+        ReadStorage<'s, GoldMine>,
+        Read<'s, LazyUpdate>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            input_events,
+            input,
+            hovered_tile,
+            mut editor,
+            mut terrain,
+            mut arena_config,
+            world_borders,
+            terrain_assets,
+            minion_assets,
+            mesh_loader,
+            mut piece_infos,
+            mut material_handles,
+            mut meshes,
+            mut debug_lines,
+            grid_lines,
+            debug_layers,
+            buildings,
+            gold_mines,
+            lazy,
+        ): Self::SystemData,
+    ) {
+        let actions: Vec<ActionBinding> = input_events
+            .read(&mut self.reader_id)
+            .filter_map(|event| match event {
+                InputEvent::ActionPressed(action) => Some(action.clone()),
+                _ => None,
+            })
+            .collect();
+        let minion_assets = match minion_assets {
+            Some(minion_assets) => minion_assets,
+            None => return,
+        };
+        let tile_size = arena_config.tile_size;
+        let mut changed_tiles = HashSet::new();
+        let mut objects_changed = false;
+
+        for action in actions {
+            match action {
+                // shift and the F-key toggle a debug layer
+                ActionBinding::Tool(_) if debug_layer_down(&input) => {}
+                ActionBinding::Tool(slot) => {
+                    if let Some(&tool) = Tool::ALL.get(slot as usize) {
+                        editor.tool = tool;
+                        info!("editor tool: {:?}", tool);
+                    }
+                }
+                ActionBinding::BrushLarger => editor.resize_brush(1),
+                ActionBinding::BrushSmaller => editor.resize_brush(-1),
+                ActionBinding::Undo | ActionBinding::Redo => {
+                    let current = snapshot(&terrain, &arena_config);
+                    let restored = if action == ActionBinding::Undo {
+                        editor.undo(current)
+                    } else {
+                        editor.redo(current)
+                    };
+                    if let Some(restored) = restored {
+                        *terrain = restored.terrain;
+                        arena_config.gold_mines = restored.gold_mines;
+                        arena_config.buildings = restored.buildings;
+                        changed_tiles.extend(all_tiles(&terrain));
+                        objects_changed = true;
+                    }
+                }
+                ActionBinding::Save => {
+                    match map_config(&terrain, &arena_config).write(&editor.path) {
+                        Ok(()) => info!("saved the map to {:?}", editor.path),
+                        Err(e) => error!("failed to save the map to {:?}: {}", editor.path, e),
+                    }
+                }
+                ActionBinding::Select => {
+                    self.stroke.clear();
+                    let tile = match hovered_tile.0 {
+                        Some(tile) if !editor.tool.is_brush() => tile,
+                        _ => continue,
+                    };
+                    editor.record(snapshot(&terrain, &arena_config));
+                    if editor.tool == Tool::GoldMine {
+                        toggle_gold_mine(&mut arena_config, tile);
+                    } else {
+                        toggle_spawn(&mut arena_config, tile);
+                    }
+                    objects_changed = true;
+                }
+                _ => {}
+            }
+        }
+
+        // brushes paint as long as the button is held
+        let painting = editor.tool.is_brush()
+            && input
+                .action_is_down(&ActionBinding::Select)
+                .unwrap_or(false);
+        if let Some(center) = hovered_tile.0.filter(|_| painting) {
+            let radius = (editor.brush_size - 1) as f32;
+            for (x, z) in grid::tiles_in_radius(terrain.topology(), center, radius) {
+                if !terrain.contains(x, z) || self.stroke.contains(&(x, z)) {
+                    continue;
+                }
+                if self.stroke.is_empty() {
+                    editor.record(snapshot(&terrain, &arena_config));
+                }
+                self.stroke.insert((x, z));
+                match editor.tool {
+                    Tool::Paint(kind) => terrain.set(x, z, kind),
+                    Tool::Raise => {
+                        let height = terrain.height(x, z) + HEIGHT_STEP;
+                        terrain.set_height(x, z, height);
+                        objects_changed = true;
+                    }
+                    Tool::Lower => {
+                        let height = (terrain.height(x, z) - HEIGHT_STEP).max(0.0);
+                        terrain.set_height(x, z, height);
+                        objects_changed = true;
+                    }
+                    Tool::GoldMine | Tool::Spawn => {}
+                }
+                changed_tiles.insert((x, z));
+            }
+        }
+
+        if !changed_tiles.is_empty() {
+            // the corners of the neighbours move with the height of a tile
+            let refreshed: HashSet<(i16, i16)> = changed_tiles
+                .iter()
+                .flat_map(|&(x, z)| {
+                    (x - 1..=x + 1).flat_map(move |x| (z - 1..=z + 1).map(move |z| (x, z)))
+                })
+                .collect();
+            for (entity, piece_info) in (&entities, &mut piece_infos).join() {
+                let tile = (piece_info.x, piece_info.z);
+                if !refreshed.contains(&tile) {
+                    continue;
+                }
+                let kind = terrain.get(tile.0, tile.1).unwrap_or_default();
+                if let Some(materials) = terrain_assets.materials.get(&kind) {
+                    piece_info.materials = materials.clone();
+                }
+                let material = if hovered_tile.0 == Some(tile) {
+                    piece_info.materials.hover.clone()
+                } else {
+                    piece_info.material()
+                };
+                material_handles
+                    .insert(entity, material)
+                    .expect("failed to change the material of a tile");
+                let mesh = mesh_loader
+                    .load_from_data(minions::tile_mesh(&terrain, tile.0, tile.1, tile_size), ());
+                meshes
+                    .insert(entity, mesh)
+                    .expect("failed to change the mesh of a tile");
+            }
+            if debug_layers.is_shown(DebugLayer::Grid) {
+                for (lines, _) in (&mut debug_lines, &grid_lines).join() {
+                    *lines = minions::grid_lines(&terrain, &world_borders, tile_size);
+                }
+            }
+        }
+
+        // gold mines and spawn points are recreated from the map
+        if objects_changed {
+            for (entity, _) in (&entities, &buildings).join() {
+                entities
+                    .delete(entity)
+                    .expect("failed to delete a spawn point");
+            }
+            for (entity, _) in (&entities, &gold_mines).join() {
+                entities
+                    .delete(entity)
+                    .expect("failed to delete a gold mine");
+            }
+            for config in arena_config.gold_mines.iter() {
+                lazy.create_entity(&entities)
+                    .with(minions::gold_mine_transform(
+                        config,
+                        &world_borders,
+                        &terrain,
+                        tile_size,
+                    ))
+                    .with(minion_assets.mesh.clone())
+                    .with(minion_assets.gold_material.clone())
+                    .with(GoldMine)
+                    .build();
+            }
+            for config in arena_config.buildings.iter() {
+                let building = Building::new(config.x, config.z, config.width, config.depth);
+                lazy.create_entity(&entities)
+                    .with(minions::building_transform(
+                        &building,
+                        &world_borders,
+                        &terrain,
+                        tile_size,
+                    ))
+                    .with(minion_assets.mesh.clone())
+                    .with(minion_assets.team_material(config.team))
+                    .with(building)
+                    .with(Team(config.team))
+                    .build();
+            }
+        }
+    }
+}
+
+fn snapshot(terrain: &TerrainMap, arena_config: &ArenaConfig) -> MapSnapshot {
+    MapSnapshot {
+        terrain: terrain.clone(),
+        gold_mines: arena_config.gold_mines.clone(),
+        buildings: arena_config.buildings.clone(),
+    }
+}
+
+fn all_tiles(terrain: &TerrainMap) -> impl Iterator<Item = (i16, i16)> {
+    let depth = terrain.depth();
+    (0..terrain.width()).flat_map(move |x| (0..depth).map(move |z| (x, z)))
+}
+
+// removes the gold mine on the tile or places a new one
+fn toggle_gold_mine(arena_config: &mut ArenaConfig, (x, z): (i16, i16)) {
+    let count = arena_config.gold_mines.len();
+    arena_config
+        .gold_mines
+        .retain(|mine| (mine.x, mine.z) != (x, z));
+    if arena_config.gold_mines.len() == count {
+        arena_config.gold_mines.push(GoldMineConfig {
+            x,
+            z,
+            amount: GOLD_MINE_AMOUNT,
+        });
+    }
+}
+
+// removes the spawn point on the tile or places one for the first team without one
+fn toggle_spawn(arena_config: &mut ArenaConfig, (x, z): (i16, i16)) {
+    let footprint =
+        |config: &BuildingConfig| Building::new(config.x, config.z, config.width, config.depth);
+    let count = arena_config.buildings.len();
+    arena_config
+        .buildings
+        .retain(|config| !footprint(config).contains(x, z));
+    if arena_config.buildings.len() != count {
+        return;
+    }
+
+    let fits = x + SPAWN_SIZE <= arena_config.width && z + SPAWN_SIZE <= arena_config.depth;
+    let overlaps = arena_config.buildings.iter().any(|config| {
+        let other = footprint(config);
+        (x..x + SPAWN_SIZE)
+            .flat_map(|x| (z..z + SPAWN_SIZE).map(move |z| (x, z)))
+            .any(|(x, z)| other.contains(x, z))
+    });
+    if !fits || overlaps {
+        return;
+    }
+    let team = (0..=u8::MAX)
+        .find(|&team| {
+            arena_config
+                .buildings
+                .iter()
+                .all(|config| config.team != team)
+        })
+        .unwrap_or_default();
+    arena_config.buildings.push(BuildingConfig {
+        x,
+        z,
+        width: SPAWN_SIZE,
+        depth: SPAWN_SIZE,
+        team,
+        produces: Vec::new(),
+        health: None,
+    });
+}
+
+// the map as it is stored in map-files
+fn map_config(terrain: &TerrainMap, arena_config: &ArenaConfig) -> ArenaConfig {
+    let tiles: Vec<(i16, i16)> = all_tiles(terrain).collect();
+    ArenaConfig {
+        depth: arena_config.depth,
+        width: arena_config.width,
+        tile_size: arena_config.tile_size,
+        topology: arena_config.topology,
+        buildings: arena_config.buildings.clone(),
+        terrain: tiles
+            .iter()
+            .filter_map(|&(x, z)| {
+                terrain
+                    .get(x, z)
+                    .map(|terrain| TerrainConfig { x, z, terrain })
+            })
+            .filter(|tile| tile.terrain != Terrain::default())
+            .collect(),
+        // the heights of the heightmap are stored per tile
+        heightmap: None,
+        heights: tiles
+            .iter()
+            .map(|&(x, z)| HeightConfig {
+                x,
+                z,
+                height: terrain.height(x, z),
+            })
+            .filter(|tile| tile.height != 0.0)
+            .collect(),
+        gold_mines: arena_config.gold_mines.clone(),
+        scripts: arena_config.scripts.clone(),
+        victory: arena_config.victory.clone(),
+        defense: arena_config.defense.clone(),
+    }
+}
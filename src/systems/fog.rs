@@ -0,0 +1,167 @@
+use amethyst::{
+    assets::Handle,
+    core::Transform,
+    derive::SystemDesc,
+    ecs::{Entities, Entity, Join, Read, ReadStorage, System, SystemData, Write, WriteStorage},
+    renderer::{Hidden, Material},
+};
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    components::{Building, Minion, PieceInfo, Sight, Team},
+    config::ArenaConfig,
+    grid,
+    minions::WorldBorders,
+    resources::{FogOfWar, HoveredTile, LocalPlayer, TerrainMap},
+};
+
+#[derive(Default, SystemDesc)]
+pub struct FogSystem {
+    // what every unit and building saw, recalculated when it moves to another tile
+    sightings: HashMap<Entity, Sighting>,
+    // enemy buildings the local player has seen, they stay where they were last seen
+    seen_buildings: HashSet<Entity>,
+}
+
+struct Sighting {
+    team: u8,
+    origin: (i16, i16),
+    sight: f32,
+    tiles: Vec<(i16, i16)>,
+}
+
+// this system calculates what every team can see, darkens the tiles the local player
+// can't see and hides the enemy minions on them and the enemy buildings never seen.
+// the fog only changes when a unit enters another tile, so large arenas stay fast
+impl<'s> System<'s> for FogSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Team>,
+        ReadStorage<'s, Sight>,
+        ReadStorage<'s, Minion>,
+        ReadStorage<'s, Building>,
+        ReadStorage<'s, Transform>,
+        WriteStorage<'s, PieceInfo>,
+        WriteStorage<'s, Handle<Material>>,
+        WriteStorage<'s, Hidden>,
+        Write<'s, FogOfWar>,
+        Read<'s, TerrainMap>,
+        Read<'s, LocalPlayer>,
+        Read<'s, HoveredTile>,
+        Read<'s, WorldBorders>,
+        Read<'s, ArenaConfig>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            teams,
+            sights,
+            minions,
+            buildings,
+            transforms,
+            mut piece_infos,
+            mut material_handles,
+            mut hidden,
+            mut fog,
+            terrain,
+            local_player,
+            hovered_tile,
+            world_borders,
+            arena_config,
+        ): Self::SystemData,
+    ) {
+        let tile_size = arena_config.tile_size;
+        let tile_of = |transform: &Transform| {
+            let translation = transform.translation();
+            world_borders.tile_at(translation.x, translation.z, tile_size)
+        };
+
+        let mut changed = fog.take_changed();
+        let mut sighted = HashSet::new();
+        for (entity, team, sight, transform) in (&entities, &teams, &sights, &transforms).join() {
+            let origin = match tile_of(transform) {
+                Some(origin) => origin,
+                None => continue,
+            };
+            sighted.insert(entity);
+            if let Some(sighting) = self.sightings.get(&entity) {
+                if (sighting.team, sighting.origin, sighting.sight) == (team.0, origin, sight.0) {
+                    continue;
+                }
+            }
+            let topology = terrain.topology();
+            let tiles = grid::tiles_in_radius(topology, origin, sight.0)
+                .filter(|&tile| {
+                    grid::line_of_sight(topology, origin, tile, |x, z| terrain.blocks_sight(x, z))
+                })
+                .collect();
+            let sighting = Sighting {
+                team: team.0,
+                origin,
+                sight: sight.0,
+                tiles,
+            };
+            self.sightings.insert(entity, sighting);
+            changed = true;
+        }
+        let sightings = self.sightings.len();
+        self.sightings.retain(|entity, _| sighted.contains(entity));
+        changed |= self.sightings.len() != sightings;
+
+        if changed {
+            for team in 0..fog.teams() {
+                fog.fade(team);
+            }
+            for sighting in self.sightings.values() {
+                for &(x, z) in &sighting.tiles {
+                    fog.reveal(sighting.team, x, z);
+                }
+            }
+            // the hovered tile gets its material back from the HoverSystem
+            for (entity, piece_info) in (&entities, &mut piece_infos).join() {
+                let visibility = fog.visibility(local_player.team, piece_info.x, piece_info.z);
+                if piece_info.visibility != visibility {
+                    piece_info.visibility = visibility;
+                    if hovered_tile.0 != Some((piece_info.x, piece_info.z)) {
+                        material_handles
+                            .insert(entity, piece_info.material())
+                            .expect("failed to change the material of a tile");
+                    }
+                }
+            }
+        }
+
+        for (entity, team, transform, _) in (&entities, &teams, &transforms, &minions).join() {
+            let visible = team.0 == local_player.team
+                || tile_of(transform)
+                    .map_or(false, |(x, z)| fog.is_visible(local_player.team, x, z));
+            if visible {
+                hidden.remove(entity);
+            } else if !hidden.contains(entity) {
+                hidden
+                    .insert(entity, Hidden)
+                    .expect("failed to hide a minion");
+            }
+        }
+
+        self.seen_buildings
+            .retain(|entity| entities.is_alive(*entity));
+        for (entity, team, building) in (&entities, &teams, &buildings).join() {
+            let in_sight = (building.x..building.x + building.width)
+                .flat_map(|x| (building.z..building.z + building.depth).map(move |z| (x, z)))
+                .any(|(x, z)| fog.is_visible(local_player.team, x, z));
+            if in_sight {
+                self.seen_buildings.insert(entity);
+            }
+            if team.0 == local_player.team || self.seen_buildings.contains(&entity) {
+                hidden.remove(entity);
+            } else if !hidden.contains(entity) {
+                hidden
+                    .insert(entity, Hidden)
+                    .expect("failed to hide a building");
+            }
+        }
+    }
+}
@@ -3,9 +3,8 @@ use amethyst::{
 	core::{
 		math::{Point2, Vector2},
 		Transform,
-		geometry::Plane
 	}, 
-	ecs::{Entity, Entities, Join, ReadStorage, System, SystemData, Read, WriteStorage, ReadExpect}, 
+	ecs::{Entity, Entities, Join, ReadStorage, System, SystemData, Read, Write, WriteStorage, ReadExpect}, 
 	input::InputHandler, 
 	derive::SystemDesc,
 	renderer::{ActiveCamera, Camera, Material}, 
@@ -31,6 +30,7 @@ impl Default for HoverSystemDesc{
 
 use crate::{
     components::PieceInfo, config::ArenaConfig, input::MovementBindingTypes, minions::WorldBorders,
+    picking, resources::{HoveredTile, TerrainMap, VirtualCursor},
 };
 
 // this system lightens the color of hovered planes
@@ -45,7 +45,10 @@ impl<'s> System<'s> for HoverSystem {
         ReadExpect<'s, ScreenDimensions>,
         Read<'s, ArenaConfig>,
         Read<'s, WorldBorders>,
+        Read<'s, TerrainMap>,
+        Read<'s, VirtualCursor>,
 		WriteStorage<'s, Handle<Material>>,
+        Write<'s, HoveredTile>,
     );
 
     fn run(
@@ -60,10 +63,14 @@ impl<'s> System<'s> for HoverSystem {
             screen_dimensions,
             arena_config,
             world_borders,
+            terrain,
+            cursor,
 			mut material_handles,
+            mut hovered_tile,
         ): Self::SystemData,
     ) {
-        if let Some(mouse_position) = input.mouse_position() {
+        // the cursor of a gamepad replaces the mouse while it moves
+        if let Some(mouse_position) = cursor.0.or_else(|| input.mouse_position()) {
             // Get the active camera if it is spawned and ready
             let mut camera_join = (&cameras, &transforms).join();
             if let Some((camera, camera_transform)) = active_camera
@@ -76,45 +83,40 @@ impl<'s> System<'s> for HoverSystem {
                     Vector2::new(screen_dimensions.width(), screen_dimensions.height()),
                     camera_transform,
 							);
-                let distance = ray.intersect_plane(&Plane::with_y(0.0)).unwrap();
-                let position = ray.at_distance(distance);
+                // off the terrain is like outside of the borders
+                let tile = picking::pick(&ray, &terrain, &world_borders, arena_config.tile_size)
+                    .and_then(|position| {
+                        world_borders.tile_at(position.x, position.z, arena_config.tile_size)
+                    });
+
+                if let Some((index_x, index_z)) = tile {
+                    hovered_tile.0 = Some((index_x, index_z));
 
-                // if mouse-coords in world-bords
-                if world_borders.left < position.x
-                    && position.x < world_borders.right
-                    && world_borders.bottom < position.z
-                    && position.z < world_borders.top
-                {
-					// get coords of plane
-                    let index_x =
-                        ((position.x + world_borders.left.abs()) / arena_config.tile_size).floor();
-                    let index_z = ((position.z + world_borders.bottom.abs())
-                        / arena_config.tile_size)
-						.floor();
-						
 					// change plane-albedo to a lighter gray
                     for (entity, piece_info) in (&entities, &piece_infos).join() {
-                        if piece_info.x == index_x as i16 && piece_info.z == index_z as i16 {
+                        if piece_info.x == index_x && piece_info.z == index_z {
                             if self.current_hover != Some(entity) {
 
 								if let Some(entity2) = self.current_hover {
+									let previous = piece_infos.get(entity2).expect("Why is there no plane-info to this plane?");
 									material_handles.remove(entity2);
-                                	material_handles.insert(entity2, piece_info.basic_material.clone()).unwrap();
+                                	material_handles.insert(entity2, previous.material()).unwrap();
 								}
 
 								self.current_hover = Some(entity);
                                 material_handles.remove(entity);
-                                material_handles.insert(entity, piece_info.hover_material.clone()).unwrap();
+                                material_handles.insert(entity, piece_info.materials.hover.clone()).unwrap();
 							}
 							break
 						} 
 					}
 				}
 				else {
+                    hovered_tile.0 = None;
 					if let Some(entity2) = self.current_hover.take() {
 						let piece_info = piece_infos.get(entity2).expect("Why is there no plane-info to this plane?");
 						material_handles.remove(entity2);
-						material_handles.insert(entity2, piece_info.basic_material.clone()).unwrap();
+						material_handles.insert(entity2, piece_info.material()).unwrap();
 					}
 				}
             }
@@ -0,0 +1,136 @@
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    ecs::{Entities, Entity, Read, ReadExpect, System, SystemData, WriteStorage},
+    ui::{get_default_font, Anchor, FontAsset, LineMode, UiText, UiTransform},
+};
+
+use crate::{
+    config::ArenaConfig,
+    resources::{Defense, LocalPlayer, Simulation, Treasury},
+    simulation::TICKS_PER_SECOND,
+};
+
+const FONT_SIZE: f32 = 20.0;
+// in pixels, from the top left corner of the window
+const MARGIN: f32 = 20.0;
+const ROW_HEIGHT: f32 = 26.0;
+const WIDTH: f32 = 300.0;
+
+const TEXT_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+// the lives for a while after an attacker reached the goal
+const WARNING_TICKS: u64 = 3 * TICKS_PER_SECOND as u64;
+const WARNING_COLOR: [f32; 4] = [0.9, 0.3, 0.2, 1.0];
+// the countdown before a wave
+const COUNTDOWN_COLOR: [f32; 4] = [0.9, 0.8, 0.1, 1.0];
+
+// shows the wave, its countdown, the lives and the gold of a tower-defense map
+// in the top left corner of the window
+#[derive(Default)]
+pub struct HudSystem {
+    // the wave, lives and gold lines, created with the first frame of a defense map
+    rows: Option<[Entity; 3]>,
+    // lives of the last frame and the tick they last dropped
+    lives: Option<(u32, Option<u64>)>,
+}
+
+impl<'s> System<'s> for HudSystem {
+    type SystemData = (
+        Read<'s, ArenaConfig>,
+        Read<'s, Simulation>,
+        Read<'s, Treasury>,
+        Read<'s, LocalPlayer>,
+        Option<Read<'s, Defense>>,
+        Entities<'s>,
+        WriteStorage<'s, UiTransform>,
+        WriteStorage<'s, UiText>,
+        ReadExpect<'s, Loader>,
+        Read<'s, AssetStorage<FontAsset>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            arena_config,
+            simulation,
+            treasury,
+            local_player,
+            defense,
+            entities,
+            mut ui_transforms,
+            mut ui_texts,
+            loader,
+            fonts,
+        ): Self::SystemData,
+    ) {
+        let (config, defense) = match (&arena_config.defense, defense) {
+            (Some(config), Some(defense)) => (config, defense),
+            _ => return,
+        };
+        let rows = *self.rows.get_or_insert_with(|| {
+            let mut create_row = |row: usize| {
+                let transform = UiTransform::new(
+                    format!("hud_{}", row),
+                    Anchor::TopLeft,
+                    Anchor::TopLeft,
+                    MARGIN,
+                    -(MARGIN + row as f32 * ROW_HEIGHT),
+                    1.0,
+                    WIDTH,
+                    ROW_HEIGHT,
+                );
+                let text = UiText::new(
+                    get_default_font(&loader, &fonts),
+                    String::new(),
+                    TEXT_COLOR,
+                    FONT_SIZE,
+                    LineMode::Single,
+                    Anchor::MiddleLeft,
+                );
+                entities
+                    .build_entity()
+                    .with(transform, &mut ui_transforms)
+                    .with(text, &mut ui_texts)
+                    .build()
+            };
+            [create_row(0), create_row(1), create_row(2)]
+        });
+
+        let tick = simulation.tick();
+        let waves = config.waves.len();
+        let (wave, wave_color) = match defense.countdown(config, tick) {
+            Some(seconds) => (
+                format!("wave {}/{} in {}s", defense.wave + 1, waves, seconds),
+                COUNTDOWN_COLOR,
+            ),
+            None if defense.waves_over(config) => ("last wave".to_string(), TEXT_COLOR),
+            None => (format!("wave {}/{}", defense.wave + 1, waves), TEXT_COLOR),
+        };
+        let dropped = match self.lives {
+            Some((lives, _)) if defense.lives < lives => Some(tick),
+            Some((_, dropped)) => dropped,
+            None => None,
+        };
+        self.lives = Some((defense.lives, dropped));
+        let lives_color = match dropped {
+            Some(dropped) if tick < dropped + WARNING_TICKS => WARNING_COLOR,
+            _ => TEXT_COLOR,
+        };
+
+        let lines = [
+            (wave, wave_color),
+            (format!("{} lives", defense.lives), lives_color),
+            (
+                format!("{} gold", treasury.gold(local_player.team)),
+                TEXT_COLOR,
+            ),
+        ];
+        for (entity, (line, color)) in rows.iter().zip(lines.iter()) {
+            if let Some(text) = ui_texts.get_mut(*entity) {
+                if text.text != *line {
+                    text.text = line.clone();
+                }
+                text.color = *color;
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use amethyst::{
+    derive::SystemDesc,
+    ecs::{Entities, Read, ReadStorage, System, SystemData, Write},
+    shrev::EventChannel,
+};
+
+use crate::{
+    components::SimulationId,
+    orders::{LocalOrders, Order},
+    resources::{Lockstep, Simulation},
+    simulation::entities_by_id,
+};
+
+#[derive(SystemDesc)]
+pub struct LockstepSystem;
+
+// this system hands the orders of the local player to the simulation, in a lan match
+// they are sent to the others and applied together with theirs a few ticks later
+impl<'s> System<'s> for LockstepSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, SimulationId>,
+        Read<'s, Simulation>,
+        Write<'s, LocalOrders>,
+        Option<Write<'s, Lockstep>>,
+        Write<'s, EventChannel<Order>>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, ids, simulation, mut local_orders, lockstep, mut orders): Self::SystemData,
+    ) {
+        let mut lockstep = match lockstep {
+            Some(lockstep) => lockstep,
+            None => {
+                orders.iter_write(local_orders.take());
+                return;
+            }
+        };
+
+        let tick = simulation.tick();
+        let commands = local_orders
+            .take()
+            .into_iter()
+            .filter_map(|order| {
+                order
+                    .kind
+                    .map_entities(|entity| ids.get(entity).map(|id| id.0))
+            })
+            .collect();
+        let checksum = tick
+            .checked_sub(1)
+            .and_then(|finished| simulation.checksum(finished).map(|sum| (finished, sum)));
+        let input_delay = lockstep.input_delay;
+        lockstep.submit(tick + input_delay, commands, checksum);
+
+        let entity_of = entities_by_id(&entities, &ids);
+        for (team, commands) in lockstep.take(tick) {
+            for kind in commands {
+                if let Some(kind) = kind.map_entities(|id| entity_of.get(&id).copied()) {
+                    orders.single_write(Order::new(team, kind));
+                }
+            }
+        }
+    }
+}
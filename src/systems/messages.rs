@@ -0,0 +1,101 @@
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    core::Time,
+    derive::SystemDesc,
+    ecs::{Entities, Entity, Read, ReadExpect, System, SystemData, Write, WriteStorage},
+    ui::{get_default_font, Anchor, FontAsset, LineMode, UiText, UiTransform},
+};
+use log::error;
+use std::collections::VecDeque;
+
+use crate::resources::ScriptMessages;
+
+// a message disappears after this time
+const MESSAGE_SECONDS: f64 = 8.0;
+// older ones make room for new ones
+const MAX_MESSAGES: usize = 4;
+const FONT_SIZE: f32 = 20.0;
+// in pixels, below the top of the window
+const MARGIN: f32 = 60.0;
+const ROW_HEIGHT: f32 = 26.0;
+const WIDTH: f32 = 900.0;
+
+const TEXT_COLOR: [f32; 4] = [1.0, 0.9, 0.5, 1.0];
+
+#[derive(Default, SystemDesc)]
+pub struct MessageSystem {
+    // (line, time it was shown), oldest first
+    shown: VecDeque<(Entity, f64)>,
+}
+
+// this system shows what the scripts of the scenario tell the players in lines
+// at the top of the window, the newest at the bottom
+impl<'s> System<'s> for MessageSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, UiTransform>,
+        WriteStorage<'s, UiText>,
+        ReadExpect<'s, Loader>,
+        Read<'s, AssetStorage<FontAsset>>,
+        Read<'s, Time>,
+        Write<'s, ScriptMessages>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, mut ui_transforms, mut ui_texts, loader, fonts, time, mut messages): Self::SystemData,
+    ) {
+        let now = time.absolute_real_time_seconds();
+        let new = messages.take();
+        let expired = self
+            .shown
+            .iter()
+            .take_while(|&&(_, shown)| now - shown >= MESSAGE_SECONDS)
+            .count();
+        let crowded = (self.shown.len() + new.len()).saturating_sub(MAX_MESSAGES);
+        let removed = expired.max(crowded).min(self.shown.len());
+        if removed == 0 && new.is_empty() {
+            return;
+        }
+        for (entity, _) in self.shown.drain(..removed) {
+            if let Err(e) = entities.delete(entity) {
+                error!("failed to remove a message: {}", e);
+            }
+        }
+
+        // only the last ones of a burst have room
+        let skipped = new.len().saturating_sub(MAX_MESSAGES);
+        for text in new.into_iter().skip(skipped) {
+            let transform = UiTransform::new(
+                "script_message".to_string(),
+                Anchor::TopMiddle,
+                Anchor::TopMiddle,
+                0.0,
+                0.0,
+                1.0,
+                WIDTH,
+                ROW_HEIGHT,
+            );
+            let text = UiText::new(
+                get_default_font(&loader, &fonts),
+                text,
+                TEXT_COLOR,
+                FONT_SIZE,
+                LineMode::Single,
+                Anchor::Middle,
+            );
+            let entity = entities
+                .build_entity()
+                .with(transform, &mut ui_transforms)
+                .with(text, &mut ui_texts)
+                .build();
+            self.shown.push_back((entity, now));
+        }
+
+        for (row, (entity, _)) in self.shown.iter().enumerate() {
+            if let Some(transform) = ui_transforms.get_mut(*entity) {
+                transform.local_y = -(MARGIN + row as f32 * ROW_HEIGHT);
+            }
+        }
+    }
+}
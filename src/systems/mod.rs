@@ -1,5 +1,49 @@
+mod alerts;
+mod bookmarks;
 mod camera;
+mod checksum;
+mod combat;
+mod cursor;
+mod debug;
+mod defense;
+mod editor;
+mod fog;
 mod hover_system;
+mod hud;
+mod lockstep;
+mod messages;
+mod movement;
+mod orders;
+mod position;
+mod production;
+mod profiler;
+mod replay;
+mod screenshot;
+mod script;
+mod selection;
+mod victory;
 
-pub use self::camera::{BorderSystem, CameraSystem};
+pub use self::alerts::AlertSystem;
+pub use self::bookmarks::BookmarkSystemDesc;
+pub use self::camera::{look_at, looking_at, BorderSystem, CameraSystem};
+pub use self::checksum::ChecksumSystem;
+pub use self::combat::CombatSystem;
+pub use self::cursor::CursorSystem;
+pub use self::debug::{DebugSystemDesc, TileLabelSystem};
+pub use self::defense::{TowerSystem, WaveSystem};
+pub use self::editor::EditorSystemDesc;
+pub use self::fog::FogSystem;
 pub use self::hover_system::HoverSystemDesc;
+pub use self::hud::HudSystem;
+pub use self::lockstep::LockstepSystem;
+pub use self::messages::MessageSystem;
+pub use self::movement::MovementSystem;
+pub use self::orders::OrderSystemDesc;
+pub use self::position::PositionSystem;
+pub use self::production::{unit_transform, ProductionSystem};
+pub use self::profiler::{add_profiled, profiler, PerformanceSystemDesc, Profiled};
+pub use self::replay::{PlaybackControlSystemDesc, PlaybackSystem, RecordSystemDesc};
+pub use self::screenshot::ScreenshotSystemDesc;
+pub use self::script::ScriptSystem;
+pub use self::selection::SelectionSystemDesc;
+pub use self::victory::VictorySystem;
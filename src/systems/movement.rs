@@ -0,0 +1,54 @@
+use amethyst::{
+    derive::SystemDesc,
+    ecs::{Entities, Join, Read, ReadStorage, System, SystemData, WriteStorage},
+};
+
+use crate::{
+    components::{GridPosition, MoveOrder, UnitStats},
+    fixed::Fixed,
+    minions::WorldBorders,
+    simulation::TICKS_PER_SECOND,
+};
+
+#[derive(SystemDesc)]
+pub struct MovementSystem;
+
+// this system walks the minions with a move-order straight to their target tile, once per tick
+impl<'s> System<'s> for MovementSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, UnitStats>,
+        WriteStorage<'s, MoveOrder>,
+        WriteStorage<'s, GridPosition>,
+        Read<'s, WorldBorders>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, unit_stats, mut move_orders, mut positions, world_borders): Self::SystemData,
+    ) {
+        let ticks_per_second = Fixed::from_int(TICKS_PER_SECOND as i32);
+        let mut arrived = Vec::new();
+
+        for (entity, stats, move_order, position) in
+            (&entities, &unit_stats, &move_orders, &mut positions).join()
+        {
+            let target = GridPosition::center(world_borders.topology, (move_order.x, move_order.z));
+            let (dx, dz) = (target.x - position.x, target.z - position.z);
+            let distance = Fixed::hypot(dx, dz);
+            let step = Fixed::from_f32(stats.speed) / ticks_per_second;
+
+            if distance <= step {
+                *position = target;
+                arrived.push(entity);
+            } else {
+                position.x += dx * step / distance;
+                position.z += dz * step / distance;
+            }
+        }
+
+        for entity in arrived {
+            move_orders.remove(entity);
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use amethyst::{
+    core::SystemDesc,
+    ecs::{
+        Builder, Entities, Join, LazyUpdate, Read, ReadStorage, System, SystemData, World, Write,
+        WriteStorage,
+    },
+    shrev::{EventChannel, ReaderId},
+};
+use log::info;
+use std::collections::HashSet;
+
+use crate::{
+    components::{Building, GridPosition, Minion, MoveOrder, ProductionQueue, Sight, Team, Tower},
+    config::ArenaConfig,
+    minions::{building_transform, WorldBorders},
+    orders::{Order, OrderKind},
+    resources::{MinionAssets, Simulation, TerrainMap, Treasury, UnitRegistry},
+    systems::defense::can_build_tower,
+};
+
+#[derive(Default, Debug)]
+pub struct OrderSystemDesc;
+
+impl<'a, 'b> SystemDesc<'a, 'b, OrderSystem> for OrderSystemDesc {
+    fn build(self, world: &mut World) -> OrderSystem {
+        <OrderSystem as System<'_>>::SystemData::setup(world);
+        let reader_id = world.fetch_mut::<EventChannel<Order>>().register_reader();
+        OrderSystem { reader_id }
+    }
+}
+
+// this system applies the orders of all teams to the game-state
+pub struct OrderSystem {
+    reader_id: ReaderId<Order>,
+}
+
+impl<'s> System<'s> for OrderSystem {
+    type SystemData = (
+        Entities<'s>,
+        Read<'s, EventChannel<Order>>,
+        Write<'s, Treasury>,
+        Read<'s, UnitRegistry>,
+        ReadStorage<'s, Team>,
+        ReadStorage<'s, Minion>,
+        ReadStorage<'s, Building>,
+        ReadStorage<'s, GridPosition>,
+        WriteStorage<'s, ProductionQueue>,
+        WriteStorage<'s, MoveOrder>,
+        Read<'s, ArenaConfig>,
+        Read<'s, WorldBorders>,
+        Read<'s, TerrainMap>,
+        // not inserted on the dedicated server
+        Option<Read<'s, MinionAssets>>,
+        Read<'s, LazyUpdate>,
+        Write<'s, Simulation>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            orders,
+            mut treasury,
+            unit_registry,
+            teams,
+            minions,
+            buildings,
+            positions,
+            mut production_queues,
+            mut move_orders,
+            arena_config,
+            world_borders,
+            terrain,
+            minion_assets,
+            lazy,
+            mut simulation,
+        ): Self::SystemData,
+    ) {
+        let occupied: HashSet<(i16, i16)> = (&minions, &positions)
+            .join()
+            .map(|(_, position)| position.tile(world_borders.topology))
+            .collect();
+        // the towers of this tick exist once the tick is over
+        let mut built = HashSet::new();
+        for order in orders.read(&mut self.reader_id) {
+            let team = order.team;
+            // nobody may command the entities of another team
+            let owned = |entity| teams.get(entity).map_or(false, |owner| owner.0 == team);
+
+            match &order.kind {
+                OrderKind::Train {
+                    building,
+                    unit_type,
+                } => {
+                    if !owned(*building) {
+                        continue;
+                    }
+                    let cost = match unit_registry.stats(unit_type) {
+                        Some(stats) => stats.cost,
+                        None => continue,
+                    };
+                    if let Some(queue) = production_queues.get_mut(*building) {
+                        if queue.is_full() {
+                            info!("production queue is full");
+                        } else if treasury.spend(team, cost) {
+                            queue.push(unit_type.clone());
+                        } else {
+                            info!(
+                                "not enough gold for {}, team {} has {}",
+                                unit_type,
+                                team,
+                                treasury.gold(team)
+                            );
+                        }
+                    }
+                }
+                OrderKind::CancelTraining { building } => {
+                    if !owned(*building) {
+                        continue;
+                    }
+                    if let Some(stats) = production_queues
+                        .get_mut(*building)
+                        .and_then(|queue| queue.cancel_last())
+                        .and_then(|unit_type| unit_registry.stats(&unit_type))
+                    {
+                        treasury.refund(team, stats.cost);
+                    }
+                }
+                OrderKind::SetRallyPoint { building, x, z } => {
+                    if !owned(*building) {
+                        continue;
+                    }
+                    if let Some(queue) = production_queues.get_mut(*building) {
+                        queue.rally_point = Some((*x, *z));
+                    }
+                }
+                OrderKind::Move { units, x, z } => {
+                    for unit in units.iter().copied() {
+                        if owned(unit) && minions.contains(unit) {
+                            // the unit may have died since the order was issued
+                            let _ = move_orders.insert(unit, MoveOrder { x: *x, z: *z });
+                        }
+                    }
+                }
+                OrderKind::BuildTower { x, z } => {
+                    let config = match &arena_config.defense {
+                        Some(config) if config.attackers != team => config,
+                        _ => continue,
+                    };
+                    let tile = (*x, *z);
+                    if !can_build_tower(
+                        config,
+                        &world_borders,
+                        &terrain,
+                        &buildings,
+                        &occupied,
+                        &built,
+                        tile,
+                    ) {
+                        info!("can't build a tower on {:?}", tile);
+                        continue;
+                    }
+                    if !treasury.spend(team, config.tower.cost) {
+                        info!(
+                            "not enough gold for a tower, team {} has {}",
+                            team,
+                            treasury.gold(team)
+                        );
+                        continue;
+                    }
+                    built.insert(tile);
+
+                    let building = Building::new(*x, *z, 1, 1);
+                    let mut builder = lazy
+                        .create_entity(&entities)
+                        .with(building)
+                        .with(Tower::default())
+                        .with(Team(team))
+                        .with(Sight(config.tower.range))
+                        .with(simulation.new_id());
+                    if let Some(minion_assets) = &minion_assets {
+                        builder = builder
+                            .with(building_transform(
+                                &building,
+                                &world_borders,
+                                &terrain,
+                                arena_config.tile_size,
+                            ))
+                            .with(minion_assets.mesh.clone())
+                            .with(minion_assets.team_material(team));
+                    }
+                    builder.build();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Attack, UnitStats};
+    use crate::grid::Topology;
+    use crate::resources::UnitType;
+    use amethyst::ecs::{Entity, RunNow, WorldExt};
+
+    fn world() -> (World, OrderSystem, Entity, Entity) {
+        let mut world = World::new();
+        world.register::<Team>();
+        world.register::<Minion>();
+        world.register::<Building>();
+        world.register::<GridPosition>();
+        world.register::<ProductionQueue>();
+        world.register::<MoveOrder>();
+        let system = OrderSystemDesc::default().build(&mut world);
+        world.insert(Treasury::new(2, 100));
+        let mut unit_registry = UnitRegistry::default();
+        unit_registry.insert(
+            "minion".to_string(),
+            UnitType {
+                prefab: None,
+                stats: UnitStats {
+                    name: "Minion".to_string(),
+                    health: 10,
+                    speed: 1.0,
+                    sight: 3.0,
+                    cost: 30,
+                    build_time: 1.0,
+                    attack: Attack::default(),
+                    abilities: Vec::new(),
+                },
+                team_colored: false,
+            },
+        );
+        world.insert(unit_registry);
+
+        let building = world
+            .create_entity()
+            .with(Building::new(0, 0, 1, 1))
+            .with(ProductionQueue::new(vec!["minion".to_string()]))
+            .with(Team(0))
+            .build();
+        let unit = world
+            .create_entity()
+            .with(Minion {
+                unit_type: "minion".to_string(),
+            })
+            .with(GridPosition::center(Topology::Square, (1, 1)))
+            .with(Team(0))
+            .build();
+        (world, system, building, unit)
+    }
+
+    // every order of the team for the building and the unit
+    fn send(world: &World, team: u8, building: Entity, unit: Entity) {
+        let mut orders = world.write_resource::<EventChannel<Order>>();
+        orders.single_write(Order::new(
+            team,
+            OrderKind::Train {
+                building,
+                unit_type: "minion".to_string(),
+            },
+        ));
+        orders.single_write(Order::new(
+            team,
+            OrderKind::SetRallyPoint {
+                building,
+                x: 1,
+                z: 0,
+            },
+        ));
+        orders.single_write(Order::new(
+            team,
+            OrderKind::Move {
+                units: vec![unit],
+                x: 0,
+                z: 1,
+            },
+        ));
+    }
+
+    #[test]
+    fn orders_for_another_team_are_rejected() {
+        let (world, mut system, building, unit) = world();
+        send(&world, 1, building, unit);
+        system.run_now(&world);
+
+        let queues = world.read_storage::<ProductionQueue>();
+        let queue = queues.get(building).unwrap();
+        assert!(queue.queue.is_empty());
+        assert_eq!(queue.rally_point, None);
+        assert!(!world.read_storage::<MoveOrder>().contains(unit));
+        assert_eq!(world.read_resource::<Treasury>().gold(1), 100);
+    }
+
+    #[test]
+    fn orders_for_the_own_team_are_applied() {
+        let (world, mut system, building, unit) = world();
+        send(&world, 0, building, unit);
+        system.run_now(&world);
+
+        let queues = world.read_storage::<ProductionQueue>();
+        let queue = queues.get(building).unwrap();
+        assert_eq!(queue.queue.len(), 1);
+        assert_eq!(queue.rally_point, Some((1, 0)));
+        let move_orders = world.read_storage::<MoveOrder>();
+        let move_order = move_orders.get(unit).unwrap();
+        assert_eq!((move_order.x, move_order.z), (0, 1));
+        assert_eq!(world.read_resource::<Treasury>().gold(0), 70);
+    }
+}
@@ -0,0 +1,39 @@
+use amethyst::{
+    core::Transform,
+    derive::SystemDesc,
+    ecs::{Join, Read, ReadStorage, System, SystemData, WriteStorage},
+};
+
+use crate::{
+    components::GridPosition, config::ArenaConfig, minions::WorldBorders, picking,
+    resources::TerrainMap,
+};
+
+#[derive(SystemDesc)]
+pub struct PositionSystem;
+
+// this system moves the transforms of the units to their position in the simulation
+// and puts them on the ground
+impl<'s> System<'s> for PositionSystem {
+    type SystemData = (
+        ReadStorage<'s, GridPosition>,
+        WriteStorage<'s, Transform>,
+        Read<'s, WorldBorders>,
+        Read<'s, ArenaConfig>,
+        Read<'s, TerrainMap>,
+    );
+
+    fn run(
+        &mut self,
+        (positions, mut transforms, world_borders, arena_config, terrain): Self::SystemData,
+    ) {
+        let tile_size = arena_config.tile_size;
+        for (position, transform) in (&positions, &mut transforms).join() {
+            let x = world_borders.left + position.x.to_f32() * tile_size;
+            let z = world_borders.bottom + position.z.to_f32() * tile_size;
+            let y = picking::terrain_height(&terrain, &world_borders, tile_size, x, z)
+                + tile_size / 4.0;
+            transform.set_translation_xyz(x, y, z);
+        }
+    }
+}
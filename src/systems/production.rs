@@ -0,0 +1,313 @@
+use amethyst::{
+    core::{math::Vector3, Transform},
+    derive::SystemDesc,
+    ecs::{Entities, Join, LazyUpdate, Read, ReadStorage, System, SystemData, Write, WriteStorage},
+};
+use std::collections::HashSet;
+
+use crate::{
+    components::{Building, GridPosition, Minion, MoveOrder, ProductionQueue, SimulationId, Team},
+    config::ArenaConfig,
+    grid::Topology,
+    minions::WorldBorders,
+    resources::{MinionAssets, Simulation, TerrainMap, UnitRegistry},
+    rng::Rng,
+    simulation,
+};
+
+// how far away from the footprint a unit may spawn
+const MAX_SPAWN_DISTANCE: i16 = 3;
+
+#[derive(SystemDesc)]
+pub struct ProductionSystem;
+
+// this system advances the production queues of all buildings by one tick
+// and spawns the finished units next to them
+impl<'s> System<'s> for ProductionSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, SimulationId>,
+        ReadStorage<'s, Building>,
+        WriteStorage<'s, ProductionQueue>,
+        ReadStorage<'s, Team>,
+        ReadStorage<'s, Minion>,
+        ReadStorage<'s, GridPosition>,
+        Read<'s, WorldBorders>,
+        Read<'s, ArenaConfig>,
+        Read<'s, TerrainMap>,
+        // not inserted on the dedicated server
+        Option<Read<'s, MinionAssets>>,
+        Read<'s, UnitRegistry>,
+        Read<'s, LazyUpdate>,
+        Write<'s, Rng>,
+        Write<'s, Simulation>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            ids,
+            buildings,
+            mut production_queues,
+            teams,
+            minions,
+            positions,
+            world_borders,
+            arena_config,
+            terrain,
+            minion_assets,
+            unit_registry,
+            lazy,
+            mut rng,
+            mut simulation,
+        ): Self::SystemData,
+    ) {
+        let tile_size = arena_config.tile_size;
+        let topology = world_borders.topology;
+        let mut occupied: HashSet<(i16, i16)> = (&minions, &positions)
+            .join()
+            .map(|(_, position)| position.tile(topology))
+            .collect();
+
+        // in the order of the SimulationIds, the rng has to be drawn from in the same order
+        // on every client
+        let mut producers: Vec<_> = (&ids, &buildings, &mut production_queues, &teams)
+            .join()
+            .collect();
+        producers.sort_by_key(|&(id, _, _, _)| id.0);
+        for (_, building, queue, team) in producers {
+            let build_ticks = match queue
+                .queue
+                .front()
+                .and_then(|unit_type| unit_registry.stats(unit_type))
+            {
+                Some(stats) => simulation::ticks(stats.build_time),
+                None => continue,
+            };
+            queue.progress = (queue.progress + 1).min(build_ticks);
+            if queue.progress < build_ticks {
+                continue;
+            }
+
+            // the unit waits inside until there is room
+            let spawn = match free_tile(topology, building, queue.rally_point, &mut rng, |x, z| {
+                world_borders.contains_tile(x, z)
+                    && !occupied.contains(&(x, z))
+                    && !(&buildings).join().any(|other| other.contains(x, z))
+            }) {
+                Some(spawn) => spawn,
+                None => continue,
+            };
+            let unit_type = queue.finish().expect("production queue is empty");
+            occupied.insert(spawn);
+
+            let transform = unit_transform(&world_borders, &terrain, tile_size, spawn);
+            let builder = unit_registry.spawn(
+                &lazy,
+                &entities,
+                &unit_type,
+                team.0,
+                GridPosition::center(topology, spawn),
+                minion_assets
+                    .as_ref()
+                    .map(|minion_assets| minion_assets.team_material(team.0)),
+            );
+            if let Some(mut builder) = builder {
+                builder = builder.with(transform).with(simulation.new_id());
+                if let Some((x, z)) = queue.rally_point {
+                    builder = builder.with(MoveOrder { x, z });
+                }
+                builder.build();
+            }
+        }
+    }
+}
+
+// where a unit on the tile is drawn until it moves
+pub fn unit_transform(
+    world_borders: &WorldBorders,
+    terrain: &TerrainMap,
+    tile_size: f32,
+    (x, z): (i16, i16),
+) -> Transform {
+    let (center_x, center_z) = world_borders.tile_center(x, z, tile_size);
+    let mut transform = Transform::default();
+    transform.set_translation_xyz(center_x, terrain.height(x, z) + tile_size / 4.0, center_z);
+    transform.set_scale(Vector3::new(
+        tile_size / 4.0,
+        tile_size / 4.0,
+        tile_size / 4.0,
+    ));
+    transform
+}
+
+// the free tile closest to the footprint, ties are broken by the distance to the rally point
+// and then by the seeded rng
+fn free_tile(
+    topology: Topology,
+    building: &Building,
+    rally_point: Option<(i16, i16)>,
+    rng: &mut Rng,
+    is_free: impl Fn(i16, i16) -> bool,
+) -> Option<(i16, i16)> {
+    let (target_x, target_z) = rally_point
+        .map(|tile| topology.center(tile))
+        .unwrap_or_else(|| building.center(topology));
+
+    let distance = |tile: (i16, i16)| {
+        let (x, z) = topology.center(tile);
+        (x - target_x).powi(2) + (z - target_z).powi(2)
+    };
+
+    (1..=MAX_SPAWN_DISTANCE).find_map(|ring| {
+        let free: Vec<_> = building
+            .ring(ring)
+            .into_iter()
+            .filter(|&(x, z)| is_free(x, z))
+            .collect();
+        if free.is_empty() {
+            return None;
+        }
+        let closest = free
+            .iter()
+            .map(|&tile| distance(tile))
+            .fold(f32::INFINITY, f32::min);
+        let candidates: Vec<_> = free
+            .into_iter()
+            .filter(|&tile| distance(tile) <= closest)
+            .collect();
+        Some(candidates[rng.below(candidates.len() as u64) as usize])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Attack, Health, Sight, UnitStats};
+    use crate::resources::UnitType;
+    use amethyst::ecs::{Builder, RunNow, World, WorldExt};
+
+    // spawn tile next to a building on (4, 4)
+    fn spawn(
+        rally_point: Option<(i16, i16)>,
+        seed: u64,
+        is_free: impl Fn(i16, i16) -> bool,
+    ) -> Option<(i16, i16)> {
+        let building = Building::new(4, 4, 1, 1);
+        free_tile(
+            Topology::Square,
+            &building,
+            rally_point,
+            &mut Rng::new(seed),
+            is_free,
+        )
+    }
+
+    #[test]
+    fn units_spawn_next_to_the_footprint() {
+        let beside = [(4, 3), (4, 5), (3, 4), (5, 4)];
+        for seed in 0..8 {
+            let tile = spawn(None, seed, |_, _| true);
+            assert!(beside.contains(&tile.unwrap()));
+            assert_eq!(spawn(None, seed, |_, _| true), tile);
+        }
+    }
+
+    #[test]
+    fn the_rally_point_breaks_ties() {
+        assert_eq!(spawn(Some((8, 4)), 0, |_, _| true), Some((5, 4)));
+    }
+
+    #[test]
+    fn occupied_rings_push_the_spawn_outwards() {
+        let building = Building::new(4, 4, 1, 1);
+        let ring = building.ring(1);
+        let tile = spawn(None, 0, |x, z| !ring.contains(&(x, z)));
+        assert!(building.ring(2).contains(&tile.unwrap()));
+        assert_eq!(spawn(None, 0, |_, _| false), None);
+    }
+
+    // tiles of the units spawned by the buildings with the given ids, created in the given order
+    fn spawned(ids: &[u32]) -> Vec<(u32, (i16, i16))> {
+        let mut world = World::new();
+        world.register::<SimulationId>();
+        world.register::<Building>();
+        world.register::<ProductionQueue>();
+        world.register::<Team>();
+        world.register::<Minion>();
+        world.register::<GridPosition>();
+        world.register::<MoveOrder>();
+        world.register::<Sight>();
+        world.register::<Health>();
+        world.register::<Transform>();
+        world.insert(WorldBorders {
+            width: 10,
+            depth: 10,
+            ..WorldBorders::default()
+        });
+        world.insert(ArenaConfig {
+            width: 10,
+            depth: 10,
+            tile_size: 1.0,
+            ..ArenaConfig::default()
+        });
+        world.insert(TerrainMap::default());
+        let mut unit_registry = UnitRegistry::default();
+        unit_registry.insert(
+            "minion".to_string(),
+            UnitType {
+                prefab: None,
+                stats: UnitStats {
+                    name: "Minion".to_string(),
+                    health: 10,
+                    speed: 1.0,
+                    sight: 3.0,
+                    cost: 10,
+                    build_time: 0.0,
+                    attack: Attack::default(),
+                    abilities: Vec::new(),
+                },
+                team_colored: false,
+            },
+        );
+        world.insert(unit_registry);
+        world.insert(Rng::new(7));
+        world.insert(Simulation::default());
+        for &id in ids {
+            let mut queue = ProductionQueue::new(vec!["minion".to_string()]);
+            queue.push("minion".to_string());
+            // both buildings compete for the tile between them
+            let x = if id == 1 { 2 } else { 4 };
+            world
+                .create_entity()
+                .with(SimulationId(id))
+                .with(Building::new(x, 4, 1, 1))
+                .with(queue)
+                .with(Team(id as u8 - 1))
+                .build();
+        }
+        ProductionSystem.run_now(&world);
+        world.maintain();
+
+        let topology = world.read_resource::<WorldBorders>().topology;
+        let mut spawns: Vec<_> = (
+            &world.read_storage::<Minion>(),
+            &world.read_storage::<Team>(),
+            &world.read_storage::<GridPosition>(),
+        )
+            .join()
+            .map(|(_, team, position)| (u32::from(team.0) + 1, position.tile(topology)))
+            .collect();
+        spawns.sort();
+        spawns
+    }
+
+    #[test]
+    fn queues_are_served_in_the_order_of_the_ids() {
+        let spawns = spawned(&[1, 2]);
+        assert_eq!(spawns.len(), 2);
+        assert_ne!(spawns[0].1, spawns[1].1);
+        assert_eq!(spawned(&[2, 1]), spawns);
+    }
+}
@@ -0,0 +1,338 @@
+use amethyst::{
+    assets::{AssetStorage, Handle, Loader},
+    core::SystemDesc,
+    ecs::{
+        DispatcherBuilder, Entities, Entity, Join, Read, ReadExpect, ReadStorage, System,
+        SystemData, World, WriteStorage,
+    },
+    input::InputEvent,
+    renderer::{Hidden, Mesh},
+    shrev::{EventChannel, ReaderId},
+    ui::{get_default_font, Anchor, FontAsset, FontHandle, LineMode, UiImage, UiText, UiTransform},
+};
+use log::{error, info};
+use std::{
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    components::{Minion, PieceInfo},
+    input::{ActionBinding, MovementBindingTypes},
+    resources::{trace_path, Profiler, SystemTimer, FRAME_HISTORY},
+};
+
+// in pixels, from the top right corner of the window
+const MARGIN: f32 = 20.0;
+const TEXT_WIDTH: f32 = 340.0;
+const TEXT_HEIGHT: f32 = 420.0;
+const FONT_SIZE: f32 = 14.0;
+const BAR_WIDTH: f32 = 2.0;
+const GRAPH_HEIGHT: f32 = 60.0;
+// frame time of a bar of the full height
+const GRAPH_SCALE: Duration = Duration::from_millis(50);
+// the slowest systems in the overlay
+const SHOWN_SYSTEMS: usize = 16;
+
+const TEXT_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+const FAST_COLOR: [f32; 4] = [0.2, 0.8, 0.2, 0.8];
+const SLOW_COLOR: [f32; 4] = [0.9, 0.8, 0.1, 0.8];
+const SLOWER_COLOR: [f32; 4] = [0.9, 0.2, 0.2, 0.8];
+
+// times the system for the Profiler under its name in the dispatcher
+pub struct Profiled<S> {
+    system: S,
+    profiler: Profiler,
+    // the PerformanceSystem collects it once a frame
+    timer: Arc<SystemTimer>,
+}
+
+impl<'s, S: System<'s>> System<'s> for Profiled<S> {
+    type SystemData = S::SystemData;
+
+    fn run(&mut self, data: Self::SystemData) {
+        if !self.profiler.enabled() {
+            self.system.run(data);
+            return;
+        }
+        let start = Instant::now();
+        self.system.run(data);
+        self.profiler.record(&self.timer, start);
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        self.system.setup(world);
+    }
+
+    fn dispose(self, world: &mut World) {
+        self.system.dispose(world);
+    }
+}
+
+// adds the system to the dispatcher like DispatcherBuilder::add, but timed
+pub fn add_profiled<'a, 'b, S>(
+    builder: &mut DispatcherBuilder<'a, 'b>,
+    profiler: &Profiler,
+    system: S,
+    name: &'static str,
+    dependencies: &[&str],
+) where
+    S: for<'c> System<'c> + Send + 'a,
+{
+    let system = Profiled {
+        system,
+        profiler: profiler.clone(),
+        timer: profiler.register(name),
+    };
+    builder.add(system, name, dependencies);
+}
+
+// the profiler of the world, the bundle and the simulation share it
+pub fn profiler(world: &mut World) -> Profiler {
+    world
+        .entry::<Profiler>()
+        .or_insert_with(Profiler::default)
+        .clone()
+}
+
+#[derive(Default, Debug)]
+pub struct PerformanceSystemDesc;
+
+impl<'a, 'b> SystemDesc<'a, 'b, PerformanceSystem> for PerformanceSystemDesc {
+    fn build(self, world: &mut World) -> PerformanceSystem {
+        <PerformanceSystem as System<'_>>::SystemData::setup(world);
+        let reader_id = world
+            .fetch_mut::<EventChannel<InputEvent<MovementBindingTypes>>>()
+            .register_reader();
+        PerformanceSystem {
+            reader_id,
+            overlay: None,
+        }
+    }
+}
+
+// this system counts the frames for the Profiler, shows the performance overlay
+// and records traces of the systems for chrome://tracing
+pub struct PerformanceSystem {
+    reader_id: ReaderId<InputEvent<MovementBindingTypes>>,
+    overlay: Option<Overlay>,
+}
+
+// the entities of the overlay while it is shown
+struct Overlay {
+    text: Entity,
+    // oldest frame first
+    bars: Vec<Entity>,
+}
+
+impl<'s> System<'s> for PerformanceSystem {
+    type SystemData = (
+        Read<'s, EventChannel<InputEvent<MovementBindingTypes>>>,
+        Read<'s, Profiler>,
+        Entities<'s>,
+        WriteStorage<'s, UiTransform>,
+        WriteStorage<'s, UiText>,
+        WriteStorage<'s, UiImage>,
+        ReadExpect<'s, Loader>,
+        Read<'s, AssetStorage<FontAsset>>,
+        ReadStorage<'s, PieceInfo>,
+        ReadStorage<'s, Minion>,
+        ReadStorage<'s, Handle<Mesh>>,
+        ReadStorage<'s, Hidden>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            input_events,
+            profiler,
+            entities,
+            mut ui_transforms,
+            mut ui_texts,
+            mut ui_images,
+            loader,
+            fonts,
+            piece_infos,
+            minions,
+            meshes,
+            hidden,
+        ): Self::SystemData,
+    ) {
+        profiler.frame();
+        for event in input_events.read(&mut self.reader_id) {
+            match event {
+                InputEvent::ActionPressed(ActionBinding::PerformanceOverlay) => {
+                    match self.overlay.take() {
+                        Some(overlay) => {
+                            for entity in overlay.bars.into_iter().chain(Some(overlay.text)) {
+                                if let Err(e) = entities.delete(entity) {
+                                    error!("failed to remove the performance overlay: {}", e);
+                                }
+                            }
+                        }
+                        None => {
+                            self.overlay = Some(create_overlay(
+                                &entities,
+                                &mut ui_transforms,
+                                &mut ui_texts,
+                                &mut ui_images,
+                                get_default_font(&loader, &fonts),
+                            ))
+                        }
+                    }
+                    profiler.show_overlay(self.overlay.is_some());
+                }
+                InputEvent::ActionPressed(ActionBinding::RecordTrace) => toggle_trace(&profiler),
+                _ => {}
+            }
+        }
+
+        let overlay = match &self.overlay {
+            Some(overlay) => overlay,
+            None => return,
+        };
+        let frame_times = profiler.frame_times();
+        let milliseconds = |duration: Duration| duration.as_secs_f32() * 1000.0;
+        let total: Duration = frame_times.iter().sum();
+        let slowest = frame_times.iter().max().copied().unwrap_or_default();
+        let fps = if total > Duration::from_secs(0) {
+            frame_times.len() as f32 / total.as_secs_f32()
+        } else {
+            0.0
+        };
+
+        let mut lines = vec![
+            format!(
+                "{:.0} fps, frame {:.2} ms, slowest {:.2} ms",
+                fps,
+                milliseconds(total) / frame_times.len().max(1) as f32,
+                milliseconds(slowest)
+            ),
+            format!(
+                "{} entities, {} tiles, {} minions",
+                (&entities).join().count(),
+                (&piece_infos).join().count(),
+                (&minions).join().count()
+            ),
+            // amethyst doesn't count its draw calls, every visible mesh is one
+            format!("{} meshes drawn", (&meshes, !&hidden).join().count()),
+        ];
+        if let Some(events) = profiler.tracing() {
+            lines.push(format!("recording a trace, {} events", events));
+        }
+        lines.push("systems per frame".to_string());
+        for (name, duration) in profiler.averages().into_iter().take(SHOWN_SYSTEMS) {
+            lines.push(format!("  {} {:.3} ms", name, milliseconds(duration)));
+        }
+        if let Some(ui_text) = ui_texts.get_mut(overlay.text) {
+            ui_text.text = lines.join("\n");
+        }
+
+        // the bars of frames before the first one stay empty
+        let missing = overlay.bars.len() - frame_times.len();
+        for (i, bar) in overlay.bars.iter().enumerate() {
+            let frame_time = match i.checked_sub(missing) {
+                Some(index) => frame_times[index],
+                None => Duration::from_secs(0),
+            };
+            if let Some(transform) = ui_transforms.get_mut(*bar) {
+                transform.height =
+                    (frame_time.as_secs_f32() / GRAPH_SCALE.as_secs_f32()).min(1.0) * GRAPH_HEIGHT;
+            }
+            // green within 60 fps, yellow within 30 fps
+            let color = if frame_time <= Duration::from_micros(16_667) {
+                FAST_COLOR
+            } else if frame_time <= Duration::from_micros(33_333) {
+                SLOW_COLOR
+            } else {
+                SLOWER_COLOR
+            };
+            if let Some(image) = ui_images.get_mut(*bar) {
+                *image = UiImage::SolidColor(color);
+            }
+        }
+    }
+}
+
+// starts a trace or saves the running one to the trace folder
+fn toggle_trace(profiler: &Profiler) {
+    let trace = match profiler.stop_trace() {
+        Some(trace) => trace,
+        None => {
+            profiler.start_trace();
+            info!("recording a trace, the trace key saves it");
+            return;
+        }
+    };
+    let path = match trace_path() {
+        Ok(path) => path,
+        Err(e) => {
+            error!("failed to create the trace folder: {}", e);
+            return;
+        }
+    };
+    // a long trace takes a while to write
+    thread::spawn(move || match trace.save(&path) {
+        Ok(()) => info!("saved a trace to {}", path.display()),
+        Err(e) => error!("failed to save the trace {}: {}", path.display(), e),
+    });
+}
+
+// a text in the top right corner and a bar for every frame of the history below it
+fn create_overlay(
+    entities: &Entities,
+    ui_transforms: &mut WriteStorage<UiTransform>,
+    ui_texts: &mut WriteStorage<UiText>,
+    ui_images: &mut WriteStorage<UiImage>,
+    font: FontHandle,
+) -> Overlay {
+    let text = entities
+        .build_entity()
+        .with(
+            UiTransform::new(
+                "performance_text".to_string(),
+                Anchor::TopRight,
+                Anchor::TopRight,
+                -MARGIN,
+                -MARGIN,
+                1.0,
+                TEXT_WIDTH,
+                TEXT_HEIGHT,
+            ),
+            ui_transforms,
+        )
+        .with(
+            UiText::new(
+                font,
+                String::new(),
+                TEXT_COLOR,
+                FONT_SIZE,
+                LineMode::Wrap,
+                Anchor::TopLeft,
+            ),
+            ui_texts,
+        )
+        .build();
+    // the bars stand on a line, the newest on the right
+    let bars = (0..FRAME_HISTORY)
+        .map(|i| {
+            let transform = UiTransform::new(
+                format!("performance_bar_{}", i),
+                Anchor::TopRight,
+                Anchor::BottomRight,
+                -MARGIN - (FRAME_HISTORY - 1 - i) as f32 * BAR_WIDTH,
+                -MARGIN - TEXT_HEIGHT - GRAPH_HEIGHT,
+                1.0,
+                BAR_WIDTH,
+                0.0,
+            );
+            entities
+                .build_entity()
+                .with(transform, ui_transforms)
+                .with(UiImage::SolidColor(FAST_COLOR), ui_images)
+                .build()
+        })
+        .collect();
+    Overlay { text, bars }
+}
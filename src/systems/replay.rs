@@ -0,0 +1,169 @@
+use amethyst::{
+    core::SystemDesc,
+    derive::SystemDesc,
+    ecs::{Entities, Read, ReadStorage, System, SystemData, World, Write},
+    input::InputEvent,
+    shrev::{EventChannel, ReaderId},
+};
+use log::info;
+
+use crate::{
+    components::SimulationId,
+    input::{ActionBinding, MovementBindingTypes},
+    orders::Order,
+    resources::{RecordedOrder, ReplayPlayer, ReplayRecorder, Simulation, SPEEDS},
+    simulation::{entities_by_id, TICKS_PER_SECOND},
+};
+
+// how far the seek actions jump, in seconds
+const SEEK_STEP: u64 = 10;
+
+#[derive(Default, Debug)]
+pub struct RecordSystemDesc;
+
+impl<'a, 'b> SystemDesc<'a, 'b, RecordSystem> for RecordSystemDesc {
+    fn build(self, world: &mut World) -> RecordSystem {
+        <RecordSystem as System<'_>>::SystemData::setup(world);
+        let reader_id = world.fetch_mut::<EventChannel<Order>>().register_reader();
+        RecordSystem { reader_id }
+    }
+}
+
+// this system adds the orders applied in this tick to the replay
+pub struct RecordSystem {
+    reader_id: ReaderId<Order>,
+}
+
+impl<'s> System<'s> for RecordSystem {
+    type SystemData = (
+        Read<'s, EventChannel<Order>>,
+        ReadStorage<'s, SimulationId>,
+        Read<'s, Simulation>,
+        Option<Write<'s, ReplayRecorder>>,
+    );
+
+    fn run(&mut self, (orders, ids, simulation, recorder): Self::SystemData) {
+        let mut recorder = match recorder {
+            Some(recorder) => recorder,
+            None => return,
+        };
+        for order in orders.read(&mut self.reader_id) {
+            if order.scripted {
+                continue;
+            }
+            let kind = order
+                .kind
+                .clone()
+                .map_entities(|entity| ids.get(entity).map(|id| id.0));
+            if let Some(kind) = kind {
+                recorder.replay.orders.push(RecordedOrder {
+                    tick: simulation.tick(),
+                    team: order.team,
+                    kind,
+                });
+            }
+        }
+    }
+}
+
+#[derive(SystemDesc)]
+pub struct PlaybackSystem;
+
+// this system issues the recorded orders of this tick
+impl<'s> System<'s> for PlaybackSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, SimulationId>,
+        Read<'s, Simulation>,
+        Option<Write<'s, ReplayPlayer>>,
+        Write<'s, EventChannel<Order>>,
+    );
+
+    fn run(&mut self, (entities, ids, simulation, player, mut orders): Self::SystemData) {
+        let mut player = match player {
+            Some(player) => player,
+            None => return,
+        };
+        let tick = simulation.tick();
+        let player = &mut *player;
+        let due = player.replay.orders[player.next_order..]
+            .iter()
+            .take_while(|order| order.tick <= tick)
+            .count();
+        if due == 0 {
+            return;
+        }
+
+        let entity_of = entities_by_id(&entities, &ids);
+        for order in &player.replay.orders[player.next_order..player.next_order + due] {
+            let kind = order
+                .kind
+                .clone()
+                .map_entities(|id| entity_of.get(&id).copied());
+            if let Some(kind) = kind {
+                orders.single_write(Order::new(order.team, kind));
+            }
+        }
+        player.next_order += due;
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct PlaybackControlSystemDesc;
+
+impl<'a, 'b> SystemDesc<'a, 'b, PlaybackControlSystem> for PlaybackControlSystemDesc {
+    fn build(self, world: &mut World) -> PlaybackControlSystem {
+        <PlaybackControlSystem as System<'_>>::SystemData::setup(world);
+        let reader_id = world
+            .fetch_mut::<EventChannel<InputEvent<MovementBindingTypes>>>()
+            .register_reader();
+        PlaybackControlSystem { reader_id }
+    }
+}
+
+// this system lets the viewer of a replay pause, change the speed and seek
+pub struct PlaybackControlSystem {
+    reader_id: ReaderId<InputEvent<MovementBindingTypes>>,
+}
+
+impl<'s> System<'s> for PlaybackControlSystem {
+    type SystemData = (
+        Read<'s, EventChannel<InputEvent<MovementBindingTypes>>>,
+        Read<'s, Simulation>,
+        Option<Write<'s, ReplayPlayer>>,
+    );
+
+    fn run(&mut self, (input_events, simulation, player): Self::SystemData) {
+        let mut player = match player {
+            Some(player) => player,
+            None => return,
+        };
+        let seek_step = SEEK_STEP * TICKS_PER_SECOND as u64;
+        for event in input_events.read(&mut self.reader_id) {
+            let tick = player.seek.unwrap_or_else(|| simulation.tick());
+            match event {
+                InputEvent::ActionPressed(ActionBinding::Pause) => player.paused = !player.paused,
+                InputEvent::ActionPressed(ActionBinding::Slower) => {
+                    player.speed = player.speed.saturating_sub(1)
+                }
+                InputEvent::ActionPressed(ActionBinding::Faster) => {
+                    player.speed = (player.speed + 1).min(SPEEDS.len() - 1)
+                }
+                InputEvent::ActionPressed(ActionBinding::SeekBack) => {
+                    player.seek = Some(tick.saturating_sub(seek_step))
+                }
+                InputEvent::ActionPressed(ActionBinding::SeekForward) => {
+                    player.seek = Some(tick + seek_step)
+                }
+                _ => continue,
+            }
+            info!(
+                "replay at {}s of {}s, {}x{}",
+                player.seek.unwrap_or(tick) / TICKS_PER_SECOND as u64,
+                player.end() / TICKS_PER_SECOND as u64,
+                SPEEDS[player.speed],
+                if player.paused { ", paused" } else { "" }
+            );
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use amethyst::{
+    core::SystemDesc,
+    ecs::{Read, System, SystemData, World, Write},
+    input::InputEvent,
+    shrev::{EventChannel, ReaderId},
+};
+use log::error;
+
+use crate::{
+    capture::{self, Captures},
+    input::{ActionBinding, MovementBindingTypes},
+};
+
+#[derive(Default, Debug)]
+pub struct ScreenshotSystemDesc;
+
+impl<'a, 'b> SystemDesc<'a, 'b, ScreenshotSystem> for ScreenshotSystemDesc {
+    fn build(self, world: &mut World) -> ScreenshotSystem {
+        <ScreenshotSystem as System<'_>>::SystemData::setup(world);
+        let reader_id = world
+            .fetch_mut::<EventChannel<InputEvent<MovementBindingTypes>>>()
+            .register_reader();
+        ScreenshotSystem { reader_id }
+    }
+}
+
+// this system saves the next rendered frame to a png in the screenshot folder,
+// the render graph reads it back, see capture
+pub struct ScreenshotSystem {
+    reader_id: ReaderId<InputEvent<MovementBindingTypes>>,
+}
+
+impl<'s> System<'s> for ScreenshotSystem {
+    type SystemData = (
+        Read<'s, EventChannel<InputEvent<MovementBindingTypes>>>,
+        Option<Write<'s, Captures>>,
+    );
+
+    fn run(&mut self, (input_events, captures): Self::SystemData) {
+        let pressed = input_events
+            .read(&mut self.reader_id)
+            .any(|event| match event {
+                InputEvent::ActionPressed(ActionBinding::Screenshot) => true,
+                _ => false,
+            });
+        let mut captures = match captures {
+            Some(captures) if pressed => captures,
+            _ => return,
+        };
+        match capture::screenshot_path() {
+            Ok(path) => captures.request(path),
+            Err(e) => error!("failed to create the screenshot folder: {}", e),
+        }
+    }
+}
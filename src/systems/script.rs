@@ -0,0 +1,184 @@
+use amethyst::{
+    derive::SystemDesc,
+    ecs::{Entities, Join, LazyUpdate, Read, ReadStorage, System, SystemData, Write},
+    shrev::EventChannel,
+};
+use log::{info, warn};
+use std::{
+    collections::{BTreeMap, HashMap},
+    mem,
+};
+
+use crate::{
+    components::{Building, GridPosition, Minion, SimulationId, Team},
+    config::ArenaConfig,
+    minions::WorldBorders,
+    orders::Order,
+    resources::{
+        MinionAssets, ScriptCommand, ScriptMessages, Scripts, Simulation, TerrainMap, Treasury,
+        UnitRegistry,
+    },
+    simulation::entities_by_id,
+    systems::production::unit_transform,
+};
+
+#[derive(SystemDesc)]
+pub struct ScriptSystem;
+
+// this system tells the scripts of the scenario what happened in the tick and applies
+// what they asked for, their orders are given in the next tick
+impl<'s> System<'s> for ScriptSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, SimulationId>,
+        ReadStorage<'s, Minion>,
+        ReadStorage<'s, Building>,
+        ReadStorage<'s, Team>,
+        ReadStorage<'s, GridPosition>,
+        Read<'s, WorldBorders>,
+        Read<'s, ArenaConfig>,
+        Read<'s, TerrainMap>,
+        // not inserted on the dedicated server
+        Option<Read<'s, MinionAssets>>,
+        Read<'s, UnitRegistry>,
+        Read<'s, Treasury>,
+        Read<'s, LazyUpdate>,
+        Write<'s, Simulation>,
+        Write<'s, EventChannel<Order>>,
+        Option<Write<'s, Scripts>>,
+        // not inserted on the dedicated server
+        Option<Write<'s, ScriptMessages>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            ids,
+            minions,
+            buildings,
+            teams,
+            positions,
+            world_borders,
+            arena_config,
+            terrain,
+            minion_assets,
+            unit_registry,
+            treasury,
+            lazy,
+            mut simulation,
+            mut orders,
+            scripts,
+            mut messages,
+        ): Self::SystemData,
+    ) {
+        let mut scripts = match scripts {
+            Some(scripts) => scripts,
+            None => return,
+        };
+        if scripts.is_empty() {
+            return;
+        }
+        let tick = simulation.tick();
+        let topology = world_borders.topology;
+
+        let unit_tiles: BTreeMap<u32, (u8, (i16, i16))> = (&ids, &minions, &teams, &positions)
+            .join()
+            .map(|(id, _, team, position)| (id.0, (team.0, position.tile(topology))))
+            .collect();
+        {
+            let mut context = scripts.context();
+            context.tick = tick;
+            // the terrain never changes during a match
+            if context.terrain.width() != terrain.width() {
+                context.terrain = terrain.clone();
+            }
+            context.units = HashMap::new();
+            for (&id, &(_, tile)) in &unit_tiles {
+                context.units.entry(tile).or_default().push(id);
+            }
+            context.teams = (&ids, &teams)
+                .join()
+                .map(|(id, team)| (id.0, team.0))
+                .collect();
+            context.buildings = (&ids, &buildings)
+                .join()
+                .map(|(id, building)| (id.0, *building))
+                .collect();
+            context.buildings.sort_by_key(|&(id, _)| id);
+            context.gold = treasury.balances();
+        }
+
+        scripts.start();
+        // the events in the order of the SimulationIds, so every client calls the same
+        let previous = mem::replace(&mut scripts.unit_tiles, unit_tiles.clone());
+        for (&id, &(team, _)) in &previous {
+            if !unit_tiles.contains_key(&id) {
+                scripts.call("on_unit_died", (id as i64, team as i64));
+            }
+        }
+        for (&id, &(team, (x, z))) in &unit_tiles {
+            let moved = previous.get(&id).map_or(false, |&(_, tile)| tile != (x, z));
+            if moved {
+                scripts.call(
+                    "on_tile_entered",
+                    (id as i64, team as i64, x as i64, z as i64),
+                );
+            }
+        }
+        for name in scripts.elapsed_timers(tick) {
+            scripts.call("on_timer", (name,));
+        }
+
+        let commands = mem::take(&mut scripts.context().commands);
+        let entity_of = entities_by_id(&entities, &ids);
+        for command in commands {
+            match command {
+                ScriptCommand::Spawn {
+                    unit_type,
+                    team,
+                    x,
+                    z,
+                } => {
+                    if !world_borders.contains_tile(x, z) {
+                        warn!("a script spawned {} outside of the arena", unit_type);
+                        continue;
+                    }
+                    let builder = unit_registry.spawn(
+                        &lazy,
+                        &entities,
+                        &unit_type,
+                        team,
+                        GridPosition::center(topology, (x, z)),
+                        minion_assets
+                            .as_ref()
+                            .map(|minion_assets| minion_assets.team_material(team)),
+                    );
+                    match builder {
+                        Some(builder) => {
+                            let transform = unit_transform(
+                                &world_borders,
+                                &terrain,
+                                arena_config.tile_size,
+                                (x, z),
+                            );
+                            builder.with(transform).with(simulation.new_id()).build();
+                        }
+                        None => warn!("a script spawned the unknown unit {}", unit_type),
+                    }
+                }
+                ScriptCommand::Order { team, kind } => {
+                    if let Some(kind) = kind.map_entities(|id| entity_of.get(&id).copied()) {
+                        orders.single_write(Order::scripted(team, kind));
+                    }
+                }
+                ScriptCommand::Message(text) => {
+                    info!("{}", text);
+                    if let Some(messages) = &mut messages {
+                        messages.push(text);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use amethyst::{
+    core::{SystemDesc, Time, Transform},
+    ecs::{
+        Entities, Entity, Join, Read, ReadStorage, System, SystemData, World, Write, WriteStorage,
+    },
+    input::{InputEvent, InputHandler},
+    shrev::{EventChannel, ReaderId},
+};
+
+use crate::{
+    components::{Building, CameraControlTag, Minion, ProductionQueue, Team},
+    config::ArenaConfig,
+    input::{ActionBinding, MovementBindingTypes},
+    minions::WorldBorders,
+    orders::{LocalOrders, Order, OrderKind},
+    resources::{ControlGroups, HoveredTile, LocalPlayer, Selection},
+    systems::camera::{centroid, look_at},
+};
+
+// seconds between the two presses of a double-click or double-tap
+const DOUBLE_PRESS_SECONDS: f64 = 0.3;
+
+#[derive(Default, Debug)]
+pub struct SelectionSystemDesc;
+
+impl<'a, 'b> SystemDesc<'a, 'b, SelectionSystem> for SelectionSystemDesc {
+    fn build(self, world: &mut World) -> SelectionSystem {
+        <SelectionSystem as System<'_>>::SystemData::setup(world);
+        let reader_id = world
+            .fetch_mut::<EventChannel<InputEvent<MovementBindingTypes>>>()
+            .register_reader();
+        SelectionSystem {
+            reader_id,
+            last_select: None,
+            last_recall: None,
+        }
+    }
+}
+
+// this system selects the entities on the hovered tile and translates
+// the actions of the player into orders for the selection
+pub struct SelectionSystem {
+    reader_id: ReaderId<InputEvent<MovementBindingTypes>>,
+    // tile and time of the last click, to detect double-clicks
+    last_select: Option<((i16, i16), f64)>,
+    // control group and time of the last recall, to detect double-taps
+    last_recall: Option<(u8, f64)>,
+}
+
+impl<'s> System<'s> for SelectionSystem {
+    type SystemData = (
+        Entities<'s>,
+        Read<'s, EventChannel<InputEvent<MovementBindingTypes>>>,
+        Read<'s, InputHandler<MovementBindingTypes>>,
+        Read<'s, Time>,
+        Read<'s, HoveredTile>,
+        Read<'s, LocalPlayer>,
+        Read<'s, WorldBorders>,
+        Read<'s, ArenaConfig>,
+        ReadStorage<'s, Building>,
+        ReadStorage<'s, ProductionQueue>,
+        ReadStorage<'s, Minion>,
+        ReadStorage<'s, Team>,
+        ReadStorage<'s, CameraControlTag>,
+        WriteStorage<'s, Transform>,
+        Write<'s, Selection>,
+        Write<'s, ControlGroups>,
+        Write<'s, LocalOrders>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            input_events,
+            input,
+            time,
+            hovered_tile,
+            local_player,
+            world_borders,
+            arena_config,
+            buildings,
+            production_queues,
+            minions,
+            teams,
+            camera_tags,
+            mut transforms,
+            mut selection,
+            mut control_groups,
+            mut orders,
+        ): Self::SystemData,
+    ) {
+        let team = local_player.team;
+        selection
+            .entities
+            .retain(|entity| entities.is_alive(*entity));
+        control_groups.retain(|entity| entities.is_alive(entity));
+        let now = time.absolute_real_time_seconds();
+
+        for event in input_events.read(&mut self.reader_id) {
+            let action = match event {
+                InputEvent::ActionPressed(action) => action,
+                _ => continue,
+            };
+            match action {
+                ActionBinding::Select => {
+                    let (x, z) = match hovered_tile.0 {
+                        Some(tile) => tile,
+                        None => {
+                            selection.clear();
+                            continue;
+                        }
+                    };
+
+                    // buildings are preferred over the minions standing in front of them
+                    let building = (&entities, &buildings, &teams)
+                        .join()
+                        .find(|(_, building, owner)| owner.0 == team && building.contains(x, z))
+                        .map(|(entity, _, _)| entity);
+                    if let Some(building) = building {
+                        selection.set(vec![building]);
+                        self.last_select = None;
+                        continue;
+                    }
+
+                    // a double-click selects every unit of the clicked type
+                    let double_click = self.last_select.map_or(false, |(tile, time)| {
+                        tile == (x, z) && now - time <= DOUBLE_PRESS_SECONDS
+                    });
+                    let clicked_type = selection
+                        .entities
+                        .first()
+                        .and_then(|entity| minions.get(*entity))
+                        .map(|minion| minion.unit_type.clone());
+                    if let (true, Some(unit_type)) = (double_click, clicked_type) {
+                        let units = (&entities, &minions, &teams)
+                            .join()
+                            .filter(|(_, minion, owner)| {
+                                owner.0 == team && minion.unit_type == unit_type
+                            })
+                            .map(|(entity, _, _)| entity)
+                            .collect();
+                        selection.set(units);
+                        self.last_select = None;
+                        continue;
+                    }
+                    self.last_select = Some(((x, z), now));
+
+                    let units = (&entities, &minions, &teams, &transforms)
+                        .join()
+                        .filter(|(_, _, owner, transform)| {
+                            let translation = transform.translation();
+                            owner.0 == team
+                                && world_borders.tile_at(
+                                    translation.x,
+                                    translation.z,
+                                    arena_config.tile_size,
+                                ) == Some((x, z))
+                        })
+                        .map(|(entity, _, _, _)| entity)
+                        .collect();
+                    selection.set(units);
+                }
+                ActionBinding::Command => {
+                    let (x, z) = match hovered_tile.0 {
+                        Some(tile) => tile,
+                        None => continue,
+                    };
+                    let units: Vec<_> = selection
+                        .entities
+                        .iter()
+                        .copied()
+                        .filter(|entity| minions.contains(*entity))
+                        .collect();
+                    if !units.is_empty() {
+                        orders.push(Order::new(team, OrderKind::Move { units, x, z }));
+                    }
+                    for building in selection.entities.iter().copied() {
+                        if production_queues.contains(building) {
+                            orders.push(Order::new(
+                                team,
+                                OrderKind::SetRallyPoint { building, x, z },
+                            ));
+                        }
+                    }
+                }
+                ActionBinding::BuildTower => {
+                    if let Some((x, z)) = hovered_tile.0 {
+                        orders.push(Order::new(team, OrderKind::BuildTower { x, z }));
+                    }
+                }
+                ActionBinding::AssignControlGroup(group) => {
+                    control_groups.assign(*group, selection.entities.clone());
+                }
+                ActionBinding::ControlGroup(group) => {
+                    // the key is part of the combination which assigns the group
+                    let assigning = input
+                        .action_is_down(&ActionBinding::AssignControlGroup(*group))
+                        .unwrap_or(false);
+                    let group_entities = control_groups.get(*group);
+                    if assigning || group_entities.is_empty() {
+                        continue;
+                    }
+                    selection.set(group_entities.to_vec());
+
+                    let double_tap = self.last_recall.map_or(false, |(last, time)| {
+                        last == *group && now - time <= DOUBLE_PRESS_SECONDS
+                    });
+                    if double_tap {
+                        center_camera(group_entities, &camera_tags, &mut transforms);
+                        self.last_recall = None;
+                    } else {
+                        self.last_recall = Some((*group, now));
+                    }
+                }
+                ActionBinding::Cancel => {
+                    for building in selection.entities.iter().copied() {
+                        if production_queues.contains(building) {
+                            orders.push(Order::new(team, OrderKind::CancelTraining { building }));
+                        }
+                    }
+                }
+                ActionBinding::CommandCard(slot) => {
+                    let card = selection.entities.iter().find_map(|entity| {
+                        production_queues
+                            .get(*entity)
+                            .map(|queue| (*entity, queue.options.get(*slot as usize)))
+                    });
+                    if let Some((building, Some(unit_type))) = card {
+                        orders.push(Order::new(
+                            team,
+                            OrderKind::Train {
+                                building,
+                                unit_type: unit_type.clone(),
+                            },
+                        ));
+                    }
+                }
+                // the other systems handle these
+                ActionBinding::StoreBookmark(_)
+                | ActionBinding::Bookmark(_)
+                | ActionBinding::JumpToAlert
+                | ActionBinding::FollowSelection
+                | ActionBinding::Screenshot
+                | ActionBinding::ToggleDebugLayer(_)
+                | ActionBinding::PerformanceOverlay
+                | ActionBinding::RecordTrace
+                | ActionBinding::Tool(_)
+                | ActionBinding::BrushLarger
+                | ActionBinding::BrushSmaller
+                | ActionBinding::Undo
+                | ActionBinding::Redo
+                | ActionBinding::Save
+                | ActionBinding::Pause
+                | ActionBinding::Slower
+                | ActionBinding::Faster
+                | ActionBinding::SeekBack
+                | ActionBinding::SeekForward => {}
+            }
+        }
+    }
+}
+
+// moves the camera to the centre of the entities
+fn center_camera(
+    entities: &[Entity],
+    camera_tags: &ReadStorage<'_, CameraControlTag>,
+    transforms: &mut WriteStorage<'_, Transform>,
+) {
+    let center = match centroid(entities, transforms) {
+        Some(center) => center,
+        None => return,
+    };
+    for (transform, _) in (transforms, camera_tags).join() {
+        look_at(transform, center.x, center.y, center.z);
+    }
+}
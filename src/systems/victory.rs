@@ -0,0 +1,256 @@
+use amethyst::{
+    derive::SystemDesc,
+    ecs::{Join, Read, ReadStorage, System, SystemData, Write},
+};
+use log::info;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{
+    components::{Attacker, Building, GridPosition, Minion, Team},
+    config::{ArenaConfig, PlayerConfig, VictoryCondition},
+    minions::WorldBorders,
+    resources::{Defense, PlayerResult, Simulation, Treasury, Victory},
+    simulation,
+};
+
+#[derive(SystemDesc)]
+pub struct VictorySystem;
+
+// this system checks the victory conditions of the arena after every tick
+// and decides the match once a team meets one
+impl<'s> System<'s> for VictorySystem {
+    type SystemData = (
+        ReadStorage<'s, Building>,
+        ReadStorage<'s, Minion>,
+        ReadStorage<'s, Team>,
+        ReadStorage<'s, GridPosition>,
+        ReadStorage<'s, Attacker>,
+        Read<'s, WorldBorders>,
+        Read<'s, ArenaConfig>,
+        Read<'s, PlayerConfig>,
+        Read<'s, Treasury>,
+        Read<'s, Simulation>,
+        Option<Read<'s, Defense>>,
+        Write<'s, Victory>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            buildings,
+            minions,
+            teams,
+            positions,
+            attackers,
+            world_borders,
+            arena_config,
+            player_config,
+            treasury,
+            simulation,
+            defense,
+            mut victory,
+        ): Self::SystemData,
+    ) {
+        if victory.is_over() || arena_config.victory.is_empty() {
+            return;
+        }
+        let victory = &mut *victory;
+        let topology = world_borders.topology;
+        // ticks finished after this one
+        let elapsed = simulation.tick() + 1;
+
+        let mut building_counts = HashMap::new();
+        for (_, team) in (&buildings, &teams).join() {
+            *building_counts.entry(team.0).or_insert(0) += 1;
+        }
+        let mut unit_counts = HashMap::new();
+        // teams with units on each tile
+        let mut occupants: HashMap<(i16, i16), BTreeSet<u8>> = HashMap::new();
+        for (_, team, position) in (&minions, &teams, &positions).join() {
+            *unit_counts.entry(team.0).or_insert(0) += 1;
+            occupants
+                .entry(position.tile(topology))
+                .or_default()
+                .insert(team.0);
+        }
+
+        let mut winners = BTreeSet::new();
+        // true once the match ends, even if nobody won
+        let mut decided = false;
+        for (index, condition) in arena_config.victory.iter().enumerate() {
+            match condition {
+                VictoryCondition::DestroyBuildings => {
+                    for team in 0..player_config.teams {
+                        if !building_counts.contains_key(&team) && victory.defeated.insert(team) {
+                            info!("team {} lost its last building", team);
+                        }
+                    }
+                }
+                VictoryCondition::HoldControlPoints { points, seconds } => {
+                    // a point with units of several teams belongs to nobody
+                    let mut holders = points.iter().map(|point| {
+                        occupants
+                            .get(point)
+                            .filter(|teams| teams.len() == 1)
+                            .and_then(|teams| teams.iter().next().copied())
+                    });
+                    let first = holders.next().flatten();
+                    let holder = first.filter(|&team| holders.all(|other| other == Some(team)));
+                    for team in 0..player_config.teams {
+                        let held = victory.held.entry((index, team)).or_insert(0);
+                        if holder == Some(team) && !victory.defeated.contains(&team) {
+                            *held += 1;
+                            if *held >= simulation::ticks(*seconds) {
+                                winners.insert(team);
+                            }
+                        } else {
+                            *held = 0;
+                        }
+                    }
+                }
+                VictoryCondition::Survive { seconds } => {
+                    if elapsed >= u64::from(simulation::ticks(*seconds)) {
+                        winners.extend(
+                            (0..player_config.teams)
+                                .filter(|team| !victory.defeated.contains(team)),
+                        );
+                    }
+                }
+                VictoryCondition::SurviveWaves => {
+                    let (defense, config) = match (&defense, &arena_config.defense) {
+                        (Some(defense), Some(config)) => (defense, config),
+                        _ => continue,
+                    };
+                    let defenders =
+                        (0..player_config.teams).filter(|&team| team != config.attackers);
+                    if defense.lives == 0 {
+                        victory.defeated.extend(defenders);
+                        decided = true;
+                    } else if defense.waves_over(config)
+                        && simulation.tick() > defense.next_spawn
+                        && attackers.join().next().is_none()
+                    {
+                        winners.extend(defenders.filter(|team| !victory.defeated.contains(team)));
+                    }
+                }
+                VictoryCondition::Gold { amount } => {
+                    winners.extend((0..player_config.teams).filter(|&team| {
+                        !victory.defeated.contains(&team) && treasury.gold(team) >= *amount
+                    }));
+                }
+            }
+            if decided || !winners.is_empty() {
+                break;
+            }
+        }
+
+        // the last team left wins, nobody does if the last teams fall in the same tick
+        let remaining: BTreeSet<u8> = (0..player_config.teams)
+            .filter(|team| !victory.defeated.contains(team))
+            .collect();
+        let over =
+            decided || !winners.is_empty() || (player_config.teams > 1 && remaining.len() <= 1);
+        if !over {
+            return;
+        }
+        if winners.is_empty() {
+            winners = remaining;
+        }
+
+        let results: Vec<PlayerResult> = (0..player_config.teams)
+            .map(|team| PlayerResult {
+                team,
+                won: winners.contains(&team),
+                gold: treasury.gold(team),
+                units: unit_counts.get(&team).copied().unwrap_or(0),
+                buildings: building_counts.get(&team).copied().unwrap_or(0),
+            })
+            .collect();
+        info!(
+            "the match is over after {}s, winners: {:?}",
+            elapsed / u64::from(simulation::TICKS_PER_SECOND),
+            winners
+        );
+        victory.finish(results);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amethyst::ecs::{Builder, RunNow, World, WorldExt};
+
+    // a world with the buildings of the given teams
+    fn arena(victory: Vec<VictoryCondition>, teams: u8, buildings: &[u8]) -> World {
+        let mut world = World::new();
+        world.register::<Building>();
+        world.register::<Minion>();
+        world.register::<Team>();
+        world.register::<GridPosition>();
+        world.register::<Attacker>();
+        world.insert(WorldBorders::default());
+        world.insert(ArenaConfig {
+            victory,
+            ..ArenaConfig::default()
+        });
+        world.insert(PlayerConfig {
+            teams,
+            starting_gold: 0,
+        });
+        world.insert(Treasury::new(teams, 0));
+        world.insert(Simulation::default());
+        world.insert(Victory::default());
+        for (x, &team) in buildings.iter().enumerate() {
+            world
+                .create_entity()
+                .with(Building::new(x as i16, 0, 1, 1))
+                .with(Team(team))
+                .build();
+        }
+        world
+    }
+
+    fn winners(world: &World) -> Option<Vec<u8>> {
+        world.read_resource::<Victory>().results().map(|results| {
+            results
+                .iter()
+                .filter(|result| result.won)
+                .map(|result| result.team)
+                .collect()
+        })
+    }
+
+    #[test]
+    fn the_last_team_with_buildings_wins() {
+        let world = arena(vec![VictoryCondition::DestroyBuildings], 2, &[0, 1, 1]);
+        VictorySystem.run_now(&world);
+        assert_eq!(winners(&world), None);
+
+        let world = arena(vec![VictoryCondition::DestroyBuildings], 2, &[1, 1]);
+        VictorySystem.run_now(&world);
+        assert_eq!(winners(&world), Some(vec![1]));
+        let victory = world.read_resource::<Victory>();
+        assert!(victory.defeated.contains(&0));
+        assert_eq!(victory.results().unwrap()[1].buildings, 2);
+    }
+
+    #[test]
+    fn the_teams_left_survive() {
+        let conditions = vec![
+            VictoryCondition::DestroyBuildings,
+            VictoryCondition::Survive { seconds: 1.0 },
+        ];
+        // team 2 has no buildings from the start
+        let world = arena(conditions, 3, &[0, 1]);
+        VictorySystem.run_now(&world);
+        assert_eq!(winners(&world), None);
+        assert!(world.read_resource::<Victory>().defeated.contains(&2));
+
+        // the tick which completes the first second
+        for _ in 1..simulation::TICKS_PER_SECOND {
+            world.write_resource::<Simulation>().finish_tick(0);
+        }
+        VictorySystem.run_now(&world);
+        assert_eq!(winners(&world), Some(vec![0, 1]));
+    }
+}